@@ -35,3 +35,89 @@ fn bench_strings(bencher: &mut Bencher, size: usize) {
         <Vec<String>>::deserialize(&mut rmp_serde::Deserializer::new(&buf[..])).unwrap();
     })
 }
+
+#[bench]
+fn bench_ignored_strings_10000(bencher: &mut Bencher) {
+    let vec: Vec<String> = ::std::iter::repeat("abcdefghijklmnopqrstuvwxyz".into())
+        .take(10000)
+        .collect();
+
+    let mut buf = Vec::new();
+    vec.serialize(&mut rmp_serde::Serializer::new(&mut buf)).unwrap();
+
+    bencher.iter(|| {
+        serde::de::IgnoredAny::deserialize(&mut rmp_serde::Deserializer::from_slice(&buf)).unwrap();
+    })
+}
+
+#[bench]
+fn bench_nested_unknown_len_1000(bencher: &mut Bencher) {
+    struct UnknownLen<'a, T>(&'a [T]);
+
+    impl<T: Serialize> Serialize for UnknownLen<'_, T> {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            se.collect_seq(self.0.iter().filter(|_| true))
+        }
+    }
+
+    let strings: Vec<String> = ::std::iter::repeat("abcdefghijklmnopqrstuvwxyz".into())
+        .take(100)
+        .collect();
+    let inner: Vec<_> = (0..1000).map(|_| UnknownLen(&strings)).collect();
+
+    bencher.iter(|| {
+        rmp_serde::to_vec(&UnknownLen(&inner)).unwrap();
+    })
+}
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+struct Point<'a> {
+    id: u64,
+    offset: i32,
+    x: f64,
+    y: f64,
+    visible: bool,
+    label: &'a str,
+}
+
+fn points(size: u64) -> Vec<Point<'static>> {
+    (0..size)
+        .map(|i| Point { id: i * 1000, offset: -(i as i32), x: 0.5, y: i as f64, visible: i % 2 == 0, label: "point" })
+        .collect()
+}
+
+#[bench]
+fn bench_from_slice_structs_1000(bencher: &mut Bencher) {
+    let buf = rmp_serde::to_vec(&points(1000)).unwrap();
+
+    bencher.iter(|| {
+        rmp_serde::from_slice::<Vec<Point<'_>>>(&buf).unwrap();
+    })
+}
+
+#[bench]
+fn bench_to_vec_structs_1000(bencher: &mut Bencher) {
+    let points = points(1000);
+
+    bencher.iter(|| {
+        rmp_serde::to_vec(&points).unwrap();
+    })
+}
+
+#[bench]
+fn bench_to_vec_ints_10000(bencher: &mut Bencher) {
+    let vec: Vec<u64> = (0..10000).map(|i| i * 100_000).collect();
+
+    bencher.iter(|| {
+        rmp_serde::to_vec(&vec).unwrap();
+    })
+}
+
+#[bench]
+fn bench_to_vec_bools_10000(bencher: &mut Bencher) {
+    let vec: Vec<bool> = (0..10000).map(|i| i % 3 == 0).collect();
+
+    bencher.iter(|| {
+        rmp_serde::to_vec(&vec).unwrap();
+    })
+}
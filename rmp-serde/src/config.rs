@@ -1,8 +1,19 @@
 //! Change MessagePack behavior with configuration wrappers.
+#[cfg(feature = "std")]
+use std::cmp;
+#[cfg(feature = "std")]
+use std::io::{self, ErrorKind, Read, Write};
+
 use rmp::encode;
+#[cfg(feature = "std")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "std")]
+use serde::Deserialize;
 use serde::{Serialize, Serializer};
 
 use crate::encode::{Error, UnderlyingWrite};
+#[cfg(feature = "std")]
+use crate::decode;
 
 /// Represents configuration that dicatates what the serializer does.
 ///
@@ -15,6 +26,8 @@ impl<T: sealed::SerializerConfig> SerializerConfig for T {}
 mod sealed {
     use serde::{Serialize, Serializer};
 
+    use super::BytesMode;
+
     use crate::encode::{Error, UnderlyingWrite};
 
     /// This is the inner trait - the real SerializerConfig.
@@ -22,12 +35,12 @@ mod sealed {
     /// This hack disallows external implementations and usage of SerializerConfig and thus
     /// allows us to change SerializerConfig methods freely without breaking backwards compatibility.
     pub trait SerializerConfig: Copy {
-        fn write_struct_len<S>(ser: &mut S, len: usize) -> Result<(), Error>
+        fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
         where
             S: UnderlyingWrite,
             for<'a> &'a mut S: Serializer<Ok = (), Error = Error>;
 
-        fn write_struct_field<S, T>(ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
+        fn write_struct_field<S, T>(self, ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
         where
             S: UnderlyingWrite,
             for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
@@ -37,6 +50,7 @@ mod sealed {
         ///
         /// Used in `Serializer::serialize_*_variant` methods.
         fn write_variant_ident<S>(
+            self,
             ser: &mut S,
             variant_index: u32,
             variant: &'static str,
@@ -47,7 +61,66 @@ mod sealed {
 
         /// Determines the value of `Serializer::is_human_readable` and
         /// `Deserializer::is_human_readable`.
-        fn is_human_readable() -> bool;
+        fn is_human_readable(self) -> bool;
+
+        /// Determines whether map entries are sorted and floats are normalized, so that equal
+        /// data is always encoded into identical bytes.
+        fn is_canonical(self) -> bool;
+
+        /// Determines whether strings and bytes are written and read as the raw type of the old
+        /// MessagePack specification, i.e. without `str8` and `bin` markers.
+        fn is_legacy_raw(self) -> bool;
+
+        /// Determines which sequences of `u8` are written as `bin`.
+        fn bytes_mode(self) -> BytesMode;
+
+        /// Determines whether the deserializer coerces values of a different type, as long as no
+        /// information is lost.
+        fn is_lenient(self) -> bool;
+
+        /// Determines whether the deserializer rejects non-canonical input, such as invalid UTF-8 or
+        /// non-minimal encodings.
+        fn is_strict(self) -> bool;
+
+        /// Determines whether the deserializer accepts the given ext type.
+        fn accepts_ext_type(self, tag: i8) -> bool;
+    }
+}
+
+/// Determines which sequences of `u8` are written as `bin` rather than as an array of integers.
+///
+/// Types like `Vec<u8>` and `[u8; N]` are serialized through serde as sequences and tuples, so by
+/// default they take up to twice as much space as `bin` and are slower to decode. Wrapping them in
+/// `serde_bytes` is the usual fix, but it isn't possible for types you don't control.
+///
+/// Sequences are detected element by element: the serializer holds the elements back as long as
+/// they are `u8`, and falls back to an array at the first element of another type. Empty
+/// sequences are always written as empty arrays, since their element type is unknown.
+///
+/// To find out whether an element is a `u8`, it is first serialized into a probe that rejects
+/// everything else. The first element of another type is thus serialized twice, once into the
+/// probe and once for real, so its `Serialize` impl should have no side effects and be cheap to
+/// run up to its first call to the serializer. All the other elements are serialized once.
+///
+/// A deserializer configured with any mode but `Normal` accepts `bin` wherever a sequence or a
+/// tuple is expected, so that the output of every mode can be read back with the same settings.
+/// With `Normal`, the default, only types that ask for bytes accept `bin`, and e.g. a `Vec<u16>`
+/// rejects it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BytesMode {
+    /// Writes `bin` only for types that ask for it, e.g. using `serde_bytes`. This is the default.
+    Normal,
+    /// Also writes sequences of `u8`, such as `Vec<u8>`, `&[u8]` or `VecDeque<u8>`, as `bin`.
+    ForceIterables,
+    /// Also writes tuples of `u8`, such as `[u8; N]` or `(u8, u8)`, as `bin`, in addition to
+    /// sequences.
+    ForceAll,
+}
+
+impl Default for BytesMode {
+    #[inline]
+    fn default() -> Self {
+        BytesMode::Normal
     }
 }
 
@@ -63,7 +136,7 @@ mod sealed {
 pub struct DefaultConfig;
 
 impl sealed::SerializerConfig for DefaultConfig {
-    fn write_struct_len<S>(ser: &mut S, len: usize) -> Result<(), Error>
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
     where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
@@ -74,7 +147,7 @@ impl sealed::SerializerConfig for DefaultConfig {
     }
 
     #[inline]
-    fn write_struct_field<S, T>(ser: &mut S, _key: &'static str, value: &T) -> Result<(), Error>
+    fn write_struct_field<S, T>(self, ser: &mut S, _key: &'static str, value: &T) -> Result<(), Error>
     where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
@@ -85,6 +158,7 @@ impl sealed::SerializerConfig for DefaultConfig {
 
     #[inline]
     fn write_variant_ident<S>(
+        self,
         ser: &mut S,
         _variant_index: u32,
         variant: &'static str,
@@ -97,9 +171,39 @@ impl sealed::SerializerConfig for DefaultConfig {
     }
 
     #[inline(always)]
-    fn is_human_readable() -> bool {
+    fn is_human_readable(self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        BytesMode::Normal
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn is_strict(self) -> bool {
         false
     }
+
+    #[inline(always)]
+    fn accepts_ext_type(self, _tag: i8) -> bool {
+        true
+    }
 }
 
 /// Config wrapper, that overrides struct serialization by packing as a map with field names.
@@ -124,7 +228,7 @@ impl<C> sealed::SerializerConfig for StructMapConfig<C>
 where
     C: sealed::SerializerConfig,
 {
-    fn write_struct_len<S>(ser: &mut S, len: usize) -> Result<(), Error>
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
     where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
@@ -134,18 +238,19 @@ where
         Ok(())
     }
 
-    fn write_struct_field<S, T>(ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
+    fn write_struct_field<S, T>(self, ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
     where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
         T: ?Sized + Serialize,
     {
-        encode::write_str(ser.get_mut(), key)?;
+        ser.serialize_str(key)?;
         value.serialize(ser)
     }
 
     #[inline]
     fn write_variant_ident<S>(
+        self,
         ser: &mut S,
         variant_index: u32,
         variant: &'static str,
@@ -154,12 +259,42 @@ where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
     {
-        C::write_variant_ident(ser, variant_index, variant)
+        self.0.write_variant_ident(ser, variant_index, variant)
+    }
+
+    #[inline(always)]
+    fn is_human_readable(self) -> bool {
+        self.0.is_human_readable()
+    }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        self.0.is_canonical()
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        self.0.is_legacy_raw()
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        self.0.bytes_mode()
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        self.0.is_lenient()
     }
 
     #[inline(always)]
-    fn is_human_readable() -> bool {
-        C::is_human_readable()
+    fn is_strict(self) -> bool {
+        self.0.is_strict()
+    }
+
+    #[inline(always)]
+    fn accepts_ext_type(self, tag: i8) -> bool {
+        self.0.accepts_ext_type(tag)
     }
 }
 
@@ -180,7 +315,7 @@ impl<C> sealed::SerializerConfig for StructTupleConfig<C>
 where
     C: sealed::SerializerConfig,
 {
-    fn write_struct_len<S>(ser: &mut S, len: usize) -> Result<(), Error>
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
     where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
@@ -191,7 +326,7 @@ where
     }
 
     #[inline]
-    fn write_struct_field<S, T>(ser: &mut S, _key: &'static str, value: &T) -> Result<(), Error>
+    fn write_struct_field<S, T>(self, ser: &mut S, _key: &'static str, value: &T) -> Result<(), Error>
     where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
@@ -202,6 +337,7 @@ where
 
     #[inline]
     fn write_variant_ident<S>(
+        self,
         ser: &mut S,
         variant_index: u32,
         variant: &'static str,
@@ -210,12 +346,42 @@ where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
     {
-        C::write_variant_ident(ser, variant_index, variant)
+        self.0.write_variant_ident(ser, variant_index, variant)
+    }
+
+    #[inline(always)]
+    fn is_human_readable(self) -> bool {
+        self.0.is_human_readable()
+    }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        self.0.is_canonical()
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        self.0.is_legacy_raw()
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        self.0.bytes_mode()
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        self.0.is_lenient()
+    }
+
+    #[inline(always)]
+    fn is_strict(self) -> bool {
+        self.0.is_strict()
     }
 
     #[inline(always)]
-    fn is_human_readable() -> bool {
-        C::is_human_readable()
+    fn accepts_ext_type(self, tag: i8) -> bool {
+        self.0.accepts_ext_type(tag)
     }
 }
 
@@ -237,26 +403,27 @@ where
     C: sealed::SerializerConfig,
 {
     #[inline]
-    fn write_struct_len<S>(ser: &mut S, len: usize) -> Result<(), Error>
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
     where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
     {
-        C::write_struct_len(ser, len)
+        self.0.write_struct_len(ser, len)
     }
 
     #[inline]
-    fn write_struct_field<S, T>(ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
+    fn write_struct_field<S, T>(self, ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
     where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
         T: ?Sized + Serialize,
     {
-        C::write_struct_field(ser, key, value)
+        self.0.write_struct_field(ser, key, value)
     }
 
     #[inline]
     fn write_variant_ident<S>(
+        self,
         ser: &mut S,
         variant_index: u32,
         variant: &'static str,
@@ -265,13 +432,43 @@ where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
     {
-        C::write_variant_ident(ser, variant_index, variant)
+        self.0.write_variant_ident(ser, variant_index, variant)
     }
 
     #[inline(always)]
-    fn is_human_readable() -> bool {
+    fn is_human_readable(self) -> bool {
         true
     }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        self.0.is_canonical()
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        self.0.is_legacy_raw()
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        self.0.bytes_mode()
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        self.0.is_lenient()
+    }
+
+    #[inline(always)]
+    fn is_strict(self) -> bool {
+        self.0.is_strict()
+    }
+
+    #[inline(always)]
+    fn accepts_ext_type(self, tag: i8) -> bool {
+        self.0.accepts_ext_type(tag)
+    }
 }
 
 /// Config wrapper that overrides `Serializer::is_human_readable` and
@@ -292,26 +489,27 @@ where
     C: sealed::SerializerConfig,
 {
     #[inline]
-    fn write_struct_len<S>(ser: &mut S, len: usize) -> Result<(), Error>
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
     where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
     {
-        C::write_struct_len(ser, len)
+        self.0.write_struct_len(ser, len)
     }
 
     #[inline]
-    fn write_struct_field<S, T>(ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
+    fn write_struct_field<S, T>(self, ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
     where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
         T: ?Sized + Serialize,
     {
-        C::write_struct_field(ser, key, value)
+        self.0.write_struct_field(ser, key, value)
     }
 
     #[inline]
     fn write_variant_ident<S>(
+        self,
         ser: &mut S,
         variant_index: u32,
         variant: &'static str,
@@ -320,11 +518,1046 @@ where
         S: UnderlyingWrite,
         for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
     {
-        C::write_variant_ident(ser, variant_index, variant)
+        self.0.write_variant_ident(ser, variant_index, variant)
     }
 
     #[inline(always)]
-    fn is_human_readable() -> bool {
+    fn is_human_readable(self) -> bool {
         false
     }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        self.0.is_canonical()
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        self.0.is_legacy_raw()
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        self.0.bytes_mode()
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        self.0.is_lenient()
+    }
+
+    #[inline(always)]
+    fn is_strict(self) -> bool {
+        self.0.is_strict()
+    }
+
+    #[inline(always)]
+    fn accepts_ext_type(self, tag: i8) -> bool {
+        self.0.accepts_ext_type(tag)
+    }
+}
+
+/// Config wrapper that makes the output deterministic, i.e. equal data is always encoded into
+/// identical bytes.
+///
+/// This configuration:
+/// - Sorts map entries by their encoded key bytes, so that the iteration order of types like
+///   `HashMap` doesn't matter. Maps with duplicate encoded keys are rejected.
+/// - Writes every NaN as the quiet NaN with an empty payload.
+/// - Uses the most compact integer and length encodings, like any other configuration.
+///
+/// Struct fields keep their declaration order, and sequences (including sets) keep their
+/// iteration order, since neither is visible to the serializer as a map. Use ordered collections
+/// like `BTreeSet` for sets.
+#[derive(Copy, Clone, Debug)]
+pub struct CanonicalConfig<C>(C);
+
+impl<C> CanonicalConfig<C> {
+    /// Creates a `CanonicalConfig` inheriting unchanged configuration options from the given configuration.
+    #[inline]
+    pub fn new(inner: C) -> Self {
+        Self(inner)
+    }
+}
+
+impl<C> sealed::SerializerConfig for CanonicalConfig<C>
+where
+    C: sealed::SerializerConfig,
+{
+    #[inline]
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        self.0.write_struct_len(ser, len)
+    }
+
+    #[inline]
+    fn write_struct_field<S, T>(self, ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+        T: ?Sized + Serialize,
+    {
+        self.0.write_struct_field(ser, key, value)
+    }
+
+    #[inline]
+    fn write_variant_ident<S>(
+        self,
+        ser: &mut S,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        self.0.write_variant_ident(ser, variant_index, variant)
+    }
+
+    #[inline(always)]
+    fn is_human_readable(self) -> bool {
+        self.0.is_human_readable()
+    }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        self.0.is_legacy_raw()
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        self.0.bytes_mode()
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        self.0.is_lenient()
+    }
+
+    #[inline(always)]
+    fn is_strict(self) -> bool {
+        self.0.is_strict()
+    }
+
+    #[inline(always)]
+    fn accepts_ext_type(self, tag: i8) -> bool {
+        self.0.accepts_ext_type(tag)
+    }
+}
+
+/// Config wrapper that writes and reads strings and bytes as the raw type of the old MessagePack
+/// specification, for peers that predate the `str8` and `bin` types.
+///
+/// When serializing, this configuration:
+/// - Never emits `str8`: strings shorter than 32 bytes are written as `fixstr`, longer ones as
+///   `str16` or `str32`.
+/// - Writes bytes as raw strings too, since the old specification had no `bin` type.
+///
+/// When deserializing, types asking for bytes receive the payload of a string as bytes, without
+/// any UTF-8 validation, and 128-bit integers are also accepted from a 16-byte `fixstr`.
+///
+/// Ext types have no equivalent in the old specification and are written unchanged.
+#[derive(Copy, Clone, Debug)]
+pub struct LegacyRawConfig<C>(C);
+
+impl<C> LegacyRawConfig<C> {
+    /// Creates a `LegacyRawConfig` inheriting unchanged configuration options from the given configuration.
+    #[inline]
+    pub fn new(inner: C) -> Self {
+        Self(inner)
+    }
+}
+
+impl<C> sealed::SerializerConfig for LegacyRawConfig<C>
+where
+    C: sealed::SerializerConfig,
+{
+    #[inline]
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        self.0.write_struct_len(ser, len)
+    }
+
+    #[inline]
+    fn write_struct_field<S, T>(self, ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+        T: ?Sized + Serialize,
+    {
+        self.0.write_struct_field(ser, key, value)
+    }
+
+    #[inline]
+    fn write_variant_ident<S>(
+        self,
+        ser: &mut S,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        self.0.write_variant_ident(ser, variant_index, variant)
+    }
+
+    #[inline(always)]
+    fn is_human_readable(self) -> bool {
+        self.0.is_human_readable()
+    }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        self.0.is_canonical()
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        self.0.bytes_mode()
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        self.0.is_lenient()
+    }
+
+    #[inline(always)]
+    fn is_strict(self) -> bool {
+        self.0.is_strict()
+    }
+
+    #[inline(always)]
+    fn accepts_ext_type(self, tag: i8) -> bool {
+        self.0.accepts_ext_type(tag)
+    }
+}
+
+/// Config wrapper that chooses which sequences of `u8` are written as `bin`, see [`BytesMode`].
+#[derive(Copy, Clone, Debug)]
+pub struct BytesModeConfig<C>(C, BytesMode);
+
+impl<C> BytesModeConfig<C> {
+    /// Creates a `BytesModeConfig` inheriting unchanged configuration options from the given configuration.
+    #[inline]
+    pub fn new(inner: C, mode: BytesMode) -> Self {
+        Self(inner, mode)
+    }
+}
+
+impl<C> sealed::SerializerConfig for BytesModeConfig<C>
+where
+    C: sealed::SerializerConfig,
+{
+    #[inline]
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        self.0.write_struct_len(ser, len)
+    }
+
+    #[inline]
+    fn write_struct_field<S, T>(self, ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+        T: ?Sized + Serialize,
+    {
+        self.0.write_struct_field(ser, key, value)
+    }
+
+    #[inline]
+    fn write_variant_ident<S>(
+        self,
+        ser: &mut S,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        self.0.write_variant_ident(ser, variant_index, variant)
+    }
+
+    #[inline(always)]
+    fn is_human_readable(self) -> bool {
+        self.0.is_human_readable()
+    }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        self.0.is_canonical()
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        self.0.is_legacy_raw()
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        self.1
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        self.0.is_lenient()
+    }
+
+    #[inline(always)]
+    fn is_strict(self) -> bool {
+        self.0.is_strict()
+    }
+
+    #[inline(always)]
+    fn accepts_ext_type(self, tag: i8) -> bool {
+        self.0.accepts_ext_type(tag)
+    }
+}
+
+/// Config wrapper that makes the deserializer coerce values of a different type, as long as no
+/// information is lost.
+///
+/// This is meant for data from dynamically typed producers. When deserializing:
+/// - Integers are also read from floats with an integral value (`3.0`) and from decimal strings
+///   (`"3"`). Other floats, like `3.5`, NaN or infinities, are rejected.
+/// - Floats are also read from integers that the float type represents exactly, and from strings
+///   like `"3.5"` or `"0.1"` that round-trip through the float type, i.e. that formatting the
+///   parsed float gives back. Other integers, like `2^53 + 1` for `f64`, and strings with more
+///   digits than the float type keeps, like `"0.10000000000000000001"`, are rejected.
+/// - Strings are also read from `bin` holding valid UTF-8.
+/// - Bytes are also read from arrays of integers in `0..=255` and from strings. The elements of
+///   such an array must be integers, they are not coerced from strings or floats in turn.
+///
+/// Serialization is not affected.
+#[derive(Copy, Clone, Debug)]
+pub struct LenientConfig<C>(C);
+
+impl<C> LenientConfig<C> {
+    /// Creates a `LenientConfig` inheriting unchanged configuration options from the given configuration.
+    #[inline]
+    pub fn new(inner: C) -> Self {
+        Self(inner)
+    }
+}
+
+impl<C> sealed::SerializerConfig for LenientConfig<C>
+where
+    C: sealed::SerializerConfig,
+{
+    #[inline]
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        self.0.write_struct_len(ser, len)
+    }
+
+    #[inline]
+    fn write_struct_field<S, T>(self, ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+        T: ?Sized + Serialize,
+    {
+        self.0.write_struct_field(ser, key, value)
+    }
+
+    #[inline]
+    fn write_variant_ident<S>(
+        self,
+        ser: &mut S,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        self.0.write_variant_ident(ser, variant_index, variant)
+    }
+
+    #[inline(always)]
+    fn is_human_readable(self) -> bool {
+        self.0.is_human_readable()
+    }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        self.0.is_canonical()
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        self.0.is_legacy_raw()
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        self.0.bytes_mode()
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn is_strict(self) -> bool {
+        self.0.is_strict()
+    }
+
+    #[inline(always)]
+    fn accepts_ext_type(self, tag: i8) -> bool {
+        self.0.accepts_ext_type(tag)
+    }
+}
+
+/// Config wrapper that makes the deserializer reject anything but the one canonical form of the
+/// data, which is meant for endpoints exposed to untrusted input.
+///
+/// When deserializing, this configuration rejects:
+/// - Strings with invalid UTF-8, instead of handing their payload to types that accept bytes.
+/// - Integers not encoded the way [`rmp::encode::write_uint`] and [`rmp::encode::write_sint`]
+///   would, i.e. using more bytes than needed or a signed marker for a non-negative value.
+/// - Lengths of strings, binaries, arrays, maps and ext types encoded using more bytes than
+///   needed. With [`LegacyRawConfig`], `str16` is allowed for strings of 32 bytes or more.
+/// - Duplicate keys in structs encoded as maps, as well as keys other than strings and integers.
+///   Duplicate string and integer keys are rejected in other maps as well, such as structs with
+///   flattened fields or a `HashMap`.
+/// - Ext types in the range reserved by the specification (`-128..=-1`), except for the
+///   timestamp type (`-1`) and the ones registered using [`StrictConfig::with_ext_type`].
+///
+/// Rejected input fails with a `Syntax` error describing it. `Marker::Reserved` is always
+/// rejected, with or without this configuration.
+///
+/// Serialization is not affected.
+#[derive(Copy, Clone, Debug)]
+pub struct StrictConfig<C>(C, u128);
+
+impl<C> StrictConfig<C> {
+    /// Creates a `StrictConfig` inheriting unchanged configuration options from the given configuration.
+    #[inline]
+    pub fn new(inner: C) -> Self {
+        Self(inner, ext_type_bit(TIMESTAMP_EXT_TYPE))
+    }
+
+    /// Registers an ext type of the reserved range, so that it is accepted.
+    ///
+    /// Ext types outside of the reserved range are always accepted.
+    #[inline]
+    pub fn with_ext_type(mut self, tag: i8) -> Self {
+        self.1 |= ext_type_bit(tag);
+        self
+    }
+}
+
+/// The ext type of timestamps, the only one defined by the specification so far.
+const TIMESTAMP_EXT_TYPE: i8 = -1;
+
+/// Returns the bit that represents the ext type in a set of registered reserved ext types.
+#[inline]
+fn ext_type_bit(tag: i8) -> u128 {
+    if tag < 0 {
+        1 << (-1 - i32::from(tag))
+    } else {
+        0
+    }
+}
+
+#[inline]
+fn is_registered_ext_type(registered: u128, tag: i8) -> bool {
+    tag >= 0 || registered & ext_type_bit(tag) != 0
+}
+
+impl<C> sealed::SerializerConfig for StrictConfig<C>
+where
+    C: sealed::SerializerConfig,
+{
+    #[inline]
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        self.0.write_struct_len(ser, len)
+    }
+
+    #[inline]
+    fn write_struct_field<S, T>(self, ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+        T: ?Sized + Serialize,
+    {
+        self.0.write_struct_field(ser, key, value)
+    }
+
+    #[inline]
+    fn write_variant_ident<S>(
+        self,
+        ser: &mut S,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        self.0.write_variant_ident(ser, variant_index, variant)
+    }
+
+    #[inline(always)]
+    fn is_human_readable(self) -> bool {
+        self.0.is_human_readable()
+    }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        self.0.is_canonical()
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        self.0.is_legacy_raw()
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        self.0.bytes_mode()
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        self.0.is_lenient()
+    }
+
+    #[inline(always)]
+    fn is_strict(self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn accepts_ext_type(self, tag: i8) -> bool {
+        is_registered_ext_type(self.1, tag)
+    }
+}
+
+/// Config assembled at runtime by [`Options`].
+///
+/// Every policy is stored as a plain value, so that it can be chosen from data that is not known
+/// at compile time.
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct RuntimeConfig {
+    struct_map: bool,
+    variant_index: bool,
+    human_readable: bool,
+    canonical: bool,
+    legacy_raw: bool,
+    bytes: BytesMode,
+    lenient: bool,
+    strict: bool,
+    ext_types: u128,
+}
+
+#[cfg(feature = "std")]
+impl sealed::SerializerConfig for RuntimeConfig {
+    fn write_struct_len<S>(self, ser: &mut S, len: usize) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        if self.struct_map {
+            encode::write_map_len(ser.get_mut(), len as u32)?;
+        } else {
+            encode::write_array_len(ser.get_mut(), len as u32)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn write_struct_field<S, T>(self, ser: &mut S, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+        T: ?Sized + Serialize,
+    {
+        if self.struct_map {
+            ser.serialize_str(key)?;
+        }
+        value.serialize(ser)
+    }
+
+    #[inline]
+    fn write_variant_ident<S>(
+        self,
+        ser: &mut S,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error>
+    where
+        S: UnderlyingWrite,
+        for<'a> &'a mut S: Serializer<Ok = (), Error = Error>,
+    {
+        if self.variant_index {
+            ser.serialize_u32(variant_index)
+        } else {
+            ser.serialize_str(variant)
+        }
+    }
+
+    #[inline(always)]
+    fn is_human_readable(self) -> bool {
+        self.human_readable
+    }
+
+    #[inline(always)]
+    fn is_canonical(self) -> bool {
+        self.canonical
+    }
+
+    #[inline(always)]
+    fn is_legacy_raw(self) -> bool {
+        self.legacy_raw
+    }
+
+    #[inline(always)]
+    fn bytes_mode(self) -> BytesMode {
+        self.bytes
+    }
+
+    #[inline(always)]
+    fn is_lenient(self) -> bool {
+        self.lenient
+    }
+
+    #[inline(always)]
+    fn is_strict(self) -> bool {
+        self.strict
+    }
+
+    #[inline(always)]
+    fn accepts_ext_type(self, tag: i8) -> bool {
+        !self.strict || is_registered_ext_type(self.ext_types, tag)
+    }
+}
+
+/// A builder of serialization and deserialization settings that can be chosen at runtime.
+///
+/// Configuration wrappers such as [`StructMapConfig`] fix the behavior in the type of the
+/// `Serializer`, which makes it impossible to pick it based on, for example, a service
+/// configuration file. `Options` gathers the same policies, along with depth and size limits, as
+/// plain values.
+///
+/// The defaults match [`to_vec`](crate::to_vec) and [`from_slice`](crate::from_slice): structs are
+/// written as tuples, enum variants by name, types use their binary representation, the nesting
+/// depth is limited to 1024 levels while deserializing and not limited while serializing, the size
+/// is not limited and trailing bytes are allowed.
+///
+/// # Examples
+///
+/// ```
+/// let opts = rmp_serde::Options::new()
+///     .with_struct_map()
+///     .with_limit(1024)
+///     .reject_trailing_bytes();
+///
+/// let buf = opts.serialize(&(42, "the Answer")).unwrap();
+///
+/// assert_eq!((42, "the Answer"), opts.deserialize(&buf).unwrap());
+/// ```
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug)]
+pub struct Options {
+    config: RuntimeConfig,
+    /// The depth limit set with `with_max_depth`, if any.
+    max_depth: Option<usize>,
+    limit: Option<u64>,
+    reject_trailing_bytes: bool,
+}
+
+#[cfg(feature = "std")]
+impl Default for Options {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Options {
+    /// Creates `Options` with the default settings.
+    #[inline]
+    pub fn new() -> Self {
+        Options {
+            config: RuntimeConfig {
+                struct_map: false,
+                variant_index: false,
+                human_readable: false,
+                canonical: false,
+                legacy_raw: false,
+                bytes: BytesMode::Normal,
+                lenient: false,
+                strict: false,
+                ext_types: ext_type_bit(TIMESTAMP_EXT_TYPE),
+            },
+            max_depth: None,
+            limit: None,
+            reject_trailing_bytes: false,
+        }
+    }
+
+    /// Serializes structs as a map with field names.
+    #[inline]
+    pub fn with_struct_map(mut self) -> Self {
+        self.config.struct_map = true;
+        self
+    }
+
+    /// Serializes structs as a tuple without field names.
+    ///
+    /// This is the default, emitting the most compact representation.
+    #[inline]
+    pub fn with_struct_tuple(mut self) -> Self {
+        self.config.struct_map = false;
+        self
+    }
+
+    /// Identifies enum variants by their index.
+    #[inline]
+    pub fn with_variant_index(mut self) -> Self {
+        self.config.variant_index = true;
+        self
+    }
+
+    /// Identifies enum variants by their name.
+    ///
+    /// This is the default.
+    #[inline]
+    pub fn with_variant_name(mut self) -> Self {
+        self.config.variant_index = false;
+        self
+    }
+
+    /// Makes `Serializer::is_human_readable` and `Deserializer::is_human_readable` return `true`.
+    #[inline]
+    pub fn with_human_readable(mut self) -> Self {
+        self.config.human_readable = true;
+        self
+    }
+
+    /// Makes `Serializer::is_human_readable` and `Deserializer::is_human_readable` return `false`.
+    ///
+    /// This is the default.
+    #[inline]
+    pub fn with_binary(mut self) -> Self {
+        self.config.human_readable = false;
+        self
+    }
+
+    /// Makes the output deterministic, see [`CanonicalConfig`] for details.
+    #[inline]
+    pub fn with_canonical(mut self) -> Self {
+        self.config.canonical = true;
+        self
+    }
+
+    /// Writes and reads strings and bytes as the raw type of the old MessagePack specification,
+    /// see [`LegacyRawConfig`] for details.
+    #[inline]
+    pub fn with_legacy_raw(mut self) -> Self {
+        self.config.legacy_raw = true;
+        self
+    }
+
+    /// Chooses which sequences of `u8` are written as `bin`, see [`BytesMode`] for details.
+    #[inline]
+    pub fn with_bytes(mut self, mode: BytesMode) -> Self {
+        self.config.bytes = mode;
+        self
+    }
+
+    /// Coerces values of a different type while deserializing, as long as no information is lost,
+    /// see [`LenientConfig`] for details.
+    #[inline]
+    pub fn with_lenient(mut self) -> Self {
+        self.config.lenient = true;
+        self
+    }
+
+    /// Rejects non-canonical input while deserializing, see [`StrictConfig`] for details.
+    #[inline]
+    pub fn with_strict(mut self) -> Self {
+        self.config.strict = true;
+        self
+    }
+
+    /// Registers an ext type of the reserved range, so that it is accepted in strict mode, see
+    /// [`StrictConfig::with_ext_type`].
+    #[inline]
+    pub fn with_ext_type(mut self, tag: i8) -> Self {
+        self.config.ext_types |= ext_type_bit(tag);
+        self
+    }
+
+    /// Changes the maximum nesting depth of sequences and maps that is allowed while serializing
+    /// or deserializing.
+    ///
+    /// Without it the depth is limited to 1024 levels while deserializing, like with
+    /// [`from_slice`](crate::from_slice), and not limited while serializing.
+    #[inline]
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Limits the number of bytes that may be written while serializing or read while
+    /// deserializing a single value.
+    ///
+    /// Exceeding the limit results in a `Syntax` error with the message "size limit exceeded".
+    #[inline]
+    pub fn with_limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Removes the size limit.
+    ///
+    /// This is the default.
+    #[inline]
+    pub fn with_no_limit(mut self) -> Self {
+        self.limit = None;
+        self
+    }
+
+    /// Allows input to continue past the end of the deserialized value.
+    ///
+    /// This is the default.
+    #[inline]
+    pub fn allow_trailing_bytes(mut self) -> Self {
+        self.reject_trailing_bytes = false;
+        self
+    }
+
+    /// Fails deserialization with a `Syntax` error if the input continues past the end of the
+    /// deserialized value.
+    #[inline]
+    pub fn reject_trailing_bytes(mut self) -> Self {
+        self.reject_trailing_bytes = true;
+        self
+    }
+
+    /// Serializes the given data structure as a MessagePack byte vector.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to fail, or if the
+    /// encoded value exceeds the size limit.
+    #[inline]
+    pub fn serialize<T>(&self, val: &T) -> Result<Vec<u8>, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        crate::encode::to_vec_with_config(val, self.config, self.max_depth, self.size_limit())
+    }
+
+    /// Returns the number of bytes [`Options::serialize`] would serialize the given data
+    /// structure to, without writing them anywhere.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to fail, if the value
+    /// is nested deeper than the depth limit, or if the encoded value exceeds the size limit.
+    pub fn encoded_size<T>(&self, val: &T) -> Result<usize, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let len = crate::encode::encoded_size_with_config(val, self.config, self.max_depth)?;
+        match self.limit {
+            Some(limit) if len as u64 > limit => Err(Error::Syntax(SIZE_LIMIT_EXCEEDED.into())),
+            _ => Ok(len),
+        }
+    }
+
+    /// Serializes the given data structure as MessagePack into the I/O stream.
+    ///
+    /// The value is encoded in memory first, so nothing is written if serialization fails.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to fail, if writing
+    /// fails, or if the encoded value exceeds the size limit.
+    pub fn to_writer<W, T>(&self, mut wr: W, val: &T) -> Result<(), Error>
+    where
+        W: Write,
+        T: Serialize + ?Sized,
+    {
+        crate::encode::to_writer_with_config(&mut wr, val, self.config, self.max_depth, self.size_limit())
+    }
+
+    /// Returns the size limit as a length in memory.
+    #[inline]
+    fn size_limit(&self) -> Option<usize> {
+        self.limit.map(|limit| usize::try_from(limit).unwrap_or(usize::MAX))
+    }
+
+    /// Deserializes an instance of type `T` from a slice, with zero-copy if possible.
+    ///
+    /// # Errors
+    ///
+    /// This conversion can fail if the structure of the input does not match the structure
+    /// expected by `T`, if the value is larger than the size limit or nested deeper than the depth
+    /// limit, or if trailing bytes are rejected and the input continues past the value.
+    pub fn deserialize<'a, T>(&self, input: &'a [u8]) -> Result<T, decode::Error>
+    where
+        T: Deserialize<'a>,
+    {
+        let buf = match self.limit {
+            Some(limit) if input.len() as u64 > limit => &input[..limit as usize],
+            _ => input,
+        };
+
+        let mut de = decode::Deserializer::from_slice(buf).with_config(self.config);
+        de.set_max_depth(self.max_depth.unwrap_or(DEFAULT_MAX_DEPTH));
+        let val = match T::deserialize(&mut de) {
+            Ok(val) => val,
+            Err(err) if buf.len() < input.len() && is_eof(&err) => {
+                return Err(decode::Error::Syntax(SIZE_LIMIT_EXCEEDED.into()))
+            }
+            Err(err) => return Err(err),
+        };
+
+        let consumed = buf.len() - de.remaining_slice().len();
+        if self.reject_trailing_bytes && consumed < input.len() {
+            return Err(decode::Error::Syntax(TRAILING_BYTES.into()));
+        }
+
+        Ok(val)
+    }
+
+    /// Deserializes an instance of type `T` from an I/O stream of MessagePack.
+    ///
+    /// When trailing bytes are rejected the stream must be exhausted after the value.
+    ///
+    /// # Errors
+    ///
+    /// This conversion can fail if the structure of the input does not match the structure
+    /// expected by `T`, if reading fails, if the value is larger than the size limit or nested
+    /// deeper than the depth limit, or if trailing bytes are rejected and the stream continues past
+    /// the value.
+    pub fn deserialize_from<R, T>(&self, rd: R) -> Result<T, decode::Error>
+    where
+        R: Read,
+        T: DeserializeOwned,
+    {
+        let mut de = decode::Deserializer::new(LimitedRead::new(rd, self.limit)).with_config(self.config);
+        de.set_max_depth(self.max_depth.unwrap_or(DEFAULT_MAX_DEPTH));
+        let val = match T::deserialize(&mut de) {
+            Ok(val) => val,
+            Err(..) if de.get_ref().exceeded => {
+                return Err(decode::Error::Syntax(SIZE_LIMIT_EXCEEDED.into()))
+            }
+            Err(err) => return Err(err),
+        };
+
+        if self.reject_trailing_bytes {
+            // Bypass the limit, the value itself has already been read.
+            match de.get_mut().rd.read(&mut [0]) {
+                Ok(0) => {}
+                Ok(..) => return Err(decode::Error::Syntax(TRAILING_BYTES.into())),
+                Err(err) => return Err(decode::Error::InvalidDataRead(err)),
+            }
+        }
+
+        Ok(val)
+    }
+}
+
+/// The depth limit of deserialization unless one is set with `with_max_depth`.
+#[cfg(feature = "std")]
+const DEFAULT_MAX_DEPTH: usize = 1024;
+
+/// The message of the error a value exceeding the size limit fails with.
+#[cfg(feature = "std")]
+pub(crate) const SIZE_LIMIT_EXCEEDED: &str = "size limit exceeded";
+
+/// The message of the error rejected trailing bytes fail deserialization with.
+#[cfg(feature = "std")]
+const TRAILING_BYTES: &str = "trailing bytes after the decoded value";
+
+#[cfg(feature = "std")]
+#[inline]
+fn is_eof(err: &decode::Error) -> bool {
+    match *err {
+        decode::Error::InvalidMarkerRead(ref err) | decode::Error::InvalidDataRead(ref err) => {
+            err.kind() == ErrorKind::UnexpectedEof
+        }
+        _ => false,
+    }
+}
+
+/// Reader wrapper that fails once more than `left` bytes are requested.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct LimitedRead<R> {
+    rd: R,
+    left: u64,
+    exceeded: bool,
+}
+
+#[cfg(feature = "std")]
+impl<R> LimitedRead<R> {
+    #[inline]
+    fn new(rd: R, limit: Option<u64>) -> Self {
+        LimitedRead {
+            rd,
+            left: limit.unwrap_or(u64::MAX),
+            exceeded: false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Read for LimitedRead<R> {
+    // `io::Error::other` needs Rust 1.74.
+    #[allow(unknown_lints, clippy::io_other_error)]
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.left == 0 {
+            self.exceeded = true;
+            return Err(io::Error::new(ErrorKind::Other, SIZE_LIMIT_EXCEEDED));
+        }
+
+        let max = cmp::min(buf.len() as u64, self.left) as usize;
+        let len = self.rd.read(&mut buf[..max])?;
+        self.left -= len as u64;
+        Ok(len)
+    }
 }
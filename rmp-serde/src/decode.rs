@@ -1,33 +1,62 @@
 //! Generic MessagePack deserialization.
 
-use std::convert::TryInto;
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp;
+use core::convert::TryInto;
+use core::marker::PhantomData;
+use core::fmt::{self, Display, Formatter};
+use core::num::TryFromIntError;
+use core::str::{self, Utf8Error};
+#[cfg(feature = "std")]
 use std::error;
-use std::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
 use std::io::{self, Cursor, ErrorKind, Read};
-use std::num::TryFromIntError;
-use std::str::{self, Utf8Error};
-
-use byteorder::{self, ReadBytesExt};
 
 use serde;
-use serde::de::{self, Deserialize, DeserializeOwned, DeserializeSeed, Unexpected, Visitor};
+#[cfg(feature = "std")]
+use serde::de::DeserializeOwned;
+use serde::de::{self, Deserialize, DeserializeSeed, Unexpected, Visitor};
 
 use rmp;
 use rmp::decode::{self, RmpRead, DecodeStringError, MarkerReadError, NumValueReadError, ValueReadError};
 use rmp::Marker;
 
-use crate::config::{BinaryConfig, DefaultConfig, HumanReadableConfig, SerializerConfig};
+use crate::config::{
+    BinaryConfig, BytesMode, BytesModeConfig, DefaultConfig, HumanReadableConfig, LegacyRawConfig,
+    LenientConfig, SerializerConfig, StrictConfig,
+};
 use crate::MSGPACK_EXT_STRUCT_NAME;
 
+/// The error type of reading from a [`Read`] reader.
+#[cfg(feature = "std")]
+pub type ReadError = io::Error;
+
+/// The error type of reading from a [`Read`] reader, i.e. of reading past the end of a `&[u8]`.
+#[cfg(not(feature = "std"))]
+pub type ReadError = <&'static [u8] as RmpRead>::Error;
+
+/// A reader the `Deserializer` can read from without `std`.
+///
+/// This is any [`RmpRead`] failing with [`ReadError`], such as `&[u8]`.
+#[cfg(not(feature = "std"))]
+pub trait Read: RmpRead<Error = ReadError> {}
+
+#[cfg(not(feature = "std"))]
+impl<R: RmpRead<Error = ReadError> + ?Sized> Read for R {}
+
 /// Enum representing errors that can occur while decoding MessagePack data.
 #[derive(Debug)]
 pub enum Error {
     /// The enclosed I/O error occurred while trying to read a MessagePack
     /// marker.
-    InvalidMarkerRead(io::Error),
+    InvalidMarkerRead(ReadError),
     /// The enclosed I/O error occurred while trying to read the encoded
     /// MessagePack data.
-    InvalidDataRead(io::Error),
+    InvalidDataRead(ReadError),
     /// A mismatch occurred between the decoded and expected value types.
     TypeMismatch(Marker),
     /// A numeric cast failed due to an out-of-range error.
@@ -60,6 +89,27 @@ macro_rules! depth_count(
     }
 );
 
+/// Implements `deserialize_*` integer methods, which coerce other types in lenient mode.
+macro_rules! deserialize_lenient_int {
+    ($($method:ident)*) => {
+        $(
+            #[inline]
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+                where V: Visitor<'de>
+            {
+                if self.config.is_lenient() {
+                    return self.read_lenient_int(visitor);
+                }
+                match self.visit_slice_int(visitor) {
+                    Ok(res) => res,
+                    Err(visitor) => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "std")]
 impl error::Error for Error {
     #[cold]
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
@@ -77,6 +127,9 @@ impl error::Error for Error {
     }
 }
 
+#[cfg(not(feature = "std"))]
+impl de::StdError for Error {}
+
 impl de::Error for Error {
     #[cold]
     fn custom<T: Display>(msg: T) -> Self {
@@ -107,9 +160,9 @@ impl Display for Error {
     }
 }
 
-impl From<MarkerReadError> for Error {
+impl From<MarkerReadError<ReadError>> for Error {
     #[cold]
-    fn from(err: MarkerReadError) -> Error {
+    fn from(err: MarkerReadError<ReadError>) -> Error {
         match err {
             MarkerReadError(err) => Error::InvalidMarkerRead(err),
         }
@@ -123,9 +176,9 @@ impl From<Utf8Error> for Error {
     }
 }
 
-impl From<ValueReadError> for Error {
+impl From<ValueReadError<ReadError>> for Error {
     #[cold]
-    fn from(err: ValueReadError) -> Error {
+    fn from(err: ValueReadError<ReadError>) -> Error {
         match err {
             ValueReadError::TypeMismatch(marker) => Error::TypeMismatch(marker),
             ValueReadError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
@@ -134,9 +187,9 @@ impl From<ValueReadError> for Error {
     }
 }
 
-impl From<NumValueReadError> for Error {
+impl From<NumValueReadError<ReadError>> for Error {
     #[cold]
-    fn from(err: NumValueReadError) -> Error {
+    fn from(err: NumValueReadError<ReadError>) -> Error {
         match err {
             NumValueReadError::TypeMismatch(marker) => Error::TypeMismatch(marker),
             NumValueReadError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
@@ -146,9 +199,9 @@ impl From<NumValueReadError> for Error {
     }
 }
 
-impl<'a> From<DecodeStringError<'a>> for Error {
+impl<'a> From<DecodeStringError<'a, ReadError>> for Error {
     #[cold]
-    fn from(err: DecodeStringError<'_>) -> Error {
+    fn from(err: DecodeStringError<'_, ReadError>) -> Error {
         match err {
             DecodeStringError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
             DecodeStringError::InvalidDataRead(err) => Error::InvalidDataRead(err),
@@ -180,25 +233,40 @@ pub struct Deserializer<R, C = DefaultConfig> {
     depth: usize,
 }
 
+impl<R, C> Deserializer<R, C> {
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn with_config<C2>(self, config: C2) -> Deserializer<R, C2> {
+        let Deserializer { rd, marker, depth, .. } = self;
+        Deserializer {
+            rd,
+            config,
+            marker,
+            depth,
+        }
+    }
+}
+
 impl<R: Read, C> Deserializer<R, C> {
     #[inline]
-    fn take_or_read_marker(&mut self) -> Result<Marker, MarkerReadError> {
+    fn take_or_read_marker(&mut self) -> Result<Marker, MarkerReadError<ReadError>> {
         self.marker
             .take()
             .map_or_else(|| rmp::decode::read_marker(&mut self.rd), Ok)
     }
 
     #[inline]
-    fn peek_or_read_marker(&mut self) -> Result<Marker, MarkerReadError> {
+    fn peek_or_read_marker(&mut self) -> Result<Marker, MarkerReadError<ReadError>> {
         if let Some(m) = self.marker {
             Ok(m)
         } else {
             let m = rmp::decode::read_marker(&mut self.rd)?;
-            Ok(self.marker.insert(m).to_owned())
+            Ok(*self.marker.insert(m))
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl<R: Read> Deserializer<ReadReader<R>, DefaultConfig> {
     /// Constructs a new `Deserializer` by consuming the given reader.
     #[inline]
@@ -213,6 +281,7 @@ impl<R: Read> Deserializer<ReadReader<R>, DefaultConfig> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<R: Read, C> Deserializer<ReadReader<R>, C> {
     /// Gets a reference to the underlying reader in this decoder.
     #[inline(always)]
@@ -265,8 +334,80 @@ impl<R: Read, C: SerializerConfig> Deserializer<R, C> {
             depth,
         }
     }
+
+    /// Consumes this deserializer and returns a new one, which will read strings as the raw type
+    /// of the old MessagePack specification, handing their payload to types that ask for bytes.
+    ///
+    /// See [`LegacyRawConfig`] for details.
+    #[inline]
+    pub fn with_legacy_raw(self) -> Deserializer<R, LegacyRawConfig<C>> {
+        let Deserializer { rd, config, marker, depth } = self;
+        Deserializer {
+            rd,
+            config: LegacyRawConfig::new(config),
+            marker,
+            depth,
+        }
+    }
+
+    /// Consumes this deserializer and returns a new one, which will accept `bin` wherever a
+    /// sequence or a tuple is expected, unless the given mode is `BytesMode::Normal`.
+    ///
+    /// See [`BytesMode`] for details.
+    #[inline]
+    pub fn with_bytes(self, mode: BytesMode) -> Deserializer<R, BytesModeConfig<C>> {
+        let Deserializer { rd, config, marker, depth } = self;
+        Deserializer {
+            rd,
+            config: BytesModeConfig::new(config, mode),
+            marker,
+            depth,
+        }
+    }
+
+    /// Consumes this deserializer and returns a new one, which will coerce values of a different
+    /// type than expected, as long as no information is lost.
+    ///
+    /// See [`LenientConfig`] for details.
+    #[inline]
+    pub fn with_lenient(self) -> Deserializer<R, LenientConfig<C>> {
+        let Deserializer { rd, config, marker, depth } = self;
+        Deserializer {
+            rd,
+            config: LenientConfig::new(config),
+            marker,
+            depth,
+        }
+    }
+
+    /// Consumes this deserializer and returns a new one, which will reject non-canonical input,
+    /// such as invalid UTF-8 or non-minimal encodings.
+    ///
+    /// See [`StrictConfig`] for details.
+    #[inline]
+    pub fn with_strict(self) -> Deserializer<R, StrictConfig<C>> {
+        let Deserializer { rd, config, marker, depth } = self;
+        Deserializer {
+            rd,
+            config: StrictConfig::new(config),
+            marker,
+            depth,
+        }
+    }
+}
+
+impl<R, C> Deserializer<R, StrictConfig<C>> {
+    /// Registers an ext type of the reserved range, so that it is accepted.
+    ///
+    /// See [`StrictConfig::with_ext_type`] for details.
+    #[inline]
+    pub fn with_ext_type(mut self, tag: i8) -> Self {
+        self.config = self.config.with_ext_type(tag);
+        self
+    }
 }
 
+#[cfg(feature = "std")]
 impl<R: AsRef<[u8]>> Deserializer<ReadReader<Cursor<R>>> {
     /// Returns the current position of this deserializer, i.e. how many bytes were read.
     #[inline(always)]
@@ -275,6 +416,7 @@ impl<R: AsRef<[u8]>> Deserializer<ReadReader<Cursor<R>>> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'de, R> Deserializer<ReadRefReader<'de, R>>
 where
     R: AsRef<[u8]> + ?Sized,
@@ -297,6 +439,29 @@ where
     }
 }
 
+impl<'de> Deserializer<&'de [u8]> {
+    /// Constructs a new `Deserializer` reading directly from the given byte slice.
+    ///
+    /// Unlike [`Deserializer::from_read_ref`] this is available without `std`.
+    #[inline(always)]
+    pub fn from_slice(input: &'de [u8]) -> Self {
+        Deserializer {
+            rd: input,
+            config: DefaultConfig,
+            marker: None,
+            depth: 1024,
+        }
+    }
+}
+
+impl<'de, C> Deserializer<&'de [u8], C> {
+    /// Returns the part of the input that hasn't been consumed yet.
+    #[inline(always)]
+    pub fn remaining_slice(&self) -> &'de [u8] {
+        self.rd
+    }
+}
+
 impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
     /// Changes the maximum nesting depth that is allowed
     #[inline(always)]
@@ -304,6 +469,24 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
         self.depth = depth;
     }
 
+    /// Rejects an integer that isn't minimally encoded in strict mode.
+    #[inline]
+    fn check_int(&self, marker: Marker, val: i128) -> Result<(), Error> {
+        if self.config.is_strict() && !is_minimal_int(marker, val) {
+            return Err(non_minimal_encoding(marker));
+        }
+        Ok(())
+    }
+
+    /// Rejects a length that isn't minimally encoded in strict mode.
+    #[inline]
+    fn check_len(&self, marker: Marker, len: u32) -> Result<(), Error> {
+        if self.config.is_strict() && !is_minimal_len(marker, len, self.config.is_legacy_raw()) {
+            return Err(non_minimal_encoding(marker));
+        }
+        Ok(())
+    }
+
     fn read_str_data<V>(&mut self, len: u32, visitor: V) -> Result<V::Value, Error>
         where V: Visitor<'de>
     {
@@ -311,6 +494,7 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
             Reference::Borrowed(buf) => {
                 match str::from_utf8(buf) {
                     Ok(s) => visitor.visit_borrowed_str(s),
+                    Err(err) if self.config.is_strict() => Err(Error::Utf8Error(err)),
                     Err(err) => {
                         // Allow to unpack invalid UTF-8 bytes into a byte array.
                         match visitor.visit_borrowed_bytes::<Error>(buf) {
@@ -323,6 +507,7 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
             Reference::Copied(buf) => {
                 match str::from_utf8(buf) {
                     Ok(s) => visitor.visit_str(s),
+                    Err(err) if self.config.is_strict() => Err(Error::Utf8Error(err)),
                     Err(err) => {
                         // Allow to unpack invalid UTF-8 bytes into a byte array.
                         match visitor.visit_bytes::<Error>(buf) {
@@ -335,14 +520,149 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
         }
     }
 
-    fn read_128(&mut self) -> Result<[u8; 16], Error> {
-        let marker = self.take_or_read_marker()?;
+    /// Decodes an integer straight from an in-memory input, with one bounds check for the marker
+    /// and data.
+    ///
+    /// Gives the visitor back when the value has to take the general path: for other readers, a
+    /// peeked marker, another type, truncated input or a strict mode violation.
+    #[inline(always)]
+    fn visit_slice_int<V>(&mut self, visitor: V) -> Result<Result<V::Value, Error>, V>
+        where V: Visitor<'de>
+    {
+        if self.marker.is_some() {
+            return Err(visitor);
+        }
+        let strict = self.config.is_strict();
+        let buf = match self.rd.remaining_buf(sealed::Local) {
+            Some(buf) => buf,
+            None => return Err(visitor),
+        };
+        let (&byte, rest) = match buf.split_first() {
+            Some(split) => split,
+            None => return Err(visitor),
+        };
+
+        macro_rules! visit_be {
+            ($ty:ty, $visit:ident) => {{
+                const N: usize = core::mem::size_of::<$ty>();
+                if rest.len() < N {
+                    return Err(visitor);
+                }
+                let (data, rest) = rest.split_at(N);
+                let val = <$ty>::from_be_bytes(data.try_into().unwrap());
+                if strict && !is_minimal_int(Marker::from_u8(byte), val.into()) {
+                    return Err(visitor);
+                }
+                *buf = rest;
+                Ok(visitor.$visit(val))
+            }};
+        }
+
+        match byte {
+            0x00..=0x7f => {
+                *buf = rest;
+                Ok(visitor.visit_u8(byte))
+            }
+            0xe0..=0xff => {
+                *buf = rest;
+                Ok(visitor.visit_i8(byte as i8))
+            }
+            0xcc => visit_be!(u8, visit_u8),
+            0xcd => visit_be!(u16, visit_u16),
+            0xce => visit_be!(u32, visit_u32),
+            0xcf => visit_be!(u64, visit_u64),
+            0xd0 => visit_be!(i8, visit_i8),
+            0xd1 => visit_be!(i16, visit_i16),
+            0xd2 => visit_be!(i32, visit_i32),
+            0xd3 => visit_be!(i64, visit_i64),
+            _ => Err(visitor),
+        }
+    }
+
+    /// Decodes a float or a boolean straight from an in-memory input, see `visit_slice_int`.
+    #[inline(always)]
+    fn visit_slice_scalar<V>(&mut self, visitor: V) -> Result<Result<V::Value, Error>, V>
+        where V: Visitor<'de>
+    {
+        if self.marker.is_some() {
+            return Err(visitor);
+        }
+        let buf = match self.rd.remaining_buf(sealed::Local) {
+            Some(buf) => buf,
+            None => return Err(visitor),
+        };
+        match buf.split_first() {
+            Some((0xc2, rest)) => {
+                *buf = rest;
+                Ok(visitor.visit_bool(false))
+            }
+            Some((0xc3, rest)) => {
+                *buf = rest;
+                Ok(visitor.visit_bool(true))
+            }
+            Some((0xca, rest)) if rest.len() >= 4 => {
+                let (data, rest) = rest.split_at(4);
+                *buf = rest;
+                Ok(visitor.visit_f32(f32::from_be_bytes(data.try_into().unwrap())))
+            }
+            Some((0xcb, rest)) if rest.len() >= 8 => {
+                let (data, rest) = rest.split_at(8);
+                *buf = rest;
+                Ok(visitor.visit_f64(f64::from_be_bytes(data.try_into().unwrap())))
+            }
+            _ => Err(visitor),
+        }
+    }
 
-        if marker != Marker::Bin8 {
-            return Err(Error::TypeMismatch(marker));
+    /// Borrows a valid UTF-8 string straight from an in-memory input, see `visit_slice_int`.
+    #[inline(always)]
+    fn visit_slice_str<V>(&mut self, visitor: V) -> Result<Result<V::Value, Error>, V>
+        where V: Visitor<'de>
+    {
+        if self.marker.is_some() {
+            return Err(visitor);
+        }
+        let strict = self.config.is_strict();
+        let legacy_raw = self.config.is_legacy_raw();
+        let buf = match self.rd.remaining_buf(sealed::Local) {
+            Some(buf) => buf,
+            None => return Err(visitor),
+        };
+        let (marker, header) = match buf.first() {
+            Some(&byte @ 0xa0..=0xbf) => (Marker::FixStr(byte & 0x1f), 1),
+            Some(0xd9) => (Marker::Str8, 2),
+            Some(0xda) => (Marker::Str16, 3),
+            Some(0xdb) => (Marker::Str32, 5),
+            _ => return Err(visitor),
+        };
+        if buf.len() < header {
+            return Err(visitor);
+        }
+        let len = match marker {
+            Marker::FixStr(len) => len.into(),
+            _ => buf[1..header].iter().fold(0, |len, &b| len << 8 | usize::from(b)),
+        };
+        if strict && !is_minimal_len(marker, len as u32, legacy_raw) {
+            return Err(visitor);
         }
+        let data = match buf.get(header..header + len) {
+            Some(data) => data,
+            None => return Err(visitor),
+        };
+        let s = match str::from_utf8(data) {
+            Ok(s) => s,
+            Err(..) => return Err(visitor),
+        };
+        *buf = &buf[header + len..];
+        Ok(visitor.visit_borrowed_str(s))
+    }
 
-        let len = read_u8(&mut self.rd)?;
+    fn read_128(&mut self) -> Result<[u8; 16], Error> {
+        let len = match self.take_or_read_marker()? {
+            Marker::Bin8 => read_u8(&mut self.rd)?,
+            Marker::FixStr(len) if self.config.is_legacy_raw() => len,
+            marker => return Err(Error::TypeMismatch(marker)),
+        };
 
         if len != 16 {
             return Err(Error::LengthMismatch(16));
@@ -355,6 +675,261 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
 
         Ok(buf.try_into().unwrap())
     }
+
+    /// Reads an integer from a float with an integral value or from a decimal string.
+    fn read_lenient_int<V>(&mut self, visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        let marker = self.take_or_read_marker()?;
+        let v = match marker {
+            Marker::F32 => f64::from(self.rd.read_data_f32()?),
+            Marker::F64 => self.rd.read_data_f64()?,
+            marker => match str_len(&mut self.rd, marker)? {
+                Some(len) => {
+                    self.check_len(marker, len)?;
+                    let buf = match read_bin_data(&mut self.rd, len)? {
+                        Reference::Borrowed(buf) => buf,
+                        Reference::Copied(buf) => buf,
+                    };
+                    let s = str::from_utf8(buf).map_err(Error::Utf8Error)?;
+                    return if let Ok(v) = s.parse::<u64>() {
+                        visitor.visit_u64(v)
+                    } else if let Ok(v) = s.parse::<i64>() {
+                        visitor.visit_i64(v)
+                    } else {
+                        Err(de::Error::invalid_value(Unexpected::Str(s), &"a decimal integer"))
+                    };
+                }
+                None => {
+                    self.marker = Some(marker);
+                    return de::Deserializer::deserialize_any(self, visitor);
+                }
+            },
+        };
+
+        // The range is checked because `as` saturates, which would lose information, and the
+        // round-trip through `i128` rejects fractions, as `f64::fract` is missing without `std`.
+        if (-9_223_372_036_854_775_808.0..18_446_744_073_709_551_616.0).contains(&v) && v as i128 as f64 == v {
+            if v < 0.0 {
+                visitor.visit_i64(v as i64)
+            } else {
+                visitor.visit_u64(v as u64)
+            }
+        } else {
+            Err(de::Error::invalid_value(Unexpected::Float(v), &"a float with an integral value"))
+        }
+    }
+
+    /// Reads a float from an integer that it represents exactly or from a decimal string.
+    ///
+    /// The float type is `f32` if `single` is set, `f64` otherwise.
+    fn read_lenient_float<V>(&mut self, single: bool, visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        let marker = self.take_or_read_marker()?;
+        let v: i128 = match marker {
+            Marker::FixPos(v) => v.into(),
+            Marker::FixNeg(v) => v.into(),
+            Marker::U8 => self.rd.read_data_u8()?.into(),
+            Marker::U16 => self.rd.read_data_u16()?.into(),
+            Marker::U32 => self.rd.read_data_u32()?.into(),
+            Marker::U64 => self.rd.read_data_u64()?.into(),
+            Marker::I8 => self.rd.read_data_i8()?.into(),
+            Marker::I16 => self.rd.read_data_i16()?.into(),
+            Marker::I32 => self.rd.read_data_i32()?.into(),
+            Marker::I64 => self.rd.read_data_i64()?.into(),
+            marker => match str_len(&mut self.rd, marker)? {
+                Some(len) => {
+                    self.check_len(marker, len)?;
+                    let buf = match read_bin_data(&mut self.rd, len)? {
+                        Reference::Borrowed(buf) => buf,
+                        Reference::Copied(buf) => buf,
+                    };
+                    let s = str::from_utf8(buf).map_err(Error::Utf8Error)?;
+                    if single {
+                        if let Ok(v) = s.parse::<f32>() {
+                            if is_round_trip(s, v) {
+                                return visitor.visit_f32(v);
+                            }
+                        }
+                    } else if let Ok(v) = s.parse::<f64>() {
+                        if is_round_trip(s, v) {
+                            return visitor.visit_f64(v);
+                        }
+                    }
+                    let exp = &"a decimal number that round-trips through the float type";
+                    return Err(de::Error::invalid_value(Unexpected::Str(s), exp));
+                }
+                None => {
+                    self.marker = Some(marker);
+                    return de::Deserializer::deserialize_any(self, visitor);
+                }
+            },
+        };
+
+        self.check_int(marker, v)?;
+
+        if single {
+            let f = v as f32;
+            if f as i128 == v {
+                return visitor.visit_f32(f);
+            }
+        } else {
+            let f = v as f64;
+            if f as i128 == v {
+                return visitor.visit_f64(f);
+            }
+        }
+
+        let unexp = match v {
+            v if v < 0 => Unexpected::Signed(v as i64),
+            v => Unexpected::Unsigned(v as u64),
+        };
+        Err(de::Error::invalid_value(unexp, &"an integer that the float type represents exactly"))
+    }
+
+    /// Reads bytes from an array of integers in `0..=255`.
+    ///
+    /// The elements must be integers themselves: strings and floats aren't coerced on top.
+    fn read_lenient_bytes<V>(&mut self, len: u32, visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        let mut buf = Vec::with_capacity(cmp::min(len as usize, 4096));
+        depth_count!(self.depth, {
+            for _ in 0..len {
+                let marker = self.take_or_read_marker()?;
+                let v: i128 = match marker {
+                    Marker::FixPos(v) => v.into(),
+                    Marker::FixNeg(v) => v.into(),
+                    Marker::U8 => self.rd.read_data_u8()?.into(),
+                    Marker::U16 => self.rd.read_data_u16()?.into(),
+                    Marker::U32 => self.rd.read_data_u32()?.into(),
+                    Marker::U64 => self.rd.read_data_u64()?.into(),
+                    Marker::I8 => self.rd.read_data_i8()?.into(),
+                    Marker::I16 => self.rd.read_data_i16()?.into(),
+                    Marker::I32 => self.rd.read_data_i32()?.into(),
+                    Marker::I64 => self.rd.read_data_i64()?.into(),
+                    marker => return Err(Error::TypeMismatch(marker)),
+                };
+                self.check_int(marker, v)?;
+                buf.push(u8::try_from(v)?);
+            }
+        });
+        visitor.visit_byte_buf(buf)
+    }
+
+    /// Moves past the next value by its markers and lengths only, without validating or
+    /// borrowing its content.
+    fn skip_value(&mut self) -> Result<(), Error> {
+        let marker = self.take_or_read_marker()?;
+        let len = match marker {
+            Marker::Null | Marker::True | Marker::False | Marker::FixPos(..) | Marker::FixNeg(..) => 0,
+            Marker::U8 | Marker::I8 => 1,
+            Marker::U16 | Marker::I16 => 2,
+            Marker::U32 | Marker::I32 | Marker::F32 => 4,
+            Marker::U64 | Marker::I64 | Marker::F64 => 8,
+            Marker::FixStr(len) => len.into(),
+            Marker::Str8 | Marker::Bin8 => read_u8(&mut self.rd)?.into(),
+            Marker::Str16 | Marker::Bin16 => read_u16(&mut self.rd)?.into(),
+            Marker::Str32 | Marker::Bin32 => read_u32(&mut self.rd)?,
+            Marker::FixArray(..) | Marker::Array16 | Marker::Array32 => {
+                let len = array_len(&mut self.rd, marker)?.unwrap_or(0);
+                return self.skip_values(len.into());
+            }
+            Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => {
+                let len = map_len(&mut self.rd, marker)?.unwrap_or(0);
+                return self.skip_values(u64::from(len) * 2);
+            }
+            Marker::FixExt1 |
+            Marker::FixExt2 |
+            Marker::FixExt4 |
+            Marker::FixExt8 |
+            Marker::FixExt16 |
+            Marker::Ext8 |
+            Marker::Ext16 |
+            Marker::Ext32 => {
+                let len = ext_len(&mut self.rd, marker)?;
+                self.rd.read_data_i8()?;
+                len
+            }
+            Marker::Reserved => return Err(Error::TypeMismatch(Marker::Reserved)),
+        };
+        self.rd.skip(len as usize).map_err(Error::InvalidDataRead)
+    }
+
+    /// Moves past the given number of values nested in a container.
+    fn skip_values(&mut self, count: u64) -> Result<(), Error> {
+        depth_count!(self.depth, {
+            for _ in 0..count {
+                self.skip_value()?;
+            }
+        });
+        Ok(())
+    }
+
+    /// Moves to the value addressed by `path`, skipping the values before it.
+    ///
+    /// Returns `Ok(Err(pos))` if the segment at `pos` matches no element or entry of the value it
+    /// is applied to.
+    fn seek_path(&mut self, path: &[PathSegment<'_>]) -> Result<Result<(), usize>, Error> {
+        for (pos, segment) in path.iter().enumerate() {
+            let marker = self.take_or_read_marker()?;
+            match marker {
+                Marker::FixArray(..) | Marker::Array16 | Marker::Array32 => {
+                    let len = array_len(&mut self.rd, marker)?.unwrap_or(0);
+                    match *segment {
+                        PathSegment::Index(idx) if idx < len as usize => self.skip_values(idx as u64)?,
+                        _ => return Ok(Err(pos)),
+                    }
+                }
+                Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => {
+                    let len = map_len(&mut self.rd, marker)?.unwrap_or(0);
+                    let mut found = false;
+                    for _ in 0..len {
+                        if self.read_key_matches(segment)? {
+                            found = true;
+                            break;
+                        }
+                        self.skip_value()?;
+                    }
+                    if !found {
+                        return Ok(Err(pos));
+                    }
+                }
+                _ => return Ok(Err(pos)),
+            }
+        }
+        Ok(Ok(()))
+    }
+
+    /// Reads a map key, returning whether it is the one named by `segment`.
+    fn read_key_matches(&mut self, segment: &PathSegment<'_>) -> Result<bool, Error> {
+        let marker = self.take_or_read_marker()?;
+        let key = match (*segment, marker) {
+            (PathSegment::Key(key), Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32) => {
+                let len = str_len(&mut self.rd, marker)?.unwrap_or(0);
+                return Ok(match read_bin_data(&mut self.rd, len)? {
+                    Reference::Borrowed(buf) => buf == key.as_bytes(),
+                    Reference::Copied(buf) => buf == key.as_bytes(),
+                });
+            }
+            (PathSegment::Index(..), Marker::FixPos(val)) => i128::from(val),
+            (PathSegment::Index(..), Marker::U8) => self.rd.read_data_u8()?.into(),
+            (PathSegment::Index(..), Marker::U16) => self.rd.read_data_u16()?.into(),
+            (PathSegment::Index(..), Marker::U32) => self.rd.read_data_u32()?.into(),
+            (PathSegment::Index(..), Marker::U64) => self.rd.read_data_u64()?.into(),
+            (PathSegment::Index(..), Marker::I8) => self.rd.read_data_i8()?.into(),
+            (PathSegment::Index(..), Marker::I16) => self.rd.read_data_i16()?.into(),
+            (PathSegment::Index(..), Marker::I32) => self.rd.read_data_i32()?.into(),
+            (PathSegment::Index(..), Marker::I64) => self.rd.read_data_i64()?.into(),
+            _ => {
+                self.marker = Some(marker);
+                self.skip_value()?;
+                return Ok(false);
+            }
+        };
+        Ok(matches!(*segment, PathSegment::Index(idx) if idx as i128 == key))
+    }
 }
 
 fn read_bin_data<'a, 'de, R: ReadSlice<'de>>(rd: &'a mut R, len: u32) -> Result<Reference<'de,'a, [u8]>, Error> {
@@ -362,17 +937,15 @@ fn read_bin_data<'a, 'de, R: ReadSlice<'de>>(rd: &'a mut R, len: u32) -> Result<
 }
 
 fn read_u8<R: Read>(rd: &mut R) -> Result<u8, Error> {
-    byteorder::ReadBytesExt::read_u8(rd).map_err(Error::InvalidDataRead)
+    Ok(rd.read_data_u8()?)
 }
 
 fn read_u16<R: Read>(rd: &mut R) -> Result<u16, Error> {
-    rd.read_u16::<byteorder::BigEndian>()
-        .map_err(Error::InvalidDataRead)
+    Ok(rd.read_data_u16()?)
 }
 
 fn read_u32<R: Read>(rd: &mut R) -> Result<u32, Error> {
-    rd.read_u32::<byteorder::BigEndian>()
-        .map_err(Error::InvalidDataRead)
+    Ok(rd.read_data_u32()?)
 }
 
 fn ext_len<R: Read>(rd: &mut R, marker: Marker) -> Result<u32, Error> {
@@ -389,6 +962,158 @@ fn ext_len<R: Read>(rd: &mut R, marker: Marker) -> Result<u32, Error> {
     })
 }
 
+/// Reads the length of a string, or returns `None` if the marker is not a string one.
+fn str_len<R: Read>(rd: &mut R, marker: Marker) -> Result<Option<u32>, Error> {
+    Ok(Some(match marker {
+        Marker::FixStr(len) => len.into(),
+        Marker::Str8 => read_u8(rd)?.into(),
+        Marker::Str16 => read_u16(rd)?.into(),
+        Marker::Str32 => read_u32(rd)?,
+        _ => return Ok(None),
+    }))
+}
+
+/// Reads the length of a binary, or returns `None` if the marker is not a binary one.
+fn bin_len<R: Read>(rd: &mut R, marker: Marker) -> Result<Option<u32>, Error> {
+    Ok(Some(match marker {
+        Marker::Bin8 => read_u8(rd)?.into(),
+        Marker::Bin16 => read_u16(rd)?.into(),
+        Marker::Bin32 => read_u32(rd)?,
+        _ => return Ok(None),
+    }))
+}
+
+/// Reads the length of an array, or returns `None` if the marker is not an array one.
+fn array_len<R: Read>(rd: &mut R, marker: Marker) -> Result<Option<u32>, Error> {
+    Ok(Some(match marker {
+        Marker::FixArray(len) => len.into(),
+        Marker::Array16 => read_u16(rd)?.into(),
+        Marker::Array32 => read_u32(rd)?,
+        _ => return Ok(None),
+    }))
+}
+
+/// Reads the length of a map, or returns `None` if the marker is not a map one.
+fn map_len<R: Read>(rd: &mut R, marker: Marker) -> Result<Option<u32>, Error> {
+    Ok(Some(match marker {
+        Marker::FixMap(len) => len.into(),
+        Marker::Map16 => read_u16(rd)?.into(),
+        Marker::Map32 => read_u32(rd)?,
+        _ => return Ok(None),
+    }))
+}
+
+/// Returns true if formatting the float parsed from the decimal number `s` gives back the same
+/// number, i.e. if parsing it lost none of its digits.
+fn is_round_trip<F: fmt::LowerExp>(s: &str, val: F) -> bool {
+    match (Decimal::parse(s), Decimal::parse(&format!("{:e}", val))) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// A decimal number as its sign, its significant digits and the exponent of its last digit, so
+/// that equal numbers compare equal however they are written.
+#[derive(PartialEq)]
+struct Decimal {
+    neg: bool,
+    digits: Vec<u8>,
+    exp: i64,
+}
+
+impl Decimal {
+    /// Parses a decimal number such as `-1.50e3`. Returns None for anything else, including
+    /// infinities and NaN.
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.as_bytes();
+        let (neg, s) = match s.split_first() {
+            Some((b'-', rest)) => (true, rest),
+            Some((b'+', rest)) => (false, rest),
+            _ => (false, s),
+        };
+        let (mantissa, mut exp) = match s.iter().position(|&c| c == b'e' || c == b'E') {
+            Some(pos) => (&s[..pos], str::from_utf8(&s[pos + 1..]).ok()?.parse::<i64>().ok()?),
+            None => (s, 0),
+        };
+
+        let mut digits = Vec::with_capacity(mantissa.len());
+        let mut point = false;
+        for &c in mantissa {
+            match c {
+                b'0'..=b'9' => {
+                    digits.push(c);
+                    if point {
+                        exp = exp.checked_sub(1)?;
+                    }
+                }
+                b'.' if !point => point = true,
+                _ => return None,
+            }
+        }
+        if digits.is_empty() {
+            return None;
+        }
+
+        let end = match digits.iter().rposition(|&c| c != b'0') {
+            Some(pos) => pos + 1,
+            None => return Some(Decimal { neg, digits: Vec::new(), exp: 0 }),
+        };
+        exp = exp.checked_add((digits.len() - end) as i64)?;
+        digits.truncate(end);
+        let start = digits.iter().position(|&c| c != b'0').unwrap_or(0);
+        digits.drain(..start);
+
+        Some(Decimal { neg, digits, exp })
+    }
+}
+
+/// The error of a path segment, at the given position, that matched no element or entry of the
+/// value it was applied to.
+#[cold]
+fn path_not_found(pos: usize) -> Error {
+    Error::Syntax(format!("path not found at segment {}", pos))
+}
+
+/// The error of a value that strict mode rejects for using more bytes than needed.
+#[cold]
+fn non_minimal_encoding(marker: Marker) -> Error {
+    Error::Syntax(format!("non-minimal encoding with marker {:?}", marker))
+}
+
+/// Returns whether the integer is encoded with the marker `rmp::encode::write_uint` or
+/// `rmp::encode::write_sint` would choose, i.e. the smallest one, unsigned for non-negative values.
+fn is_minimal_int(marker: Marker, val: i128) -> bool {
+    let expected = match val {
+        0..=0x7f => return matches!(marker, Marker::FixPos(..)),
+        -32..=-1 => return matches!(marker, Marker::FixNeg(..)),
+        0x80..=0xff => Marker::U8,
+        0x100..=0xffff => Marker::U16,
+        0x1_0000..=0xffff_ffff => Marker::U32,
+        val if val > 0 => Marker::U64,
+        -128..=-33 => Marker::I8,
+        -32_768..=-129 => Marker::I16,
+        -2_147_483_648..=-32_769 => Marker::I32,
+        _ => Marker::I64,
+    };
+    marker == expected
+}
+
+/// Returns whether the length is encoded with the smallest marker of its family.
+///
+/// The old specification had no `str8`, so `str16` is the smallest marker for longer strings in
+/// legacy raw mode.
+fn is_minimal_len(marker: Marker, len: u32, legacy_raw: bool) -> bool {
+    match marker {
+        Marker::Str8 => len >= 32,
+        Marker::Str16 if legacy_raw => len >= 32,
+        Marker::Str16 | Marker::Bin16 | Marker::Ext16 => len > 0xff,
+        Marker::Array16 | Marker::Map16 => len >= 16,
+        Marker::Str32 | Marker::Bin32 | Marker::Ext32 | Marker::Array32 | Marker::Map32 => len > 0xffff,
+        Marker::Ext8 => !matches!(len, 1 | 2 | 4 | 8 | 16),
+        _ => true,
+    }
+}
+
 #[derive(Debug)]
 enum ExtDeserializerState {
     New,
@@ -399,7 +1124,7 @@ enum ExtDeserializerState {
 #[derive(Debug)]
 struct ExtDeserializer<'a, R, C> {
     rd: &'a mut R,
-    _config: C,
+    config: C,
     len: u32,
     state: ExtDeserializerState,
 }
@@ -408,7 +1133,7 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> ExtDeserializer<'a, R
     fn new(d: &'a mut Deserializer<R, C>, len: u32) -> Self {
         ExtDeserializer {
             rd: &mut d.rd,
-            _config: d.config,
+            config: d.config,
             len,
             state: ExtDeserializerState::New,
         }
@@ -458,6 +1183,9 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::Deserializer<'de>
         match self.state {
             ExtDeserializerState::New => {
                 let tag = self.rd.read_data_i8()?;
+                if !self.config.accepts_ext_type(tag) {
+                    return Err(Error::Syntax(format!("reserved ext type {}", tag)));
+                }
                 self.state = ExtDeserializerState::ReadTag;
                 visitor.visit_i8(tag)
             }
@@ -485,7 +1213,7 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
 
     #[inline(always)]
     fn is_human_readable(&self) -> bool {
-        C::is_human_readable()
+        self.config.is_human_readable()
     }
 
     #[inline(never)]
@@ -500,24 +1228,57 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
             Marker::False => visitor.visit_bool(marker == Marker::True),
             Marker::FixPos(val) => visitor.visit_u8(val),
             Marker::FixNeg(val) => visitor.visit_i8(val),
-            Marker::U8 => visitor.visit_u8(self.rd.read_data_u8()?),
-            Marker::U16 => visitor.visit_u16(self.rd.read_data_u16()?),
-            Marker::U32 => visitor.visit_u32(self.rd.read_data_u32()?),
-            Marker::U64 => visitor.visit_u64(self.rd.read_data_u64()?),
-            Marker::I8 => visitor.visit_i8(self.rd.read_data_i8()?),
-            Marker::I16 => visitor.visit_i16(self.rd.read_data_i16()?),
-            Marker::I32 => visitor.visit_i32(self.rd.read_data_i32()?),
-            Marker::I64 => visitor.visit_i64(self.rd.read_data_i64()?),
-            Marker::F32 => visitor.visit_f32(self.rd.read_data_f32()?),
-            Marker::F64 => visitor.visit_f64(self.rd.read_data_f64()?),
-            Marker::FixStr(_) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
-                let len = match marker {
+            Marker::U8 => {
+                let val = self.rd.read_data_u8()?;
+                self.check_int(marker, val.into())?;
+                visitor.visit_u8(val)
+            }
+            Marker::U16 => {
+                let val = self.rd.read_data_u16()?;
+                self.check_int(marker, val.into())?;
+                visitor.visit_u16(val)
+            }
+            Marker::U32 => {
+                let val = self.rd.read_data_u32()?;
+                self.check_int(marker, val.into())?;
+                visitor.visit_u32(val)
+            }
+            Marker::U64 => {
+                let val = self.rd.read_data_u64()?;
+                self.check_int(marker, val.into())?;
+                visitor.visit_u64(val)
+            }
+            Marker::I8 => {
+                let val = self.rd.read_data_i8()?;
+                self.check_int(marker, val.into())?;
+                visitor.visit_i8(val)
+            }
+            Marker::I16 => {
+                let val = self.rd.read_data_i16()?;
+                self.check_int(marker, val.into())?;
+                visitor.visit_i16(val)
+            }
+            Marker::I32 => {
+                let val = self.rd.read_data_i32()?;
+                self.check_int(marker, val.into())?;
+                visitor.visit_i32(val)
+            }
+            Marker::I64 => {
+                let val = self.rd.read_data_i64()?;
+                self.check_int(marker, val.into())?;
+                visitor.visit_i64(val)
+            }
+            Marker::F32 => visitor.visit_f32(self.rd.read_data_f32()?),
+            Marker::F64 => visitor.visit_f64(self.rd.read_data_f64()?),
+            Marker::FixStr(_) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+                let len = match marker {
                     Marker::FixStr(len) => Ok(len.into()),
                     Marker::Str8 => read_u8(&mut self.rd).map(u32::from),
                     Marker::Str16 => read_u16(&mut self.rd).map(u32::from),
                     Marker::Str32 => read_u32(&mut self.rd).map(u32::from),
                     _ => unreachable!()
                 }?;
+                self.check_len(marker, len)?;
                 self.read_str_data(len, visitor)
             }
             Marker::FixArray(_) |
@@ -529,6 +1290,7 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
                     Marker::Array32 => read_u32(&mut self.rd)?,
                     _ => unreachable!(),
                 };
+                self.check_len(marker, len)?;
 
                 depth_count!(self.depth, {
                     let mut seq = SeqAccess::new(self, len);
@@ -548,8 +1310,18 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
                     Marker::Map32 => read_u32(&mut self.rd)?,
                     _ => unreachable!()
                 };
+                self.check_len(marker, len)?;
 
                 depth_count!(self.depth, {
+                    if self.config.is_strict() {
+                        // Structs with flattened fields are read as maps as well.
+                        let mut map = StrictMapAccess::new(self, len, false);
+                        let res = visitor.visit_map(&mut map)?;
+                        return match map.left {
+                            0 => Ok(res),
+                            excess => Err(Error::LengthMismatch(len - excess)),
+                        };
+                    }
                     let mut seq = MapAccess::new(self, len);
                     let res = visitor.visit_map(&mut seq)?;
                     match seq.left {
@@ -565,6 +1337,7 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
                     Marker::Bin32 => read_u32(&mut self.rd).map(u32::from),
                     _ => unreachable!()
                 }?;
+                self.check_len(marker, len)?;
                 match read_bin_data(&mut self.rd, len)? {
                     Reference::Borrowed(buf) => visitor.visit_borrowed_bytes(buf),
                     Reference::Copied(buf) => visitor.visit_bytes(buf),
@@ -579,6 +1352,7 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
             Marker::Ext16 |
             Marker::Ext32 => {
                 let len = ext_len(&mut self.rd, marker)?;
+                self.check_len(marker, len)?;
                 depth_count!(self.depth, visitor.visit_newtype_struct(ExtDeserializer::new(self, len)))
             }
             Marker::Reserved => Err(Error::TypeMismatch(Marker::Reserved)),
@@ -622,6 +1396,7 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
                 // where the K = the variant & V = associated data
                 // or as just the variant
                 1 => {
+                    self.check_len(marker, len)?;
                     self.marker = None;
                     visitor.visit_enum(VariantAccess::new(self))
                 }
@@ -639,6 +1414,7 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
             let marker = self.take_or_read_marker()?;
 
             let len = ext_len(&mut self.rd, marker)?;
+            self.check_len(marker, len)?;
             let ext_de = ExtDeserializer::new(self, len);
             return visitor.visit_newtype_struct(ext_de);
         }
@@ -679,11 +1455,183 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
         visitor.visit_u128(u128::from_be_bytes(buf))
     }
 
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        let lenient = self.config.is_lenient();
+        if !lenient && !self.config.is_legacy_raw() {
+            return self.deserialize_any(visitor);
+        }
+
+        let marker = self.take_or_read_marker()?;
+        if let Some(len) = str_len(&mut self.rd, marker)? {
+            self.check_len(marker, len)?;
+            let strict = self.config.is_strict();
+            // The raw type may carry arbitrary bytes, so there is no UTF-8 validation unless
+            // strict mode asks for it.
+            return match read_bin_data(&mut self.rd, len)? {
+                Reference::Borrowed(buf) if strict => visitor.visit_borrowed_bytes(str::from_utf8(buf)?.as_bytes()),
+                Reference::Copied(buf) if strict => visitor.visit_bytes(str::from_utf8(buf)?.as_bytes()),
+                Reference::Borrowed(buf) => visitor.visit_borrowed_bytes(buf),
+                Reference::Copied(buf) => visitor.visit_bytes(buf),
+            };
+        }
+        if lenient {
+            if let Some(len) = array_len(&mut self.rd, marker)? {
+                self.check_len(marker, len)?;
+                return self.read_lenient_bytes(len, visitor);
+            }
+        }
+
+        self.marker = Some(marker);
+        self.deserialize_any(visitor)
+    }
+
+    #[inline]
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if self.config.bytes_mode() == BytesMode::Normal {
+            return self.deserialize_any(visitor);
+        }
+
+        let marker = self.take_or_read_marker()?;
+        let len = match bin_len(&mut self.rd, marker)? {
+            Some(len) => len,
+            None => {
+                self.marker = Some(marker);
+                return self.deserialize_any(visitor);
+            }
+        };
+        self.check_len(marker, len)?;
+
+        // Sequences of `u8` may have been written as `bin`, see `BytesMode`.
+        let buf = match read_bin_data(&mut self.rd, len)? {
+            Reference::Borrowed(buf) => buf,
+            Reference::Copied(buf) => buf,
+        };
+        let mut seq = de::value::SeqDeserializer::<_, Error>::new(buf.iter().copied());
+        let res = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(res)
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if self.config.is_lenient() {
+            return self.read_lenient_float(true, visitor);
+        }
+        match self.visit_slice_scalar(visitor) {
+            Ok(res) => res,
+            Err(visitor) => self.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if self.config.is_lenient() {
+            return self.read_lenient_float(false, visitor);
+        }
+        match self.visit_slice_scalar(visitor) {
+            Ok(res) => res,
+            Err(visitor) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if self.config.is_lenient() {
+            let marker = self.take_or_read_marker()?;
+            if let Some(len) = bin_len(&mut self.rd, marker)? {
+                self.check_len(marker, len)?;
+                return match read_bin_data(&mut self.rd, len)? {
+                    Reference::Borrowed(buf) => visitor.visit_borrowed_str(str::from_utf8(buf).map_err(Error::Utf8Error)?),
+                    Reference::Copied(buf) => visitor.visit_str(str::from_utf8(buf).map_err(Error::Utf8Error)?),
+                };
+            }
+            self.marker = Some(marker);
+            return self.deserialize_any(visitor);
+        }
+        match self.visit_slice_str(visitor) {
+            Ok(res) => res,
+            Err(visitor) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_struct<V>(self, _name: &'static str, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if self.config.is_strict() {
+            let marker = self.take_or_read_marker()?;
+            if let Some(len) = map_len(&mut self.rd, marker)? {
+                self.check_len(marker, len)?;
+                return depth_count!(self.depth, {
+                    let mut map = StrictMapAccess::new(self, len, true);
+                    let res = visitor.visit_map(&mut map)?;
+                    match map.left {
+                        0 => Ok(res),
+                        excess => Err(Error::LengthMismatch(len - excess)),
+                    }
+                });
+            }
+            self.marker = Some(marker);
+        }
+        self.deserialize_any(visitor)
+    }
+
+    #[inline]
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        self.deserialize_str(visitor)
+    }
+
+    deserialize_lenient_int! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        // Strict mode has to validate the ignored values as well.
+        if self.config.is_strict() {
+            return self.deserialize_any(visitor);
+        }
+        self.skip_value()?;
+        visitor.visit_unit()
+    }
+
+    #[inline]
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        match self.visit_slice_scalar(visitor) {
+            Ok(res) => res,
+            Err(visitor) => self.deserialize_any(visitor),
+        }
+    }
+
     forward_to_deserialize_any! {
-        bool u8 u16 u32 u64 i8 i16 i32 i64 f32
-        f64 char str string bytes byte_buf unit
-        seq map struct identifier tuple
-        tuple_struct ignored_any
+        char unit map identifier tuple_struct
     }
 }
 
@@ -765,6 +1713,133 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::MapAccess<'de> fo
     }
 }
 
+/// Map access in strict mode, which rejects duplicate keys.
+///
+/// Keys are compared if they are strings or integers, the keys struct fields are identified by.
+/// A struct read with `deserialize_struct` must only have such keys, while other maps may also
+/// have keys of other types, which are not compared.
+struct StrictMapAccess<'a, 'de, R, C> {
+    de: &'a mut Deserializer<R, C>,
+    left: u32,
+    seen: BTreeSet<StructKey<'de>>,
+    is_struct: bool,
+}
+
+impl<'a, 'de, R: 'a, C> StrictMapAccess<'a, 'de, R, C> {
+    fn new(de: &'a mut Deserializer<R, C>, len: u32, is_struct: bool) -> Self {
+        StrictMapAccess {
+            de,
+            left: len,
+            seen: BTreeSet::new(),
+            is_struct,
+        }
+    }
+}
+
+impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::MapAccess<'de> for StrictMapAccess<'a, 'de, R, C> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+        where K: DeserializeSeed<'de>
+    {
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+
+        if !self.is_struct && !is_struct_key(self.de.peek_or_read_marker()?) {
+            return seed.deserialize(&mut *self.de).map(Some);
+        }
+        let key = StructKey::deserialize(&mut *self.de)?;
+        if !self.seen.insert(key.clone()) {
+            let kind = if self.is_struct { "struct" } else { "map" };
+            return Err(Error::Syntax(format!("duplicate key {:?} in {}", key, kind)));
+        }
+
+        match key {
+            StructKey::Str(Cow::Borrowed(s)) => seed.deserialize(de::value::BorrowedStrDeserializer::new(s)),
+            StructKey::Str(Cow::Owned(s)) => seed.deserialize(de::value::StringDeserializer::new(s)),
+            StructKey::Unsigned(v) => seed.deserialize(de::value::U64Deserializer::new(v)),
+            StructKey::Signed(v) => seed.deserialize(de::value::I64Deserializer::new(v)),
+        }.map(Some)
+    }
+
+    #[inline]
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+        where V: DeserializeSeed<'de>
+    {
+        seed.deserialize(&mut *self.de)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> Option<usize> {
+        self.left.try_into().ok()
+    }
+}
+
+/// Returns whether the marker starts a string or an integer, see `StructKey`.
+fn is_struct_key(marker: Marker) -> bool {
+    matches!(
+        marker,
+        Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 |
+        Marker::FixPos(..) | Marker::FixNeg(..) |
+        Marker::U8 | Marker::U16 | Marker::U32 | Marker::U64 |
+        Marker::I8 | Marker::I16 | Marker::I32 | Marker::I64
+    )
+}
+
+/// A key of a struct encoded as a map: a field name, or a field index for some formats.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum StructKey<'de> {
+    Str(Cow<'de, str>),
+    Unsigned(u64),
+    Signed(i64),
+}
+
+impl fmt::Debug for StructKey<'_> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            StructKey::Str(ref s) => write!(fmt, "{:?}", s),
+            StructKey::Unsigned(v) => write!(fmt, "{}", v),
+            StructKey::Signed(v) => write!(fmt, "{}", v),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for StructKey<'de> {
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        struct StructKeyVisitor;
+
+        impl<'de> Visitor<'de> for StructKeyVisitor {
+            type Value = StructKey<'de>;
+
+            fn expecting(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+                fmt.write_str("a string or an integer")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(StructKey::Unsigned(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(StructKey::Signed(v))
+            }
+
+            fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+                Ok(StructKey::Str(Cow::Borrowed(v)))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(StructKey::Str(Cow::Owned(v.into())))
+            }
+        }
+
+        de.deserialize_any(StructKeyVisitor)
+    }
+}
+
 struct UnitVariantAccess<'a, R: 'a, C> {
     de: &'a mut Deserializer<R, C>,
 }
@@ -902,16 +1977,83 @@ pub enum Reference<'b, 'c, T: ?Sized + 'static> {
 /// Used to allow zero-copy reading.
 pub trait ReadSlice<'de>: Read {
     /// Reads the exact number of bytes from the underlying byte-array.
-    fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a, [u8]>, io::Error>;
+    fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a, [u8]>, ReadError>;
+
+    /// Skips the exact number of bytes.
+    ///
+    /// The default implementation goes through `read_slice`, which only advances the cursor of
+    /// readers over an in-memory buffer. Readers that copy override it to discard the bytes
+    /// without buffering them.
+    #[inline]
+    fn skip(&mut self, len: usize) -> Result<(), ReadError> {
+        self.read_slice(len).map(|_| ())
+    }
+
+    /// Returns the unread part of an in-memory input, or `None` for readers that copy.
+    ///
+    /// The `Deserializer` decodes hot primitives straight from this slice, checking bounds once per
+    /// value instead of going through `Read` for the marker and each piece of data. Only the
+    /// readers of this crate provide it, as other crates can neither name the argument to override
+    /// this method nor call it.
+    #[doc(hidden)]
+    #[inline(always)]
+    fn remaining_buf(&mut self, _: sealed::Local) -> Option<&mut &'de [u8]> {
+        None
+    }
+}
+
+mod sealed {
+    /// An argument that only this crate can name, which seals the methods taking it.
+    #[derive(Clone, Copy, Debug)]
+    pub struct Local;
+}
+
+/// Returns the error of reading past the end of a slice.
+#[cfg(feature = "std")]
+#[inline]
+fn slice_eof() -> ReadError {
+    io::ErrorKind::UnexpectedEof.into()
+}
+
+/// Returns the error of reading past the end of a slice.
+#[cfg(not(feature = "std"))]
+#[inline]
+fn slice_eof() -> ReadError {
+    // The error type of `&[u8]` depends on whether `rmp` is built with `std`, so it can't be
+    // named here, and an empty slice produces it instead.
+    match RmpRead::read_u8(&mut &[][..]) {
+        Err(err) => err,
+        Ok(..) => unreachable!("an empty slice has no bytes to read"),
+    }
+}
+
+impl<'de> ReadSlice<'de> for &'de [u8] {
+    #[inline]
+    fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a, [u8]>, ReadError> {
+        if len > self.len() {
+            *self = &[];
+            return Err(slice_eof());
+        }
+        let (a, b) = self.split_at(len);
+        *self = b;
+        Ok(Reference::Borrowed(a))
+    }
+
+    #[inline(always)]
+    fn remaining_buf(&mut self, _: sealed::Local) -> Option<&mut &'de [u8]> {
+        Some(self)
+    }
 }
 
 /// Owned reader wrapper.
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub struct ReadReader<R: Read> {
     rd: R,
     buf: Vec<u8>,
 }
 
+#[cfg(feature = "std")]
 impl<R: Read> ReadReader<R> {
     #[inline]
     fn new(rd: R) -> Self {
@@ -922,6 +2064,7 @@ impl<R: Read> ReadReader<R> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'de, R: Read> ReadSlice<'de> for ReadReader<R> {
     #[inline]
     fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a, [u8]>, io::Error> {
@@ -933,8 +2076,19 @@ impl<'de, R: Read> ReadSlice<'de> for ReadReader<R> {
 
         Ok(Reference::Copied(&self.buf[..]))
     }
+
+    #[inline]
+    fn skip(&mut self, len: usize) -> Result<(), io::Error> {
+        let skipped = io::copy(&mut self.rd.by_ref().take(len as u64), &mut io::sink())?;
+        if skipped != len as u64 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(())
+    }
 }
 
+#[cfg(feature = "std")]
 impl<R: Read> Read for ReadReader<R> {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -948,12 +2102,14 @@ impl<R: Read> Read for ReadReader<R> {
 }
 
 /// Borrowed reader wrapper.
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub struct ReadRefReader<'a, R: ?Sized> {
     whole_slice: &'a R,
     buf: &'a [u8],
 }
 
+#[cfg(feature = "std")]
 impl<'a, T> ReadRefReader<'a, T> {
     /// Returns the part that hasn't been consumed yet
     pub fn remaining_slice(&self) -> &'a [u8] {
@@ -961,6 +2117,7 @@ impl<'a, T> ReadRefReader<'a, T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'a, T: AsRef<[u8]> + ?Sized> ReadRefReader<'a, T> {
     #[inline]
     fn new(rd: &'a T) -> Self {
@@ -971,6 +2128,7 @@ impl<'a, T: AsRef<[u8]> + ?Sized> ReadRefReader<'a, T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'a, T: AsRef<[u8]> + ?Sized> Read for ReadRefReader<'a, T> {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
@@ -983,6 +2141,7 @@ impl<'a, T: AsRef<[u8]> + ?Sized> Read for ReadRefReader<'a, T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'de, T: AsRef<[u8]> + ?Sized> ReadSlice<'de> for ReadRefReader<'de, T> {
     #[inline]
     fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a, [u8]>, io::Error> {
@@ -993,8 +2152,14 @@ impl<'de, T: AsRef<[u8]> + ?Sized> ReadSlice<'de> for ReadRefReader<'de, T> {
         self.buf = b;
         Ok(Reference::Borrowed(a))
     }
+
+    #[inline(always)]
+    fn remaining_buf(&mut self, _: sealed::Local) -> Option<&mut &'de [u8]> {
+        Some(&mut self.buf)
+    }
 }
 
+#[cfg(feature = "std")]
 #[test]
 fn test_as_ref_reader() {
     let buf = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
@@ -1013,6 +2178,7 @@ fn test_as_ref_reader() {
 /// This conversion can fail if the structure of the Value does not match the structure expected
 /// by `T`. It can also fail if the structure is correct but `T`'s implementation of `Deserialize`
 /// decides that something is wrong with the data, for example required struct fields are missing.
+#[cfg(feature = "std")]
 #[inline]
 pub fn from_read<R, T>(rd: R) -> Result<T, Error>
 where R: Read,
@@ -1049,14 +2215,201 @@ where R: Read,
 /// assert_eq!(Dog { name: "Bobby", age: 8 }, rmp_serde::from_slice(&buf).unwrap());
 /// ```
 #[inline(always)]
-#[allow(deprecated)]
 pub fn from_slice<'a, T>(input: &'a [u8]) -> Result<T, Error>
 where
     T: Deserialize<'a>
 {
-    from_read_ref(input)
+    Deserialize::deserialize(&mut Deserializer::from_slice(input))
+}
+
+/// A step of the path to a nested value, see [`from_slice_at`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathSegment<'a> {
+    /// Selects the entry of a map with the given string key.
+    Key(&'a str),
+    /// Selects the element of an array at the given position, or the entry of a map with the
+    /// given integer key.
+    Index(usize),
+}
+
+impl<'a> From<&'a str> for PathSegment<'a> {
+    #[inline]
+    fn from(key: &'a str) -> Self {
+        PathSegment::Key(key)
+    }
+}
+
+impl From<usize> for PathSegment<'_> {
+    #[inline]
+    fn from(idx: usize) -> Self {
+        PathSegment::Index(idx)
+    }
+}
+
+/// Deserialize an instance of type `T` from the value nested in a slice at the given path, with
+/// zero-copy if possible.
+///
+/// Only the markers and lengths of the values before the addressed one are read to skip them, so
+/// extracting a single field is cheap even for large messages. Nothing after the addressed value
+/// is read. An empty path addresses the whole message, like [`from_slice`].
+///
+/// # Errors
+///
+/// Fails with [`Error::Syntax`] naming the position of the segment, such as "path not found at
+/// segment 1", if a segment addresses a missing element or key, or is applied to a value that is
+/// not an array or map. Use [`from_slice_at_opt`] to tell a missing value apart from malformed
+/// input. Otherwise fails like [`from_slice`].
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate serde_derive;
+/// use rmp_serde::decode::PathSegment;
+///
+/// #[derive(Serialize)]
+/// struct Header<'a> {
+///     tenant: &'a str,
+///     ids: Vec<u32>,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Message<'a> {
+///     header: Header<'a>,
+///     body: &'a str,
+/// }
+///
+/// let msg = Message { header: Header { tenant: "acme", ids: vec![7, 8] }, body: "..." };
+/// let buf = rmp_serde::to_vec_named(&msg).unwrap();
+///
+/// let tenant: &str = rmp_serde::from_slice_at(&buf, &["header".into(), "tenant".into()]).unwrap();
+/// assert_eq!("acme", tenant);
+///
+/// let id: u32 = rmp_serde::from_slice_at(&buf, &[PathSegment::Key("header"), "ids".into(), 1.into()]).unwrap();
+/// assert_eq!(8, id);
+/// ```
+#[inline]
+pub fn from_slice_at<'a, T>(input: &'a [u8], path: &[PathSegment<'_>]) -> Result<T, Error>
+where
+    T: Deserialize<'a>
+{
+    let mut de = Deserializer::from_slice(input);
+    de.seek_path(path)?.map_err(path_not_found)?;
+    Deserialize::deserialize(&mut de)
+}
+
+/// Deserialize an instance of type `T` from the value nested in a slice at the given path like
+/// [`from_slice_at`], or return `None` if there is no value at that path.
+///
+/// # Errors
+///
+/// Fails like [`from_slice`] if the input is malformed or the addressed value doesn't match `T`.
+///
+/// # Examples
+///
+/// ```
+/// let buf = rmp_serde::to_vec(&(1, [2, 3])).unwrap();
+///
+/// assert_eq!(Some(3), rmp_serde::from_slice_at_opt::<u8>(&buf, &[1.into(), 1.into()]).unwrap());
+/// assert_eq!(None, rmp_serde::from_slice_at_opt::<u8>(&buf, &[1.into(), 2.into()]).unwrap());
+/// ```
+#[inline]
+pub fn from_slice_at_opt<'a, T>(input: &'a [u8], path: &[PathSegment<'_>]) -> Result<Option<T>, Error>
+where
+    T: Deserialize<'a>
+{
+    let mut de = Deserializer::from_slice(input);
+    if de.seek_path(path)?.is_err() {
+        return Ok(None);
+    }
+    Deserialize::deserialize(&mut de).map(Some)
+}
+
+/// Deserialize the elements of an array in a slice one by one, carrying on after an element that
+/// fails to deserialize.
+///
+/// The returned iterator yields a `Result` for every element. After an error it skips the rest
+/// of the failed element by its markers and lengths, so that a corrupt or mismatched element
+/// only fails on its own. Elements are deserialized with zero-copy if possible.
+///
+/// # Errors
+///
+/// Fails if the input doesn't start with an array. The iterator yields the error of every
+/// element that fails to deserialize, and ends early if an element can't be skipped either,
+/// for example because the input is truncated.
+///
+/// # Examples
+///
+/// ```
+/// // Encoded `[1, "two", 3]`.
+/// let buf = [0x93, 0x01, 0xa3, 0x74, 0x77, 0x6f, 0x03];
+///
+/// let vals: Vec<_> = rmp_serde::decode::from_slice_each::<u8>(&buf).unwrap().collect();
+///
+/// assert_eq!(1, *vals[0].as_ref().unwrap());
+/// assert!(vals[1].is_err());
+/// assert_eq!(3, *vals[2].as_ref().unwrap());
+/// ```
+pub fn from_slice_each<'a, T>(input: &'a [u8]) -> Result<SliceEach<'a, T>, Error>
+where
+    T: Deserialize<'a>
+{
+    let mut de = Deserializer::from_slice(input);
+    let marker = de.take_or_read_marker()?;
+    let remaining = match array_len(&mut de.rd, marker)? {
+        Some(len) => len,
+        None => return Err(Error::TypeMismatch(marker)),
+    };
+    Ok(SliceEach { de, remaining, _marker: PhantomData })
+}
+
+/// An iterator over the elements of an array in a slice, see [`from_slice_each`].
+#[derive(Debug)]
+pub struct SliceEach<'de, T> {
+    de: Deserializer<&'de [u8]>,
+    remaining: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'de, T> SliceEach<'de, T> {
+    /// Returns the part of the input after the elements consumed so far.
+    #[inline(always)]
+    pub fn remaining_slice(&self) -> &'de [u8] {
+        self.de.rd
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Iterator for SliceEach<'de, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let (start, depth) = (self.de.rd, self.de.depth);
+        let err = match T::deserialize(&mut self.de) {
+            Ok(val) => return Some(Ok(val)),
+            Err(err) => err,
+        };
+        // Start over from the beginning of the element, which the failed attempt left at an
+        // unknown position.
+        self.de.rd = start;
+        self.de.marker = None;
+        self.de.depth = depth;
+        if self.de.skip_value().is_err() {
+            self.remaining = 0;
+        }
+        Some(Err(err))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining as usize))
+    }
 }
 
+#[cfg(feature = "std")]
 #[inline]
 #[doc(hidden)]
 #[deprecated(note = "use from_slice")]
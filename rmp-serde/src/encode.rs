@@ -1,7 +1,11 @@
 //! Serialize a Rust data structure into MessagePack data.
 
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+#[cfg(feature = "std")]
 use std::error;
-use std::fmt::{self, Display};
+#[cfg(feature = "std")]
 use std::io::Write;
 
 use serde;
@@ -11,21 +15,58 @@ use serde::ser::{
 };
 use serde::Serialize;
 
-use rmp::encode::ValueWriteError;
-use rmp::{encode, Marker};
+use rmp::encode::{RmpWrite, RmpWriteErr, ValueWriteError};
+use rmp::encode;
 
 use crate::config::{
-    BinaryConfig, DefaultConfig, HumanReadableConfig, SerializerConfig, StructMapConfig,
-    StructTupleConfig
+    BinaryConfig, BytesMode, BytesModeConfig, CanonicalConfig, DefaultConfig, HumanReadableConfig,
+    LegacyRawConfig, SerializerConfig, StructMapConfig, StructTupleConfig
 };
 use crate::MSGPACK_EXT_STRUCT_NAME;
 
+use self::sink::{PatchBuffer, Sink};
+#[cfg(feature = "std")]
+use self::sink::{ByteCounter, LimitedBuffer};
+
+/// The error type of writing to a [`Write`] writer.
+#[cfg(feature = "std")]
+pub type WriteError = std::io::Error;
+
+/// The error type of writing to a [`Write`] writer, i.e. of writing past the end of a `&mut [u8]`.
+#[cfg(not(feature = "std"))]
+pub type WriteError = <&'static mut [u8] as RmpWrite>::Error;
+
+/// A writer the `Serializer` can write into without `std`.
+///
+/// This is any [`RmpWrite`] whose errors convert into [`WriteError`], such as `&mut [u8]` and
+/// `Vec<u8>`.
+#[cfg(not(feature = "std"))]
+pub trait Write: RmpWrite<Error = <Self as WriteErrorType>::Error> + WriteErrorType {}
+
+#[cfg(not(feature = "std"))]
+impl<W: RmpWrite + ?Sized> Write for W where W::Error: Into<WriteError> {}
+
+/// Names the error type of a [`Write`] writer along with its conversion into [`WriteError`].
+///
+/// A `where` clause on `Write` itself wouldn't be implied by a `W: Write` bound, so the bound is
+/// carried by this supertrait instead.
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+pub trait WriteErrorType {
+    type Error: RmpWriteErr + Into<WriteError>;
+}
+
+#[cfg(not(feature = "std"))]
+impl<W: RmpWrite + ?Sized> WriteErrorType for W where W::Error: Into<WriteError> {
+    type Error = W::Error;
+}
+
 /// This type represents all possible errors that can occur when serializing or
 /// deserializing MessagePack data.
 #[derive(Debug)]
 pub enum Error {
     /// Failed to write a MessagePack value.
-    InvalidValueWrite(ValueWriteError),
+    InvalidValueWrite(ValueWriteError<WriteError>),
     //TODO: This can be removed at some point
     /// Failed to serialize struct, sequence or map, because its length is unknown.
     UnknownLength,
@@ -37,6 +78,7 @@ pub enum Error {
     Syntax(String),
 }
 
+#[cfg(feature = "std")]
 impl error::Error for Error {
     #[cold]
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
@@ -50,6 +92,9 @@ impl error::Error for Error {
     }
 }
 
+#[cfg(not(feature = "std"))]
+impl serde::ser::StdError for Error {}
+
 impl Display for Error {
     #[cold]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
@@ -65,10 +110,13 @@ impl Display for Error {
     }
 }
 
-impl From<ValueWriteError> for Error {
+impl<E: RmpWriteErr + Into<WriteError>> From<ValueWriteError<E>> for Error {
     #[cold]
-    fn from(err: ValueWriteError) -> Error {
-        Error::InvalidValueWrite(err)
+    fn from(err: ValueWriteError<E>) -> Error {
+        Error::InvalidValueWrite(match err {
+            ValueWriteError::InvalidMarkerWrite(err) => ValueWriteError::InvalidMarkerWrite(err.into()),
+            ValueWriteError::InvalidDataWrite(err) => ValueWriteError::InvalidDataWrite(err.into()),
+        })
     }
 }
 
@@ -97,6 +145,573 @@ pub trait UnderlyingWrite {
     fn into_inner(self) -> Self::Write;
 }
 
+mod sink {
+    use alloc::vec::Vec;
+
+    use rmp::encode::{self, ValueWriteError};
+    #[cfg(feature = "std")]
+    use rmp::encode::RmpWrite;
+
+    use super::{Error, Write};
+
+    /// The output of a `Serializer`: either a [`Write`] writer, a `PatchBuffer` or a `ByteCounter`.
+    pub trait Sink {
+        /// The writer the values are encoded with.
+        type Write: Write;
+
+        fn writer(&mut self) -> &mut Self::Write;
+
+        fn writer_ref(&self) -> &Self::Write;
+
+        fn into_writer(self) -> Self::Write;
+
+        /// Reserves space for the header of a sequence or map of unknown length, returning its
+        /// offset, or `None` if the elements must be buffered until the length is known.
+        #[inline(always)]
+        fn reserve_header(&mut self) -> Option<usize> {
+            None
+        }
+
+        /// Writes the array or map header reserved at `offset`.
+        fn patch_header(&mut self, _offset: usize, _is_map: bool, _len: u32) -> Result<(), Error> {
+            unreachable!("header patched without having been reserved")
+        }
+
+        // The values the `Serializer` writes most often. In-memory sinks, which can't fail,
+        // override these to write each value with a single append.
+
+        #[inline]
+        fn write_nil(&mut self) -> Result<(), Error> {
+            encode::write_nil(self.writer())
+                .map_err(|err| Error::from(ValueWriteError::InvalidMarkerWrite(err)))
+        }
+
+        #[inline]
+        fn write_bool(&mut self, val: bool) -> Result<(), Error> {
+            encode::write_bool(self.writer(), val)
+                .map_err(|err| Error::from(ValueWriteError::InvalidMarkerWrite(err)))
+        }
+
+        #[inline]
+        fn write_uint(&mut self, val: u64) -> Result<(), Error> {
+            encode::write_uint(self.writer(), val)?;
+            Ok(())
+        }
+
+        #[inline]
+        fn write_sint(&mut self, val: i64) -> Result<(), Error> {
+            encode::write_sint(self.writer(), val)?;
+            Ok(())
+        }
+
+        #[inline]
+        fn write_f32(&mut self, val: f32) -> Result<(), Error> {
+            encode::write_f32(self.writer(), val)?;
+            Ok(())
+        }
+
+        #[inline]
+        fn write_f64(&mut self, val: f64) -> Result<(), Error> {
+            encode::write_f64(self.writer(), val)?;
+            Ok(())
+        }
+
+        #[inline]
+        fn write_str(&mut self, val: &str) -> Result<(), Error> {
+            encode::write_str(self.writer(), val)?;
+            Ok(())
+        }
+
+        #[inline]
+        fn write_bin(&mut self, val: &[u8]) -> Result<(), Error> {
+            encode::write_bin(self.writer(), val)?;
+            Ok(())
+        }
+
+        #[inline]
+        fn write_array_len(&mut self, len: u32) -> Result<(), Error> {
+            encode::write_array_len(self.writer(), len)?;
+            Ok(())
+        }
+
+        #[inline]
+        fn write_map_len(&mut self, len: u32) -> Result<(), Error> {
+            encode::write_map_len(self.writer(), len)?;
+            Ok(())
+        }
+    }
+
+    impl<W: Write> Sink for W {
+        type Write = W;
+
+        #[inline(always)]
+        fn writer(&mut self) -> &mut W {
+            self
+        }
+
+        #[inline(always)]
+        fn writer_ref(&self) -> &W {
+            self
+        }
+
+        #[inline(always)]
+        fn into_writer(self) -> W {
+            self
+        }
+    }
+
+    /// The length of the widest header, i.e. of an `array32` or `map32` marker and its `u32`.
+    const MAX_HEADER_LEN: usize = 5;
+
+    /// A buffer for sequences and maps whose length is not known until their end.
+    ///
+    /// A sequence or map of unknown length nested in the buffer reserves `MAX_HEADER_LEN` bytes for
+    /// its header and encodes its elements right after them. On `end()` the header is patched in
+    /// and the bytes it leaves unused are recorded as a gap, which is skipped when the buffer is
+    /// written out. This way the elements are copied only once however deeply they are nested,
+    /// while the output is the same as if their lengths had been known upfront.
+    #[derive(Debug)]
+    pub struct PatchBuffer {
+        buf: Vec<u8>,
+        /// Unused header bytes as `(offset, len)`, in the order the headers have been patched.
+        gaps: Vec<(usize, usize)>,
+        /// The total length of the gaps.
+        gaps_len: usize,
+    }
+
+    impl PatchBuffer {
+        #[inline]
+        pub fn with_capacity(capacity: usize) -> Self {
+            Self { buf: Vec::with_capacity(capacity), gaps: Vec::new(), gaps_len: 0 }
+        }
+
+        /// Returns the number of encoded bytes, counting headers that haven't been patched yet
+        /// as `MAX_HEADER_LEN` bytes long.
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.buf.len() - self.gaps_len
+        }
+
+        /// Writes the encoded values to `wr`, skipping the gaps.
+        pub fn write_to<W: Write>(mut self, wr: &mut W) -> Result<(), Error> {
+            self.gaps.sort_unstable();
+
+            let mut start = 0;
+            for &(offset, len) in &self.gaps {
+                wr.write_bytes(&self.buf[start..offset])
+                    .map_err(|err| Error::from(ValueWriteError::InvalidDataWrite(err)))?;
+                start = offset + len;
+            }
+            wr.write_bytes(&self.buf[start..])
+                .map_err(|err| Error::from(ValueWriteError::InvalidDataWrite(err)))
+        }
+
+        /// Closes the gaps in place, moving each byte at most once.
+        ///
+        /// The ascending `offsets` into the buffer are adjusted accordingly.
+        pub fn close_gaps(&mut self, offsets: &mut [usize]) {
+            if self.gaps.is_empty() {
+                return;
+            }
+            self.gaps.sort_unstable();
+
+            let mut gaps = self.gaps.iter().peekable();
+            let mut removed = 0;
+            for offset in offsets {
+                while let Some(&(_, len)) = gaps.next_if(|&&(gap, _)| gap < *offset) {
+                    removed += len;
+                }
+                *offset -= removed;
+            }
+
+            let mut start = 0;
+            let mut end = 0;
+            for &(offset, len) in &self.gaps {
+                if start != end {
+                    self.buf.copy_within(start..offset, end);
+                }
+                end += offset - start;
+                start = offset + len;
+            }
+            self.buf.copy_within(start.., end);
+            end += self.buf.len() - start;
+            self.buf.truncate(end);
+            self.gaps.clear();
+            self.gaps_len = 0;
+        }
+
+        /// Returns the encoded values with the gaps closed.
+        #[inline]
+        pub fn into_vec(mut self) -> Vec<u8> {
+            self.close_gaps(&mut []);
+            self.buf
+        }
+    }
+
+    impl Sink for PatchBuffer {
+        type Write = Vec<u8>;
+
+        #[inline(always)]
+        fn writer(&mut self) -> &mut Vec<u8> {
+            &mut self.buf
+        }
+
+        #[inline(always)]
+        fn writer_ref(&self) -> &Vec<u8> {
+            &self.buf
+        }
+
+        #[inline(always)]
+        fn into_writer(self) -> Vec<u8> {
+            self.into_vec()
+        }
+
+        #[inline]
+        fn reserve_header(&mut self) -> Option<usize> {
+            let offset = self.buf.len();
+            self.buf.extend_from_slice(&[0; MAX_HEADER_LEN]);
+            Some(offset)
+        }
+
+        fn patch_header(&mut self, offset: usize, is_map: bool, len: u32) -> Result<(), Error> {
+            let mut header = [0; MAX_HEADER_LEN];
+            let mut wr = &mut header[..];
+            if is_map {
+                encode::write_map_len(&mut wr, len)?;
+            } else {
+                encode::write_array_len(&mut wr, len)?;
+            }
+            let header_len = MAX_HEADER_LEN - wr.len();
+
+            self.buf[offset..offset + header_len].copy_from_slice(&header[..header_len]);
+            if header_len < MAX_HEADER_LEN {
+                self.gaps.push((offset + header_len, MAX_HEADER_LEN - header_len));
+                self.gaps_len += MAX_HEADER_LEN - header_len;
+            }
+            Ok(())
+        }
+
+        #[inline]
+        fn write_nil(&mut self) -> Result<(), Error> {
+            self.buf.push(0xc0);
+            Ok(())
+        }
+
+        #[inline]
+        fn write_bool(&mut self, val: bool) -> Result<(), Error> {
+            self.buf.push(if val { 0xc3 } else { 0xc2 });
+            Ok(())
+        }
+
+        #[inline]
+        fn write_uint(&mut self, val: u64) -> Result<(), Error> {
+            write_vec_uint(&mut self.buf, val);
+            Ok(())
+        }
+
+        #[inline]
+        fn write_sint(&mut self, val: i64) -> Result<(), Error> {
+            if val >= 0 {
+                write_vec_uint(&mut self.buf, val as u64);
+            } else if val >= -32 {
+                self.buf.push(val as u8);
+            } else if val >= -128 {
+                put(&mut self.buf, 0xd0, (val as i8).to_be_bytes());
+            } else if val >= -32768 {
+                put(&mut self.buf, 0xd1, (val as i16).to_be_bytes());
+            } else if val >= -2147483648 {
+                put(&mut self.buf, 0xd2, (val as i32).to_be_bytes());
+            } else {
+                put(&mut self.buf, 0xd3, val.to_be_bytes());
+            }
+            Ok(())
+        }
+
+        #[inline]
+        fn write_f32(&mut self, val: f32) -> Result<(), Error> {
+            put(&mut self.buf, 0xca, val.to_be_bytes());
+            Ok(())
+        }
+
+        #[inline]
+        fn write_f64(&mut self, val: f64) -> Result<(), Error> {
+            put(&mut self.buf, 0xcb, val.to_be_bytes());
+            Ok(())
+        }
+
+        #[inline]
+        fn write_str(&mut self, val: &str) -> Result<(), Error> {
+            let len = val.len() as u32;
+            if len < 32 {
+                self.buf.push(0xa0 | len as u8);
+            } else if len < 256 {
+                put(&mut self.buf, 0xd9, (len as u8).to_be_bytes());
+            } else if len < 65536 {
+                put(&mut self.buf, 0xda, (len as u16).to_be_bytes());
+            } else {
+                put(&mut self.buf, 0xdb, len.to_be_bytes());
+            }
+            self.buf.extend_from_slice(val.as_bytes());
+            Ok(())
+        }
+
+        #[inline]
+        fn write_bin(&mut self, val: &[u8]) -> Result<(), Error> {
+            let len = val.len() as u32;
+            if len < 256 {
+                put(&mut self.buf, 0xc4, (len as u8).to_be_bytes());
+            } else if len < 65536 {
+                put(&mut self.buf, 0xc5, (len as u16).to_be_bytes());
+            } else {
+                put(&mut self.buf, 0xc6, len.to_be_bytes());
+            }
+            self.buf.extend_from_slice(val);
+            Ok(())
+        }
+
+        #[inline]
+        fn write_array_len(&mut self, len: u32) -> Result<(), Error> {
+            if len < 16 {
+                self.buf.push(0x90 | len as u8);
+            } else if len < 65536 {
+                put(&mut self.buf, 0xdc, (len as u16).to_be_bytes());
+            } else {
+                put(&mut self.buf, 0xdd, len.to_be_bytes());
+            }
+            Ok(())
+        }
+
+        #[inline]
+        fn write_map_len(&mut self, len: u32) -> Result<(), Error> {
+            if len < 16 {
+                self.buf.push(0x80 | len as u8);
+            } else if len < 65536 {
+                put(&mut self.buf, 0xde, (len as u16).to_be_bytes());
+            } else {
+                put(&mut self.buf, 0xdf, len.to_be_bytes());
+            }
+            Ok(())
+        }
+    }
+
+    /// A `PatchBuffer` that fails as soon as the encoded value exceeds a size limit.
+    ///
+    /// The length is checked after every value, so a value exceeding the limit is not buffered
+    /// further. Each header that hasn't been patched yet counts as a single byte, the least it
+    /// can shrink to, so the limit can't be exceeded by the reserved space alone. Bytes written
+    /// straight to the `writer` are counted by the next check.
+    #[cfg(feature = "std")]
+    #[derive(Debug)]
+    pub struct LimitedBuffer {
+        buf: PatchBuffer,
+        limit: usize,
+        /// The number of headers that have been reserved but not patched yet.
+        open: usize,
+    }
+
+    #[cfg(feature = "std")]
+    impl LimitedBuffer {
+        #[inline]
+        pub fn new(limit: usize) -> Self {
+            Self { buf: PatchBuffer::with_capacity(128), limit, open: 0 }
+        }
+
+        #[inline]
+        fn check(&self) -> Result<(), Error> {
+            if self.buf.len() - self.open * (MAX_HEADER_LEN - 1) > self.limit {
+                return Err(Error::Syntax(crate::config::SIZE_LIMIT_EXCEEDED.into()));
+            }
+            Ok(())
+        }
+
+        /// Returns the encoded values with the gaps closed.
+        #[inline]
+        pub fn into_vec(self) -> Result<Vec<u8>, Error> {
+            self.check()?;
+            Ok(self.buf.into_vec())
+        }
+
+        /// Writes the encoded values to `wr`, skipping the gaps.
+        #[inline]
+        pub fn write_to<W: Write>(self, wr: &mut W) -> Result<(), Error> {
+            self.check()?;
+            self.buf.write_to(wr)
+        }
+    }
+
+    /// Implements `Sink` methods writing a value to the inner `PatchBuffer`, then checking the
+    /// size limit.
+    #[cfg(feature = "std")]
+    macro_rules! limited_writes {
+        ($($name:ident($ty:ty);)*) => {
+            $(
+                #[inline]
+                fn $name(&mut self, val: $ty) -> Result<(), Error> {
+                    self.buf.$name(val)?;
+                    self.check()
+                }
+            )*
+        };
+    }
+
+    #[cfg(feature = "std")]
+    impl Sink for LimitedBuffer {
+        type Write = Vec<u8>;
+
+        #[inline(always)]
+        fn writer(&mut self) -> &mut Vec<u8> {
+            self.buf.writer()
+        }
+
+        #[inline(always)]
+        fn writer_ref(&self) -> &Vec<u8> {
+            self.buf.writer_ref()
+        }
+
+        #[inline(always)]
+        fn into_writer(self) -> Vec<u8> {
+            self.buf.into_writer()
+        }
+
+        #[inline]
+        fn reserve_header(&mut self) -> Option<usize> {
+            self.open += 1;
+            self.buf.reserve_header()
+        }
+
+        #[inline]
+        fn patch_header(&mut self, offset: usize, is_map: bool, len: u32) -> Result<(), Error> {
+            self.buf.patch_header(offset, is_map, len)?;
+            self.open -= 1;
+            self.check()
+        }
+
+        #[inline]
+        fn write_nil(&mut self) -> Result<(), Error> {
+            self.buf.write_nil()?;
+            self.check()
+        }
+
+        limited_writes! {
+            write_bool(bool);
+            write_uint(u64);
+            write_sint(i64);
+            write_f32(f32);
+            write_f64(f64);
+            write_str(&str);
+            write_bin(&[u8]);
+            write_array_len(u32);
+            write_map_len(u32);
+        }
+    }
+
+    /// Counts the bytes written to it, dropping them.
+    #[cfg(feature = "std")]
+    #[derive(Debug, Default)]
+    pub struct CountingWrite(pub usize);
+
+    #[cfg(feature = "std")]
+    impl std::io::Write for CountingWrite {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        #[inline]
+        fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+            self.0 += buf.len();
+            Ok(())
+        }
+
+        #[inline]
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Counts the bytes a value encodes to, without writing them anywhere.
+    ///
+    /// Unlike a plain `CountingWrite` it reserves the headers of sequences and maps of unknown
+    /// length like a `PatchBuffer`, instead of buffering their elements.
+    #[cfg(feature = "std")]
+    #[derive(Debug, Default)]
+    pub struct ByteCounter {
+        wr: CountingWrite,
+    }
+
+    #[cfg(feature = "std")]
+    impl ByteCounter {
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.wr.0
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl Sink for ByteCounter {
+        type Write = CountingWrite;
+
+        #[inline(always)]
+        fn writer(&mut self) -> &mut CountingWrite {
+            &mut self.wr
+        }
+
+        #[inline(always)]
+        fn writer_ref(&self) -> &CountingWrite {
+            &self.wr
+        }
+
+        #[inline(always)]
+        fn into_writer(self) -> CountingWrite {
+            self.wr
+        }
+
+        #[inline]
+        fn reserve_header(&mut self) -> Option<usize> {
+            let offset = self.wr.0;
+            self.wr.0 += MAX_HEADER_LEN;
+            Some(offset)
+        }
+
+        #[inline]
+        fn patch_header(&mut self, _offset: usize, _is_map: bool, len: u32) -> Result<(), Error> {
+            // Array and map headers of the same length are equally long.
+            let header_len = match len {
+                0..=15 => 1,
+                16..=65535 => 3,
+                _ => 5,
+            };
+            self.wr.0 -= MAX_HEADER_LEN - header_len;
+            Ok(())
+        }
+    }
+
+    /// Appends a marker and the big-endian data that follows it with a single copy.
+    #[inline(always)]
+    fn put<const N: usize>(buf: &mut Vec<u8>, marker: u8, data: [u8; N]) {
+        let mut bytes = [marker; 9];
+        bytes[1..=N].copy_from_slice(&data);
+        buf.extend_from_slice(&bytes[..=N]);
+    }
+
+    /// Appends an unsigned integer with the marker `rmp::encode::write_uint` would choose.
+    #[inline]
+    fn write_vec_uint(buf: &mut Vec<u8>, val: u64) {
+        if val < 128 {
+            buf.push(val as u8);
+        } else if val < 256 {
+            put(buf, 0xcc, (val as u8).to_be_bytes());
+        } else if val < 65536 {
+            put(buf, 0xcd, (val as u16).to_be_bytes());
+        } else if val < 4294967296 {
+            put(buf, 0xce, (val as u32).to_be_bytes());
+        } else {
+            put(buf, 0xcf, val.to_be_bytes());
+        }
+    }
+}
+
 /// Represents MessagePack serialization implementation.
 ///
 /// # Note
@@ -115,7 +730,8 @@ pub trait UnderlyingWrite {
 pub struct Serializer<W, C = DefaultConfig> {
     wr: W,
     config: C,
-    depth: usize,
+    /// The nesting depth left, or `None` if it is not limited.
+    depth: Option<usize>,
 }
 
 impl<W: Write, C> Serializer<W, C> {
@@ -139,13 +755,14 @@ impl<W: Write, C> Serializer<W, C> {
         self.wr
     }
 
-    /// Changes the maximum nesting depth that is allowed.
+    /// Changes the maximum nesting depth of sequences and maps that is allowed.
     ///
-    /// Currently unused.
+    /// Exceeding it fails serialization with `Error::DepthLimitExceeded`. By default the depth is
+    /// not limited.
     #[doc(hidden)]
     #[inline]
     pub fn unstable_set_max_depth(&mut self, depth: usize) {
-        self.depth = depth;
+        self.depth = Some(depth);
     }
 }
 
@@ -161,36 +778,79 @@ impl<W: Write> Serializer<W, DefaultConfig> {
     pub fn new(wr: W) -> Self {
         Serializer {
             wr,
-            depth: 1024,
+            depth: None,
             config: DefaultConfig,
         }
     }
 }
 
-impl<'a, W: Write + 'a, C> Serializer<W, C> {
+impl<'a, W: Sink + 'a, C> Serializer<W, C> {
     #[inline]
     fn compound(&'a mut self) -> Result<Compound<'a, W, C>, Error> {
         let c = Compound { se: self };
         Ok(c)
     }
+
+    /// Enters a nested sequence or map, failing if that exceeds the depth limit, if any.
+    #[inline]
+    fn enter(&mut self) -> Result<(), Error> {
+        if let Some(depth) = &mut self.depth {
+            if *depth <= 1 {
+                return Err(Error::DepthLimitExceeded);
+            }
+            *depth -= 1;
+        }
+        Ok(())
+    }
+
+    /// Leaves a nested sequence or map on its `end()`.
+    #[inline(always)]
+    fn leave(&mut self) {
+        if let Some(depth) = &mut self.depth {
+            *depth += 1;
+        }
+    }
 }
 
-impl<'a, W: Write + 'a, C: SerializerConfig> Serializer<W, C> {
+impl<'a, W: Sink + 'a, C: SerializerConfig> Serializer<W, C> {
     #[inline]
     fn maybe_unknown_len_compound<F>(&'a mut self, len: Option<usize>, f: F) -> Result<MaybeUnknownLengthCompound<'a, W, C>, Error>
-    where F: Fn(&mut W, u32) -> Result<Marker, ValueWriteError>
+    where F: Fn(&mut W, u32) -> Result<(), Error>
     {
         Ok(MaybeUnknownLengthCompound {
-            compound: match len {
+            elements: match len {
                 Some(len) => {
                     f(&mut self.wr, len as u32)?;
-                    None
+                    Elements::Direct
                 }
-                None => Some(UnknownLengthCompound::from(&*self)),
+                None => Elements::Counted(self.unknown_len()),
             },
             se: self,
         })
     }
+
+    /// Starts counting the elements of a sequence or map of unknown length, reserving its header
+    /// in the enclosing buffer if there is one.
+    #[inline]
+    fn unknown_len(&mut self) -> UnknownLength<C> {
+        match self.wr.reserve_header() {
+            Some(offset) => UnknownLength::Reserved { offset, elem_count: 0 },
+            None => UnknownLength::Buffered(UnknownLengthCompound::from(&*self)),
+        }
+    }
+
+    /// Returns a compound that holds the elements back as long as they are `u8`, to write them
+    /// as `bin` if the sequence turns out to contain nothing else.
+    #[inline]
+    fn pending_bytes_compound(&'a mut self, len: Option<usize>) -> MaybeUnknownLengthCompound<'a, W, C> {
+        MaybeUnknownLengthCompound {
+            se: self,
+            elements: Elements::Pending(PendingBytes {
+                buf: Vec::with_capacity(len.unwrap_or(0)),
+                len,
+            }),
+        }
+    }
 }
 
 impl<W: Write, C> Serializer<W, C> {
@@ -254,24 +914,68 @@ impl<W: Write, C> Serializer<W, C> {
             config: BinaryConfig::new(config),
         }
     }
+
+    /// Consumes this serializer returning the new one, which will produce identical bytes for
+    /// equal data.
+    ///
+    /// Map entries are sorted by their encoded keys and NaNs are normalized. This is useful when
+    /// the output is hashed or signed. See [`CanonicalConfig`] for details.
+    #[inline]
+    pub fn with_canonical(self) -> Serializer<W, CanonicalConfig<C>> {
+        let Serializer { wr, depth, config } = self;
+        Serializer {
+            wr,
+            depth,
+            config: CanonicalConfig::new(config),
+        }
+    }
+
+    /// Consumes this serializer returning the new one, which will write strings and bytes as the
+    /// raw type of the old MessagePack specification.
+    ///
+    /// Use it to talk to peers that predate the `str8` and `bin` types. See [`LegacyRawConfig`]
+    /// for details.
+    #[inline]
+    pub fn with_legacy_raw(self) -> Serializer<W, LegacyRawConfig<C>> {
+        let Serializer { wr, depth, config } = self;
+        Serializer {
+            wr,
+            depth,
+            config: LegacyRawConfig::new(config),
+        }
+    }
+
+    /// Consumes this serializer returning the new one, which will write sequences of `u8` as
+    /// `bin` according to the given mode.
+    ///
+    /// See [`BytesMode`] for details.
+    #[inline]
+    pub fn with_bytes(self, mode: BytesMode) -> Serializer<W, BytesModeConfig<C>> {
+        let Serializer { wr, depth, config } = self;
+        Serializer {
+            wr,
+            depth,
+            config: BytesModeConfig::new(config, mode),
+        }
+    }
 }
 
-impl<W: Write, C> UnderlyingWrite for Serializer<W, C> {
-    type Write = W;
+impl<W: Sink, C> UnderlyingWrite for Serializer<W, C> {
+    type Write = W::Write;
 
     #[inline(always)]
     fn get_ref(&self) -> &Self::Write {
-        &self.wr
+        self.wr.writer_ref()
     }
 
     #[inline(always)]
     fn get_mut(&mut self) -> &mut Self::Write {
-        &mut self.wr
+        self.wr.writer()
     }
 
     #[inline(always)]
     fn into_inner(self) -> Self::Write {
-        self.wr
+        self.wr.into_writer()
     }
 }
 
@@ -296,7 +1000,7 @@ pub struct ExtSerializer<'a, W> {
     tuple_received: bool,
 }
 
-impl<'a, W: Write + 'a, C: SerializerConfig> SerializeSeq for Compound<'a, W, C> {
+impl<'a, W: Sink + 'a, C: SerializerConfig> SerializeSeq for Compound<'a, W, C> {
     type Ok = ();
     type Error = Error;
 
@@ -307,11 +1011,12 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeSeq for Compound<'a, W, C>
 
     #[inline(always)]
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
         Ok(())
     }
 }
 
-impl<'a, W: Write + 'a, C: SerializerConfig> SerializeTuple for Compound<'a, W, C> {
+impl<'a, W: Sink + 'a, C: SerializerConfig> SerializeTuple for Compound<'a, W, C> {
     type Ok = ();
     type Error = Error;
 
@@ -322,11 +1027,12 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeTuple for Compound<'a, W,
 
     #[inline(always)]
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
         Ok(())
     }
 }
 
-impl<'a, W: Write + 'a, C: SerializerConfig> SerializeTupleStruct for Compound<'a, W, C> {
+impl<'a, W: Sink + 'a, C: SerializerConfig> SerializeTupleStruct for Compound<'a, W, C> {
     type Ok = ();
     type Error = Error;
 
@@ -337,11 +1043,12 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeTupleStruct for Compound<'
 
     #[inline(always)]
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
         Ok(())
     }
 }
 
-impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStruct for Compound<'a, W, C> {
+impl<'a, W: Sink + 'a, C: SerializerConfig> SerializeStruct for Compound<'a, W, C> {
     type Ok = ();
     type Error = Error;
 
@@ -349,16 +1056,17 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStruct for Compound<'a, W,
     fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) ->
         Result<(), Self::Error>
     {
-        C::write_struct_field(&mut *self.se, key, value)
+        self.se.config.write_struct_field(&mut *self.se, key, value)
     }
 
     #[inline(always)]
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
         Ok(())
     }
 }
 
-impl<'a, W: Write + 'a, C: SerializerConfig> SerializeTupleVariant for Compound<'a, W, C> {
+impl<'a, W: Sink + 'a, C: SerializerConfig> SerializeTupleVariant for Compound<'a, W, C> {
     type Ok = ();
     type Error = Error;
 
@@ -369,22 +1077,24 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeTupleVariant for Compound<
 
     #[inline(always)]
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
         Ok(())
     }
 }
 
-impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStructVariant for Compound<'a, W, C> {
+impl<'a, W: Sink + 'a, C: SerializerConfig> SerializeStructVariant for Compound<'a, W, C> {
     type Ok = ();
     type Error = Error;
 
     fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) ->
         Result<(), Self::Error>
     {
-        C::write_struct_field(&mut *self.se, key, value)
+        self.se.config.write_struct_field(&mut *self.se, key, value)
     }
 
     #[inline(always)]
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
         Ok(())
     }
 }
@@ -393,18 +1103,74 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStructVariant for Compound
 /// and a counter for the number of elements that are encoded by the `Serializer`.
 #[derive(Debug)]
 struct UnknownLengthCompound<C> {
-    se: Serializer<Vec<u8>, C>,
+    se: Serializer<PatchBuffer, C>,
     elem_count: u32,
+    /// Offsets at which each encoded element ends, recorded only if map entries must be sorted.
+    bounds: Option<Vec<usize>>,
 }
 impl<W, C: SerializerConfig> From<&Serializer<W, C>> for UnknownLengthCompound<C> {
     fn from(se: &Serializer<W, C>) -> Self {
         Self {
-            se: Serializer { wr: Vec::with_capacity(128), config: se.config, depth: se.depth },
-            elem_count: 0
+            se: Serializer { wr: PatchBuffer::with_capacity(128), config: se.config, depth: se.depth },
+            elem_count: 0,
+            bounds: None,
+        }
+    }
+}
+
+impl<C> UnknownLengthCompound<C> {
+    /// Writes the buffered map entries sorted by their encoded keys.
+    fn write_sorted_entries<W: Write>(self, wr: &mut W) -> Result<(), Error> {
+        let mut buf = self.se.wr;
+        let mut bounds = self.bounds.unwrap_or_default();
+        buf.close_gaps(&mut bounds);
+        let buf = buf.into_vec();
+
+        let mut entries = Vec::with_capacity(bounds.len() / 2);
+        let mut start = 0;
+        for pair in bounds.chunks_exact(2) {
+            entries.push((&buf[start..pair[0]], &buf[pair[0]..pair[1]]));
+            start = pair[1];
+        }
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        if entries.windows(2).any(|w| w[0].0 == w[1].0) {
+            return Err(Error::InvalidDataModel("canonical map contains duplicate keys"));
+        }
+
+        encode::write_map_len(wr, entries.len() as u32)?;
+        for (key, value) in entries {
+            wr.write_bytes(key).map_err(ValueWriteError::InvalidDataWrite)?;
+            wr.write_bytes(value).map_err(ValueWriteError::InvalidDataWrite)?;
         }
+        Ok(())
     }
 }
 
+/// Counts the elements of a sequence or map whose length is not yet known.
+#[derive(Debug)]
+enum UnknownLength<C> {
+    /// The elements are encoded into a buffer of their own, which is written after the length.
+    Buffered(UnknownLengthCompound<C>),
+    /// The elements are encoded into the enclosing `PatchBuffer`, after a header reserved at
+    /// `offset`, which is patched in on `end()`.
+    Reserved { offset: usize, elem_count: u32 },
+}
+
+/// How the elements of a `MaybeUnknownLengthCompound` are written.
+///
+/// This is a single enum, rather than an option for each case, so that writing an element of
+/// known length checks a single discriminant.
+#[derive(Debug)]
+enum Elements<C> {
+    /// The length has been written, and the elements follow it directly.
+    Direct,
+    /// The elements are counted, as the length is not known yet.
+    Counted(UnknownLength<C>),
+    /// The elements are held back, as they have all been `u8` so far.
+    Pending(PendingBytes),
+}
+
 /// Contains a `Serializer` for encoding elements of sequences and maps.
 ///
 /// # Note
@@ -415,7 +1181,9 @@ impl<W, C: SerializerConfig> From<&Serializer<W, C>> for UnknownLengthCompound<C
 /// length.
 ///
 /// For the described case a `UnknownLengthCompound` is used to encode the elements. On `end()`
-/// the counted length and the encoded elements will be written to the `Serializer`. A caveat is,
+/// the counted length and the encoded elements will be written to the `Serializer`. Sequences and
+/// maps nested in such a compound don't get a buffer of their own, but reserve space for their
+/// header in the enclosing one, so their elements are only copied once. A caveat is,
 /// that structs that contain flattened fields arem always written as a map, even when compact
 /// representaion is desired.
 ///
@@ -423,35 +1191,142 @@ impl<W, C: SerializerConfig> From<&Serializer<W, C>> for UnknownLengthCompound<C
 #[derive(Debug)]
 pub struct MaybeUnknownLengthCompound<'a, W: 'a, C: 'a> {
     se: &'a mut Serializer<W, C>,
-    compound: Option<UnknownLengthCompound<C>>,
+    elements: Elements<C>,
 }
 
-impl<'a, W: Write + 'a, C: SerializerConfig> SerializeSeq for MaybeUnknownLengthCompound<'a, W, C> {
-    type Ok = ();
-    type Error = Error;
+/// Elements of a sequence that have all been `u8` so far.
+///
+/// They are written as `bin` at the end of the sequence, unless an element of another type comes
+/// first, in which case they are written as the beginning of an array.
+#[derive(Debug)]
+struct PendingBytes {
+    buf: Vec<u8>,
+    len: Option<usize>,
+}
 
-    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
-        match self.compound.as_mut() {
-            None => value.serialize(&mut *self.se),
-            Some(buf) => {
+impl<'a, W: Sink + 'a, C: SerializerConfig> MaybeUnknownLengthCompound<'a, W, C> {
+    /// Gives up on writing the sequence as `bin`, writing the pending elements as integers.
+    fn write_pending_as_array(&mut self) -> Result<(), Error> {
+        if let Elements::Pending(bytes) = core::mem::replace(&mut self.elements, Elements::Direct) {
+            match bytes.len {
+                Some(len) => {
+                    self.se.wr.write_array_len(len as u32)?;
+                    for byte in bytes.buf {
+                        self.se.wr.write_uint(byte.into())?;
+                    }
+                }
+                None => {
+                    let mut compound = self.se.unknown_len();
+                    for &byte in &bytes.buf {
+                        match compound {
+                            UnknownLength::Buffered(ref mut buf) => {
+                                buf.se.wr.write_uint(byte.into())?;
+                            }
+                            UnknownLength::Reserved { .. } => {
+                                self.se.wr.write_uint(byte.into())?;
+                            }
+                        }
+                    }
+                    match compound {
+                        UnknownLength::Buffered(ref mut buf) => buf.elem_count = bytes.buf.len() as u32,
+                        UnknownLength::Reserved { ref mut elem_count, .. } => *elem_count = bytes.buf.len() as u32,
+                    }
+                    self.elements = Elements::Counted(compound);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes an element once there are no pending bytes, counting it if the length is unknown.
+    ///
+    /// This doesn't call back into `serialize_element`, so that it can be inlined into loops.
+    #[inline]
+    fn serialize_counted<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        match self.elements {
+            Elements::Direct | Elements::Pending(..) => value.serialize(&mut *self.se),
+            Elements::Counted(UnknownLength::Buffered(ref mut buf)) => {
                 value.serialize(&mut buf.se)?;
                 buf.elem_count += 1;
+                if let Some(bounds) = buf.bounds.as_mut() {
+                    bounds.push(buf.se.wr.writer().len());
+                }
+                Ok(())
+            }
+            Elements::Counted(UnknownLength::Reserved { ref mut elem_count, .. }) => {
+                value.serialize(&mut *self.se)?;
+                *elem_count += 1;
                 Ok(())
             }
         }
     }
 
+    /// Writes the counted length of a sequence or map of unknown length followed by its
+    /// elements, if they have been buffered.
+    fn end_unknown_len(self, is_map: bool) -> Result<(), Error> {
+        match self.elements {
+            Elements::Direct | Elements::Pending(..) => Ok(()),
+            Elements::Counted(UnknownLength::Buffered(compound)) => {
+                if compound.bounds.is_some() {
+                    return compound.write_sorted_entries(self.se.wr.writer());
+                }
+                if is_map {
+                    self.se.wr.write_map_len(compound.elem_count / 2)?;
+                } else {
+                    self.se.wr.write_array_len(compound.elem_count)?;
+                }
+                compound.se.wr.write_to(self.se.wr.writer())
+            }
+            Elements::Counted(UnknownLength::Reserved { offset, elem_count }) => {
+                let len = if is_map { elem_count / 2 } else { elem_count };
+                self.se.wr.patch_header(offset, is_map, len)
+            }
+        }
+    }
+}
+
+impl<'a, W: Sink + 'a, C: SerializerConfig> SerializeSeq for MaybeUnknownLengthCompound<'a, W, C> {
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        if let Elements::Pending(ref mut bytes) = self.elements {
+            if let Ok(byte) = value.serialize(ByteProbe) {
+                bytes.buf.push(byte);
+                return Ok(());
+            }
+            self.write_pending_as_array()?;
+        }
+        self.serialize_counted(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
+        match self.elements {
+            Elements::Pending(bytes) if bytes.buf.is_empty() => self.se.wr.write_array_len(0),
+            Elements::Pending(bytes) => serde::Serializer::serialize_bytes(self.se, &bytes.buf),
+            _ => self.end_unknown_len(false),
+        }
+    }
+}
+
+impl<'a, W: Sink + 'a, C: SerializerConfig> SerializeTuple for MaybeUnknownLengthCompound<'a, W, C> {
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        <Self as SerializeSeq>::serialize_element(self, value)
+    }
+
+    #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        if let Some(compound) = self.compound {
-            encode::write_array_len(&mut self.se.wr, compound.elem_count)?;
-            self.se.wr.write_all(&compound.se.into_inner())
-                .map_err(ValueWriteError::InvalidDataWrite)?;
-        }
-        Ok(())
+        <Self as SerializeSeq>::end(self)
     }
 }
 
-impl<'a, W: Write + 'a, C: SerializerConfig> SerializeMap for MaybeUnknownLengthCompound<'a, W, C> {
+impl<'a, W: Sink + 'a, C: SerializerConfig> SerializeMap for MaybeUnknownLengthCompound<'a, W, C> {
     type Ok = ();
     type Error = Error;
 
@@ -464,25 +1339,21 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeMap for MaybeUnknownLength
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        if let Some(compound) = self.compound {
-            encode::write_map_len(&mut self.se.wr, compound.elem_count / 2)?;
-            self.se.wr.write_all(&compound.se.into_inner())
-                .map_err(ValueWriteError::InvalidDataWrite)?;
-        }
-        Ok(())
+        self.se.leave();
+        self.end_unknown_len(true)
     }
 }
 
 impl<'a, W, C> serde::Serializer for &'a mut Serializer<W, C>
 where
-    W: Write,
+    W: Sink,
     C: SerializerConfig,
 {
     type Ok = ();
     type Error = Error;
 
     type SerializeSeq = MaybeUnknownLengthCompound<'a, W, C>;
-    type SerializeTuple = Compound<'a, W, C>;
+    type SerializeTuple = MaybeUnknownLengthCompound<'a, W, C>;
     type SerializeTupleStruct = Compound<'a, W, C>;
     type SerializeTupleVariant = Compound<'a, W, C>;
     type SerializeMap = MaybeUnknownLengthCompound<'a, W, C>;
@@ -490,12 +1361,11 @@ where
     type SerializeStructVariant = Compound<'a, W, C>;
 
     fn is_human_readable(&self) -> bool {
-        C::is_human_readable()
+        self.config.is_human_readable()
     }
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
-        encode::write_bool(&mut self.wr, v)
-            .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidMarkerWrite(err)))
+        self.wr.write_bool(v)
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
@@ -511,8 +1381,7 @@ where
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        encode::write_sint(&mut self.wr, v)?;
-        Ok(())
+        self.wr.write_sint(v)
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
@@ -532,8 +1401,7 @@ where
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        encode::write_uint(&mut self.wr, v)?;
-        Ok(())
+        self.wr.write_uint(v)
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
@@ -541,13 +1409,13 @@ where
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        encode::write_f32(&mut self.wr, v)?;
-        Ok(())
+        let v = if v.is_nan() && self.config.is_canonical() { f32::NAN } else { v };
+        self.wr.write_f32(v)
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        encode::write_f64(&mut self.wr, v)?;
-        Ok(())
+        let v = if v.is_nan() && self.config.is_canonical() { f64::NAN } else { v };
+        self.wr.write_f64(v)
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
@@ -557,15 +1425,22 @@ where
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        encode::write_str(&mut self.wr, v)?;
-        Ok(())
+        if self.config.is_legacy_raw() {
+            encode::write_raw(self.wr.writer(), v.as_bytes())?;
+            Ok(())
+        } else {
+            self.wr.write_str(v)
+        }
     }
 
     fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
-        encode::write_bin_len(&mut self.wr, value.len() as u32)?;
-        self.wr
-            .write_all(value)
-            .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)))
+        if !self.config.is_legacy_raw() {
+            return self.wr.write_bin(value);
+        }
+        encode::write_raw_len(self.wr.writer(), value.len() as u32)?;
+        self.wr.writer()
+            .write_bytes(value)
+            .map_err(|err| Error::from(ValueWriteError::InvalidDataWrite(err)))
     }
 
     fn serialize_none(self) -> Result<(), Self::Error> {
@@ -577,19 +1452,18 @@ where
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
-        encode::write_nil(&mut self.wr)
-            .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidMarkerWrite(err)))
+        self.wr.write_nil()
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
-        encode::write_array_len(&mut self.wr, 0)?;
+        self.wr.write_array_len(0)?;
         Ok(())
     }
 
     fn serialize_unit_variant(self, _name: &str, idx: u32, variant: &'static str) ->
         Result<Self::Ok, Self::Error>
     {
-        C::write_variant_ident(self, idx, variant)
+        self.config.write_variant_ident(self, idx, variant)
     }
 
     fn serialize_newtype_struct<T: ?Sized + serde::Serialize>(self, name: &'static str, value: &T) -> Result<(), Self::Error> {
@@ -606,26 +1480,64 @@ where
 
     fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(self, _name: &'static str, idx: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
         // encode as a map from variant idx to its attributed data, like: {idx => value}
-        encode::write_map_len(&mut self.wr, 1)?;
-        C::write_variant_ident(self, idx, variant)?;
+        self.wr.write_map_len(1)?;
+        self.config.write_variant_ident(self, idx, variant)?;
         value.serialize(self)
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
-        self.maybe_unknown_len_compound(len, |wr, len| encode::write_array_len(wr, len))
+        self.enter()?;
+        if self.config.bytes_mode() != BytesMode::Normal {
+            return Ok(self.pending_bytes_compound(len));
+        }
+        self.maybe_unknown_len_compound(len, W::write_array_len)
     }
 
-    //TODO: normal compund
-    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        encode::write_array_len(&mut self.wr, len as u32)?;
+    // A sequence whose length is known up front needs no per-element bookkeeping, so its elements
+    // are written straight to the serializer, which lets the loop keep the buffer in registers.
+    fn collect_seq<I>(self, iter: I) -> Result<Self::Ok, Self::Error>
+    where
+        I: IntoIterator,
+        I::Item: Serialize,
+    {
+        let iter = iter.into_iter();
+        let len = match iter.size_hint() {
+            (lo, Some(hi)) if lo == hi => Some(lo),
+            _ => None,
+        };
+        match len {
+            Some(len) if self.config.bytes_mode() == BytesMode::Normal => {
+                self.enter()?;
+                self.wr.write_array_len(len as u32)?;
+                for item in iter {
+                    item.serialize(&mut *self)?;
+                }
+                self.leave();
+                Ok(())
+            }
+            _ => {
+                let mut seq = self.serialize_seq(len)?;
+                for item in iter {
+                    SerializeSeq::serialize_element(&mut seq, &item)?;
+                }
+                SerializeSeq::end(seq)
+            }
+        }
+    }
 
-        self.compound()
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.enter()?;
+        if self.config.bytes_mode() == BytesMode::ForceAll {
+            return Ok(self.pending_bytes_compound(Some(len)));
+        }
+        self.maybe_unknown_len_compound(Some(len), W::write_array_len)
     }
 
     fn serialize_tuple_struct(self, _name: &'static str, len: usize) ->
         Result<Self::SerializeTupleStruct, Self::Error>
     {
-        encode::write_array_len(&mut self.wr, len as u32)?;
+        self.enter()?;
+        self.wr.write_array_len(len as u32)?;
 
         self.compound()
     }
@@ -634,19 +1546,33 @@ where
         Result<Self::SerializeTupleVariant, Error>
     {
         // encode as a map from variant idx to a sequence of its attributed data, like: {idx => [v1,...,vN]}
-        encode::write_map_len(&mut self.wr, 1)?;
-        C::write_variant_ident(self, idx, variant)?;
-        self.serialize_tuple(len)
+        self.enter()?;
+        self.wr.write_map_len(1)?;
+        self.config.write_variant_ident(self, idx, variant)?;
+        self.wr.write_array_len(len as u32)?;
+
+        self.compound()
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
-        self.maybe_unknown_len_compound(len, |wr, len| encode::write_map_len(wr, len))
+        self.enter()?;
+        if self.config.is_canonical() {
+            // Entries are buffered regardless of the length, to be sorted on `end()`.
+            let mut compound = UnknownLengthCompound::from(&*self);
+            compound.bounds = Some(Vec::with_capacity(len.unwrap_or(0) * 2));
+            return Ok(MaybeUnknownLengthCompound {
+                se: self,
+                elements: Elements::Counted(UnknownLength::Buffered(compound)),
+            });
+        }
+        self.maybe_unknown_len_compound(len, W::write_map_len)
     }
 
     fn serialize_struct(self, _name: &'static str, len: usize) ->
         Result<Self::SerializeStruct, Self::Error>
     {
-        C::write_struct_len(self, len)?;
+        self.enter()?;
+        self.config.write_struct_len(self, len)?;
         self.compound()
     }
 
@@ -654,8 +1580,8 @@ where
         Result<Self::SerializeStructVariant, Error>
     {
         // encode as a map from variant idx to a sequence of its attributed data, like: {idx => [v1,...,vN]}
-        encode::write_map_len(&mut self.wr, 1)?;
-        C::write_variant_ident(self, id, variant)?;
+        self.wr.write_map_len(1)?;
+        self.config.write_variant_ident(self, id, variant)?;
         self.serialize_struct(name, len)
     }
 }
@@ -687,8 +1613,8 @@ impl<'a, W: Write + 'a> serde::Serializer for &mut ExtFieldSerializer<'a, W> {
         if let Some(tag) = self.tag.take() {
             encode::write_ext_meta(self.wr, val.len() as u32, tag)?;
             self.wr
-                .write_all(val)
-                .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)))?;
+                .write_bytes(val)
+                .map_err(|err| Error::from(ValueWriteError::InvalidDataWrite(err)))?;
 
             self.finish = true;
 
@@ -1005,7 +1931,7 @@ impl<'a, W: Write + 'a> SerializeTuple for &mut ExtSerializer<'a, W> {
 
 impl<'a, W: Write + 'a> ExtSerializer<'a, W> {
     #[inline]
-    fn new<C>(ser: &'a mut Serializer<W, C>) -> Self {
+    fn new<S: Sink<Write = W>, C>(ser: &'a mut Serializer<S, C>) -> Self {
         Self {
             fields_se: ExtFieldSerializer::new(ser),
             tuple_received: false,
@@ -1024,7 +1950,7 @@ impl<'a, W: Write + 'a> ExtSerializer<'a, W> {
 
 impl<'a, W: Write + 'a> ExtFieldSerializer<'a, W> {
     #[inline]
-    fn new<C>(ser: &'a mut Serializer<W, C>) -> Self {
+    fn new<S: Sink<Write = W>, C>(ser: &'a mut Serializer<S, C>) -> Self {
         Self {
             wr: UnderlyingWrite::get_mut(ser),
             tag: None,
@@ -1042,10 +1968,168 @@ impl<'a, W: Write + 'a> ExtFieldSerializer<'a, W> {
     }
 }
 
+/// Serializer that accepts nothing but a `u8`, used to find out whether the elements of a sequence
+/// can be written as `bin`.
+struct ByteProbe;
+
+impl serde::Serializer for ByteProbe {
+    type Ok = u8;
+    type Error = Error;
+
+    type SerializeSeq = serde::ser::Impossible<u8, Error>;
+    type SerializeTuple = serde::ser::Impossible<u8, Error>;
+    type SerializeTupleStruct = serde::ser::Impossible<u8, Error>;
+    type SerializeTupleVariant = serde::ser::Impossible<u8, Error>;
+    type SerializeMap = serde::ser::Impossible<u8, Error>;
+    type SerializeStruct = serde::ser::Impossible<u8, Error>;
+    type SerializeStructVariant = serde::ser::Impossible<u8, Error>;
+
+    #[inline]
+    fn serialize_u8(self, val: u8) -> Result<Self::Ok, Self::Error> {
+        Ok(val)
+    }
+
+    #[inline]
+    fn serialize_bool(self, _val: bool) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received bool"))
+    }
+
+    #[inline]
+    fn serialize_i8(self, _val: i8) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received i8"))
+    }
+
+    #[inline]
+    fn serialize_i16(self, _val: i16) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received i16"))
+    }
+
+    #[inline]
+    fn serialize_i32(self, _val: i32) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received i32"))
+    }
+
+    #[inline]
+    fn serialize_i64(self, _val: i64) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received i64"))
+    }
+
+    #[inline]
+    fn serialize_u16(self, _val: u16) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received u16"))
+    }
+
+    #[inline]
+    fn serialize_u32(self, _val: u32) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received u32"))
+    }
+
+    #[inline]
+    fn serialize_u64(self, _val: u64) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received u64"))
+    }
+
+    #[inline]
+    fn serialize_f32(self, _val: f32) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received f32"))
+    }
+
+    #[inline]
+    fn serialize_f64(self, _val: f64) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received f64"))
+    }
+
+    #[inline]
+    fn serialize_char(self, _val: char) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received char"))
+    }
+
+    #[inline]
+    fn serialize_str(self, _val: &str) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received str"))
+    }
+
+    #[inline]
+    fn serialize_bytes(self, _val: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received bytes"))
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received none"))
+    }
+
+    #[inline]
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received some"))
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received unit"))
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received unit struct"))
+    }
+
+    #[inline]
+    fn serialize_unit_variant(self, _name: &'static str, _idx: u32, _variant: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received unit variant"))
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, _value: &T) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received newtype struct"))
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _name: &'static str, _idx: u32, _variant: &'static str, _value: &T) -> Result<Self::Ok, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received newtype variant"))
+    }
+
+    #[inline]
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received seq"))
+    }
+
+    #[inline]
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received tuple"))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received tuple struct"))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(self, _name: &'static str, _idx: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received tuple variant"))
+    }
+
+    #[inline]
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received map"))
+    }
+
+    #[inline]
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received struct"))
+    }
+
+    #[inline]
+    fn serialize_struct_variant(self, _name: &'static str, _idx: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(Error::InvalidDataModel("expected u8, received struct variant"))
+    }
+}
+
 /// Serialize the given data structure as MessagePack into the I/O stream.
 /// This function uses compact representation - structures as arrays
 ///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[cfg(feature = "std")]
 #[inline]
 pub fn write<W, T>(wr: &mut W, val: &T) -> Result<(), Error>
 where
@@ -1059,6 +2143,7 @@ where
 /// This function serializes structures as maps
 ///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[cfg(feature = "std")]
 pub fn write_named<W, T>(wr: &mut W, val: &T) -> Result<(), Error>
 where
     W: Write + ?Sized,
@@ -1077,9 +2162,68 @@ pub fn to_vec<T>(val: &T) -> Result<Vec<u8>, Error>
 where
     T: Serialize + ?Sized
 {
-    let mut wr = Vec::with_capacity(128);
-    write(&mut wr, val)?;
-    Ok(wr)
+    let mut se = Serializer {
+        wr: PatchBuffer::with_capacity(128),
+        depth: None,
+        config: DefaultConfig,
+    };
+    val.serialize(&mut se)?;
+    Ok(se.wr.into_vec())
+}
+
+/// Serializes the given data structure like [`to_vec`], with the given config, depth limit and
+/// size limit, if any.
+#[cfg(feature = "std")]
+pub(crate) fn to_vec_with_config<T, C>(val: &T, config: C, depth: Option<usize>, limit: Option<usize>) -> Result<Vec<u8>, Error>
+where
+    T: Serialize + ?Sized,
+    C: SerializerConfig,
+{
+    if let Some(limit) = limit {
+        let mut se = Serializer {
+            wr: LimitedBuffer::new(limit),
+            depth,
+            config,
+        };
+        val.serialize(&mut se)?;
+        return se.wr.into_vec();
+    }
+
+    let mut se = Serializer {
+        wr: PatchBuffer::with_capacity(128),
+        depth,
+        config,
+    };
+    val.serialize(&mut se)?;
+    Ok(se.wr.into_vec())
+}
+
+/// Serializes the given data structure like [`to_vec`], with the given config, depth limit and
+/// size limit, if any, then writes it into the I/O stream.
+#[cfg(feature = "std")]
+pub(crate) fn to_writer_with_config<W, T, C>(wr: &mut W, val: &T, config: C, depth: Option<usize>, limit: Option<usize>) -> Result<(), Error>
+where
+    W: Write,
+    T: Serialize + ?Sized,
+    C: SerializerConfig,
+{
+    if let Some(limit) = limit {
+        let mut se = Serializer {
+            wr: LimitedBuffer::new(limit),
+            depth,
+            config,
+        };
+        val.serialize(&mut se)?;
+        return se.wr.write_to(wr);
+    }
+
+    let mut se = Serializer {
+        wr: PatchBuffer::with_capacity(128),
+        depth,
+        config,
+    };
+    val.serialize(&mut se)?;
+    se.wr.write_to(wr)
 }
 
 /// Serializes data structure into byte vector as a map
@@ -1093,7 +2237,267 @@ pub fn to_vec_named<T>(val: &T) -> Result<Vec<u8>, Error>
 where
     T: Serialize + ?Sized
 {
-    let mut wr = Vec::with_capacity(128);
-    write_named(&mut wr, val)?;
-    Ok(wr)
+    let mut se = Serializer {
+        wr: PatchBuffer::with_capacity(128),
+        depth: None,
+        config: StructMapConfig::new(DefaultConfig),
+    };
+    val.serialize(&mut se)?;
+    Ok(se.wr.into_vec())
+}
+
+/// Returns the number of bytes [`to_vec`] would serialize the given data structure to.
+///
+/// Nothing is written or allocated for the encoded bytes, so this is a cheap way to size a buffer
+/// for [`to_slice`] or a length prefix. The only exception are maps of unknown length while
+/// sorting map keys, which are still buffered to find duplicate keys.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+///
+/// # Examples
+///
+/// ```
+/// let val = (42, "the Answer");
+///
+/// assert_eq!(13, rmp_serde::encoded_size(&val).unwrap());
+/// assert_eq!(rmp_serde::to_vec(&val).unwrap().len(), rmp_serde::encoded_size(&val).unwrap());
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn encoded_size<T>(val: &T) -> Result<usize, Error>
+where
+    T: Serialize + ?Sized
+{
+    encoded_size_with_config(val, DefaultConfig, None)
+}
+
+/// Returns the number of bytes [`to_vec_named`] would serialize the given data structure to.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[cfg(feature = "std")]
+#[inline]
+pub fn encoded_size_named<T>(val: &T) -> Result<usize, Error>
+where
+    T: Serialize + ?Sized
+{
+    encoded_size_with_config(val, StructMapConfig::new(DefaultConfig), None)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn encoded_size_with_config<T, C>(val: &T, config: C, depth: Option<usize>) -> Result<usize, Error>
+where
+    T: Serialize + ?Sized,
+    C: SerializerConfig,
+{
+    let mut se = Serializer {
+        wr: ByteCounter::default(),
+        depth,
+        config,
+    };
+    val.serialize(&mut se)?;
+    Ok(se.wr.len())
+}
+
+/// Serializes data structure into the given buffer, returning the number of bytes written.
+/// This method uses compact representation, structs are serialized as arrays
+///
+/// Unlike [`to_vec`] this doesn't allocate for the output, which makes it suitable for targets
+/// without `std`.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail, or if the
+/// encoded value doesn't fit into the buffer.
+///
+/// # Examples
+///
+/// ```
+/// let mut buf = [0; 16];
+/// let len = rmp_serde::to_slice(&(42, "the Answer"), &mut buf).unwrap();
+///
+/// assert_eq!(&[0x92, 0x2a, 0xaa][..], &buf[..3]);
+/// assert_eq!(13, len);
+/// ```
+#[inline]
+pub fn to_slice<T>(val: &T, buf: &mut [u8]) -> Result<usize, Error>
+where
+    T: Serialize + ?Sized
+{
+    let len = buf.len();
+    let mut se = Serializer::new(buf);
+    val.serialize(&mut se)?;
+    Ok(len - se.into_inner().len())
+}
+
+/// A writer that sends large payloads to the underlying writer without copying them.
+///
+/// The `Serializer` writes the bytes of a `bin` or `str` value with a single write. `GatherWriter`
+/// buffers writes shorter than its threshold, such as markers, lengths and small values, and
+/// passes a longer write straight through, together with the buffered bytes before it, in one
+/// call to [`Write::write_vectored`]. Serializing a struct with multi-megabyte `serde_bytes`
+/// fields into a socket or file thus never duplicates the blobs in memory.
+///
+/// The buffered bytes are written out before they would reach the threshold, so the buffer stays
+/// shorter than the threshold however many small writes there are.
+///
+/// # Limitations
+///
+/// This writes each large payload as soon as the `Serializer` is given it, rather than collecting
+/// the whole message as a `Vec<IoSlice>` for a single `write_vectored` call. Serde passes the bytes
+/// to [`serialize_bytes`](serde::Serializer::serialize_bytes) and
+/// [`serialize_str`](serde::Serializer::serialize_str) with a lifetime that ends with the call,
+/// and a `Serialize` implementation is free to pass a temporary buffer there, so no safe sink can
+/// keep them for later. A message with several large payloads thus takes one vectored write per
+/// payload.
+///
+/// Elements of sequences and maps of unknown length are still copied, as they are buffered until
+/// their length is known.
+///
+/// The remaining buffered bytes are written by [`flush`](Write::flush) or
+/// [`into_inner`](GatherWriter::into_inner), but not on drop.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+///
+/// let blob = vec![0xaa; 1 << 20];
+/// let mut wr = rmp_serde::encode::GatherWriter::new(Vec::new());
+/// rmp_serde::encode::write(&mut wr, &(42, serde_bytes::Bytes::new(&blob))).unwrap();
+/// let buf = wr.into_inner().unwrap();
+///
+/// assert_eq!(rmp_serde::to_vec(&(42, serde_bytes::Bytes::new(&blob))).unwrap(), buf);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct GatherWriter<W: Write> {
+    wr: W,
+    buf: Vec<u8>,
+    threshold: usize,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> GatherWriter<W> {
+    /// Creates a `GatherWriter` that passes through writes of at least 4 KiB.
+    #[inline]
+    pub fn new(wr: W) -> Self {
+        Self::with_threshold(4096, wr)
+    }
+
+    /// Creates a `GatherWriter` that passes through writes of at least `threshold` bytes.
+    #[inline]
+    pub fn with_threshold(threshold: usize, wr: W) -> Self {
+        GatherWriter {
+            wr,
+            buf: Vec::with_capacity(threshold.min(4096)),
+            threshold,
+        }
+    }
+
+    /// Gets a reference to the underlying writer.
+    #[inline(always)]
+    pub fn get_ref(&self) -> &W {
+        &self.wr
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    ///
+    /// Writing to it directly skips the bytes buffered so far.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.wr
+    }
+
+    /// Returns the bytes buffered so far, which the next flush writes.
+    #[inline(always)]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Writes the buffered bytes and unwraps the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing the buffered bytes fails.
+    pub fn into_inner(mut self) -> std::io::Result<W> {
+        self.write_buffered()?;
+        Ok(self.wr)
+    }
+
+    /// Writes the buffered bytes, dropping from the buffer those written before an error.
+    fn write_buffered(&mut self) -> std::io::Result<()> {
+        use std::io::ErrorKind;
+
+        let mut head = 0;
+        while head < self.buf.len() {
+            match self.wr.write(&self.buf[head..]) {
+                Ok(0) => {
+                    self.buf.drain(..head);
+                    return Err(ErrorKind::WriteZero.into());
+                }
+                Ok(len) => head += len,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => {
+                    self.buf.drain(..head);
+                    return Err(err);
+                }
+            }
+        }
+        self.buf.clear();
+        Ok(())
+    }
+
+    /// Writes the buffered bytes followed by `data`, with as few writes as the writer allows, and
+    /// returns how many bytes of `data` were written.
+    ///
+    /// An error is returned only if none of `data` was written. Once some of it was, the error is
+    /// left for the next write to run into, so that the caller learns how much was consumed.
+    fn write_gathered(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        use std::io::{ErrorKind, IoSlice};
+
+        let (mut head, mut tail) = (0, 0);
+        while head < self.buf.len() || tail < data.len() {
+            let bufs = [IoSlice::new(&self.buf[head..]), IoSlice::new(&data[tail..])];
+            let err = match self.wr.write_vectored(&bufs) {
+                Ok(0) => ErrorKind::WriteZero.into(),
+                Ok(len) => {
+                    let buffered = len.min(self.buf.len() - head);
+                    head += buffered;
+                    tail += len - buffered;
+                    continue;
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => err,
+            };
+            self.buf.drain(..head);
+            return if tail > 0 { Ok(tail) } else { Err(err) };
+        }
+        self.buf.clear();
+        Ok(tail)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for GatherWriter<W> {
+    #[inline]
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if data.len() < self.threshold {
+            if self.buf.len() + data.len() >= self.threshold {
+                self.write_buffered()?;
+            }
+            self.buf.extend_from_slice(data);
+            Ok(data.len())
+        } else {
+            self.write_gathered(data)
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write_buffered()?;
+        self.wr.flush()
+    }
 }
@@ -54,25 +54,48 @@
 //! }
 //! ```
 //!
+//!
+//! # `no_std` support
+//!
+//! Disabling the default `std` feature makes this crate depend on `alloc` only. The serializer
+//! and deserializer then run on [`RmpWrite`](rmp::encode::RmpWrite) and
+//! [`RmpRead`](rmp::decode::RmpRead) instead of the I/O traits: serialize into a fixed buffer with
+//! [`to_slice`] or into a vector with [`to_vec`], and deserialize with [`from_slice`].
+//!
+//! An allocator is still required, since errors carry `String` messages and sequences and maps of
+//! unknown length are buffered until their length is known. Targets without an allocator can use
+//! the `rmp` crate directly.
+//!
 //! [serde]: https://serde.rs/
+#![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(unsafe_code)]
 #![warn(missing_debug_implementations, missing_docs)]
 
+extern crate alloc;
 #[macro_use]
 extern crate serde;
 
-use std::fmt::{self, Display, Formatter};
-use std::str::{self, Utf8Error};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::str::{self, Utf8Error};
 
 use serde::de;
 use serde::{Deserialize, Serialize};
 
-pub use crate::decode::{from_read, Deserializer};
+#[cfg(feature = "std")]
+pub use crate::config::Options;
+pub use crate::decode::Deserializer;
+#[cfg(feature = "std")]
+pub use crate::decode::from_read;
+#[cfg(feature = "std")]
 #[allow(deprecated)]
 pub use crate::decode::from_read_ref;
-pub use crate::encode::{to_vec, to_vec_named, Serializer};
+pub use crate::encode::{to_slice, to_vec, to_vec_named, Serializer};
+#[cfg(feature = "std")]
+pub use crate::encode::{encoded_size, encoded_size_named};
 
-pub use crate::decode::from_slice;
+pub use crate::decode::{from_slice, from_slice_at, from_slice_at_opt};
 
 pub mod config;
 pub mod decode;
@@ -371,3 +394,32 @@ impl<'de> Deserialize<'de> for RawRef<'de> {
         de.deserialize_any(RawRefVisitor)
     }
 }
+
+// Runs without `std` as well, see the `no_std` step of the CI.
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Reading<'a> {
+        sensor: &'a str,
+        values: Vec<i16>,
+        ok: bool,
+    }
+
+    #[test]
+    fn pass_to_slice_from_slice() {
+        let val = Reading { sensor: "temp", values: vec![-40, 125], ok: true };
+
+        let mut buf = [0; 32];
+        let len = crate::to_slice(&val, &mut buf).unwrap();
+        assert_eq!(crate::to_vec(&val).unwrap(), &buf[..len]);
+        assert_eq!(val, crate::from_slice(&buf[..len]).unwrap());
+
+        assert!(crate::to_slice(&val, &mut buf[..len - 1]).is_err());
+        assert!(crate::from_slice::<Reading<'_>>(&buf[..len - 1]).is_err());
+    }
+}
@@ -6,6 +6,7 @@ use std::io::Cursor;
 use serde::de;
 use serde::Deserialize;
 
+use crate::rmps::config::BytesMode;
 use crate::rmps::decode::{self, Error};
 use crate::rmps::{Deserializer, Raw, RawRef};
 use rmp::Marker;
@@ -569,3 +570,513 @@ fn fail_depth_limit() {
         other => panic!("unexpected result: {:?}", other),
     }
 }
+
+#[test]
+fn fail_options_size_limit() {
+    // ["Bobby", 8]
+    let buf = [0x92, 0xa5, 0x42, 0x6f, 0x62, 0x62, 0x79, 0x8];
+    let opts = rmps::Options::new().with_limit(7);
+
+    match opts.deserialize::<(String, u8)>(&buf) {
+        Err(decode::Error::Syntax(ref msg)) if msg == "size limit exceeded" => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match opts.deserialize_from::<_, (String, u8)>(&buf[..]) {
+        Err(decode::Error::Syntax(ref msg)) if msg == "size limit exceeded" => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let opts = opts.with_limit(8);
+    assert_eq!(("Bobby", 8), opts.deserialize(&buf).unwrap());
+    assert_eq!(("Bobby".to_owned(), 8), opts.deserialize_from(&buf[..]).unwrap());
+}
+
+#[test]
+fn fail_options_trailing_bytes() {
+    let buf = [0x2a, 0xc0];
+    let opts = rmps::Options::new();
+
+    assert_eq!(42, opts.deserialize::<u8>(&buf).unwrap());
+    assert_eq!(42, opts.deserialize_from::<_, u8>(&buf[..]).unwrap());
+
+    let opts = opts.reject_trailing_bytes();
+    assert_eq!(42, opts.deserialize::<u8>(&buf[..1]).unwrap());
+    match opts.deserialize::<u8>(&buf) {
+        Err(decode::Error::Syntax(ref msg)) if msg == "trailing bytes after the decoded value" => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match opts.deserialize_from::<_, u8>(&buf[..]) {
+        Err(decode::Error::Syntax(ref msg)) if msg == "trailing bytes after the decoded value" => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_options_depth_limit() {
+    let buf = [0x91, 0x91, 0x91, 0x90];
+    let opts = rmps::Options::new().with_max_depth(3);
+
+    match opts.deserialize::<Vec<Vec<Vec<Vec<()>>>>>(&buf) {
+        Err(decode::Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(opts.with_max_depth(5).deserialize::<Vec<Vec<Vec<Vec<()>>>>>(&buf).is_ok());
+}
+
+#[test]
+fn pass_legacy_raw_bytes() {
+    let buf = [0xa2, 0xff, 0x00];
+
+    let mut de = Deserializer::from_read_ref(&buf[..]).with_legacy_raw();
+    let val: &[u8] = serde_bytes::deserialize(&mut de).unwrap();
+    assert_eq!(&[0xff, 0x00], val);
+
+    let mut de = Deserializer::new(&buf[..]).with_legacy_raw();
+    let val: serde_bytes::ByteBuf = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(vec![0xff, 0x00], val.into_vec());
+
+    // Strings are still validated when asked for.
+    let mut de = Deserializer::new(&buf[..]).with_legacy_raw();
+    match String::deserialize(&mut de) {
+        Err(Error::Utf8Error(..)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_legacy_raw_bin_still_accepted() {
+    let buf = [0xc4, 0x02, 0xff, 0x00];
+
+    let mut de = Deserializer::new(&buf[..]).with_legacy_raw();
+    let val: serde_bytes::ByteBuf = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(vec![0xff, 0x00], val.into_vec());
+}
+
+#[test]
+fn pass_bin_as_seq() {
+    let buf = [0xc4, 0x03, 0x01, 0x02, 0xff];
+
+    let mut de = Deserializer::new(&buf[..]).with_bytes(BytesMode::ForceIterables);
+    assert_eq!(vec![1u8, 2, 0xff], Vec::<u8>::deserialize(&mut de).unwrap());
+
+    let mut de = Deserializer::new(&buf[..]).with_bytes(BytesMode::ForceIterables);
+    assert_eq!([1u8, 2, 0xff], <[u8; 3]>::deserialize(&mut de).unwrap());
+
+    let mut de = Deserializer::new(&buf[..]).with_bytes(BytesMode::ForceAll);
+    assert_eq!(vec![1u16, 2, 0xff], Vec::<u16>::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_bin_as_seq_by_default() {
+    let buf = [0xc4, 0x03, 0x01, 0x02, 0xff];
+
+    let mut de = Deserializer::new(&buf[..]);
+    assert!(Vec::<u16>::deserialize(&mut de).is_err());
+
+    let mut de = Deserializer::new(&buf[..]).with_bytes(BytesMode::Normal);
+    assert!(Vec::<u8>::deserialize(&mut de).is_err());
+
+    assert!(rmp_serde::from_slice::<Vec<u16>>(&buf).is_err());
+}
+
+#[test]
+fn fail_bin_as_tuple_length_mismatch() {
+    let buf = [0xc4, 0x03, 0x01, 0x02, 0xff];
+
+    let mut de = Deserializer::new(&buf[..]).with_bytes(BytesMode::ForceAll);
+    assert!(<[u8; 2]>::deserialize(&mut de).is_err());
+}
+
+#[test]
+fn pass_lenient_int() {
+    // 3.0f64, "42", -2.0f32
+    let buf = [0xcb, 0x40, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert_eq!(3u64, Deserialize::deserialize(&mut de).unwrap());
+
+    let buf = [0xa2, 0x34, 0x32];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert_eq!(42u8, Deserialize::deserialize(&mut de).unwrap());
+
+    let buf = [0xca, 0xc0, 0x00, 0x00, 0x00];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert_eq!(-2i32, Deserialize::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_lenient_int_lossy() {
+    // 3.5f64
+    let buf = [0xcb, 0x40, 0x0c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert!(u64::deserialize(&mut de).is_err());
+
+    // -1.0f32 into an unsigned integer.
+    let buf = [0xca, 0xbf, 0x80, 0x00, 0x00];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert!(u64::deserialize(&mut de).is_err());
+
+    let buf = [0xa3, 0x31, 0x2e, 0x35];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert!(u64::deserialize(&mut de).is_err());
+
+    // Without the lenient mode nothing is coerced.
+    let buf = [0xcb, 0x40, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut de = Deserializer::new(&buf[..]);
+    assert!(u64::deserialize(&mut de).is_err());
+}
+
+#[test]
+fn pass_lenient_float() {
+    let buf = [0x2a];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert_eq!(42.0f64, Deserialize::deserialize(&mut de).unwrap());
+
+    let buf = [0xa3, 0x31, 0x2e, 0x35];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert_eq!(1.5f32, Deserialize::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_lenient_float_lossy() {
+    // 2^53 + 1
+    let buf = [0xcf, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert!(f64::deserialize(&mut de).is_err());
+
+    // 2^24 + 1
+    let buf = [0xce, 0x01, 0x00, 0x00, 0x01];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert!(f32::deserialize(&mut de).is_err());
+
+    let buf = [0xce, 0x01, 0x00, 0x00, 0x01];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert_eq!(16777217.0f64, Deserialize::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn pass_lenient_float_from_str() {
+    for (s, val) in [("0.1", 0.1), ("-1.50", -1.5), ("2.5e3", 2500.0), ("100", 100.0), ("1e22", 1e22)] {
+        let buf = rmp_serde::to_vec(s).unwrap();
+        let mut de = Deserializer::new(&buf[..]).with_lenient();
+        assert_eq!(val, f64::deserialize(&mut de).unwrap(), "{}", s);
+    }
+
+    let buf = rmp_serde::to_vec("16777216").unwrap();
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert_eq!(16777216.0, f32::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_lenient_float_from_lossy_str() {
+    let cases = ["0.10000000000000000001", "123456789012345678901234567890", "1e400", "NaN", "inf"];
+    for s in cases {
+        let buf = rmp_serde::to_vec(s).unwrap();
+        let mut de = Deserializer::new(&buf[..]).with_lenient();
+        assert!(f64::deserialize(&mut de).is_err(), "{}", s);
+    }
+
+    // 2^24 + 1
+    let buf = rmp_serde::to_vec("16777217").unwrap();
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert!(f32::deserialize(&mut de).is_err());
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert_eq!(16777217.0, f64::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn pass_lenient_str_from_bin() {
+    let buf = [0xc4, 0x02, 0x6c, 0x65];
+    let mut de = Deserializer::from_read_ref(&buf[..]).with_lenient();
+    assert_eq!("le", <&str>::deserialize(&mut de).unwrap());
+
+    let buf = [0xc4, 0x02, 0xff, 0x65];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    match String::deserialize(&mut de) {
+        Err(Error::Utf8Error(..)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_lenient_bytes_from_array() {
+    let buf = [0x93, 0x01, 0xcc, 0xff, 0x00];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    let val: serde_bytes::ByteBuf = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(vec![1, 0xff, 0], val.into_vec());
+
+    let buf = [0x92, 0x01, 0xcd, 0x01, 0x00];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    assert!(serde_bytes::ByteBuf::deserialize(&mut de).is_err());
+}
+
+#[test]
+fn fail_lenient_bytes_from_non_int_elements() {
+    // ["1", 2]
+    let buf = [0x92, 0xa1, b'1', 0x02];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    match serde_bytes::ByteBuf::deserialize(&mut de) {
+        Err(Error::TypeMismatch(Marker::FixStr(1))) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // [1, 2.0]
+    let buf = [0x92, 0x01, 0xcb, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut de = Deserializer::new(&buf[..]).with_lenient();
+    match serde_bytes::ByteBuf::deserialize(&mut de) {
+        Err(Error::TypeMismatch(Marker::F64)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // [-1]
+    let mut de = Deserializer::new(&[0x91, 0xff][..]).with_lenient();
+    match serde_bytes::ByteBuf::deserialize(&mut de) {
+        Err(Error::OutOfRange) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_strict_invalid_utf8() {
+    let buf = [0xa2, 0xff, 0x00];
+
+    // Without the strict mode, the payload is handed to types that accept bytes.
+    let mut de = Deserializer::new(&buf[..]);
+    let val: serde_bytes::ByteBuf = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(vec![0xff, 0x00], val.into_vec());
+
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    match serde_bytes::ByteBuf::deserialize(&mut de) {
+        Err(Error::Utf8Error(..)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_strict_non_minimal_int() {
+    for buf in [&[0xcc, 0x01][..], &[0xcd, 0x00, 0xff], &[0xd0, 0x01], &[0xd1, 0xff, 0x80], &[0xd1, 0x01, 0x00]] {
+        let mut de = Deserializer::new(buf).with_strict();
+        match i64::deserialize(&mut de) {
+            Err(Error::Syntax(ref msg)) if msg.starts_with("non-minimal encoding") => (),
+            other => panic!("unexpected result for {:?}: {:?}", buf, other),
+        }
+
+        let mut de = Deserializer::new(buf);
+        assert!(i64::deserialize(&mut de).is_ok());
+    }
+
+    for buf in [&[0x01][..], &[0xcc, 0xff], &[0xd0, 0x80], &[0xd1, 0xff, 0x7f], &[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]] {
+        let mut de = Deserializer::new(buf).with_strict();
+        assert!(serde::de::IgnoredAny::deserialize(&mut de).is_ok(), "{:?}", buf);
+    }
+}
+
+#[test]
+fn fail_strict_non_minimal_len() {
+    // "a" as str8, [1] as array16, {} as map16, and bin16 of length 1.
+    for buf in [&[0xd9, 0x01, 0x61][..], &[0xdc, 0x00, 0x01, 0x01], &[0xde, 0x00, 0x00], &[0xc5, 0x00, 0x01, 0x00]] {
+        let mut de = Deserializer::new(buf).with_strict();
+        match serde::de::IgnoredAny::deserialize(&mut de) {
+            Err(Error::Syntax(ref msg)) if msg.starts_with("non-minimal encoding") => (),
+            other => panic!("unexpected result for {:?}: {:?}", buf, other),
+        }
+    }
+
+    // str16 is the smallest marker of the old specification for strings longer than 31 bytes.
+    let mut buf = vec![0xda, 0x00, 0x20];
+    buf.extend_from_slice(&[0x61; 32]);
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    assert!(String::deserialize(&mut de).is_err());
+    let mut de = Deserializer::new(&buf[..]).with_legacy_raw().with_strict();
+    assert_eq!("a".repeat(32), String::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_strict_reserved_marker() {
+    let buf = [0xc1];
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    match serde::de::IgnoredAny::deserialize(&mut de) {
+        Err(Error::TypeMismatch(Marker::Reserved)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_strict_reserved_ext_type() {
+    use serde::de::IgnoredAny;
+
+    // fixext1 with type -2.
+    let buf = [0xd4, 0xfe, 0x00];
+    let mut de = Deserializer::new(&buf[..]);
+    assert!(IgnoredAny::deserialize(&mut de).is_ok());
+
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    match IgnoredAny::deserialize(&mut de) {
+        Err(Error::Syntax(ref msg)) if msg == "reserved ext type -2" => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let mut de = Deserializer::new(&buf[..]).with_strict().with_ext_type(-2);
+    assert!(IgnoredAny::deserialize(&mut de).is_ok());
+
+    // The timestamp type is always accepted.
+    let buf = [0xd4, 0xff, 0x00];
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    assert!(IgnoredAny::deserialize(&mut de).is_ok());
+}
+
+#[test]
+fn pass_from_slice_remaining() {
+    let buf = [0x2a, 0xa3, 0x61, 0x62, 0x63];
+    let mut de = Deserializer::from_slice(&buf[..]);
+
+    assert_eq!(42, u8::deserialize(&mut de).unwrap());
+    assert_eq!(&buf[1..], de.remaining_slice());
+    assert_eq!("abc", <&str>::deserialize(&mut de).unwrap());
+    assert!(de.remaining_slice().is_empty());
+}
+
+#[test]
+fn fail_from_slice_truncated_bin() {
+    let buf = [0xc4, 0x03, 0x01];
+    let mut de = Deserializer::from_slice(&buf[..]);
+
+    match <&[u8]>::deserialize(&mut de) {
+        Err(Error::InvalidDataRead(..)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_from_slice_truncated() {
+    // Missing marker, u32 cut short, str length cut short.
+    match rmp_serde::from_slice::<u32>(&[]) {
+        Err(Error::InvalidMarkerRead(..)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match rmp_serde::from_slice::<u32>(&[0xce, 0x00, 0x01, 0x02]) {
+        Err(Error::InvalidDataRead(..)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match rmp_serde::from_slice::<&str>(&[0xda, 0x00]) {
+        Err(Error::InvalidDataRead(..)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let mut de = Deserializer::from_slice(&[0xcb, 0x00, 0x00]);
+    assert!(f64::deserialize(&mut de).is_err());
+    assert_eq!(0, de.remaining_slice().len());
+}
+
+#[test]
+fn pass_from_slice_fixed_width() {
+    let buf = [0xce, 0x00, 0x01, 0x00, 0x00, 0xd1, 0xff, 0x00, 0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut de = Deserializer::from_slice(&buf[..]);
+
+    assert_eq!(65536, u32::deserialize(&mut de).unwrap());
+    assert_eq!(-256, i16::deserialize(&mut de).unwrap());
+    assert_eq!(1.5, f64::deserialize(&mut de).unwrap());
+    assert_eq!(0, de.remaining_slice().len());
+}
+
+#[test]
+fn pass_from_slice_falls_back_to_general_path() {
+    // An Option peeks the marker, and invalid UTF-8 is still handed out as bytes.
+    let buf = [0xcd, 0x01, 0x00, 0xa2, 0xff, 0xfe];
+    let mut de = Deserializer::from_slice(&buf[..]);
+    assert_eq!(Some(256), Option::<u16>::deserialize(&mut de).unwrap());
+    assert_eq!(&[0xff, 0xfe][..], <&serde_bytes::Bytes>::deserialize(&mut de).unwrap().as_ref());
+
+    // Strict mode still rejects non-minimal encodings read from a slice.
+    let mut de = Deserializer::from_slice(&[0xcc, 0x01]).with_strict();
+    assert!(matches!(u8::deserialize(&mut de), Err(Error::Syntax(..))));
+    let mut de = Deserializer::from_slice(&[0xd9, 0x01, b'a']).with_strict();
+    assert!(matches!(<&str>::deserialize(&mut de), Err(Error::Syntax(..))));
+}
+
+#[test]
+fn pass_from_slice_at() {
+    use crate::rmps::decode::PathSegment;
+    use std::collections::BTreeMap;
+
+    // {"a": [1, {"x": nil}], 5: "five", "b": {"c": "deep"}}, with a non-string key and nested
+    // siblings to skip over.
+    let mut inner = BTreeMap::new();
+    inner.insert("x", ());
+    let buf = [
+        &[0x83, 0xa1, b'a', 0x92, 0x01][..],
+        &rmps::to_vec(&inner).unwrap(),
+        &[0x05, 0xa4, b'f', b'i', b'v', b'e'],
+        &[0xa1, b'b', 0x81, 0xa1, b'c', 0xa4, b'd', b'e', b'e', b'p'],
+    ]
+    .concat();
+
+    let deep: &str = rmps::from_slice_at(&buf, &["b".into(), "c".into()]).unwrap();
+    assert_eq!("deep", deep);
+    assert_eq!(1u8, rmps::from_slice_at(&buf, &["a".into(), 0.into()]).unwrap());
+    assert_eq!((), rmps::from_slice_at::<()>(&buf, &["a".into(), 1.into(), "x".into()]).unwrap());
+    assert_eq!("five", rmps::from_slice_at::<&str>(&buf, &[PathSegment::Index(5)]).unwrap());
+    assert_eq!(inner, rmps::from_slice_at(&buf, &["a".into(), 1.into()]).unwrap());
+    assert_eq!(7u8, rmps::from_slice_at(&[0x07], &[]).unwrap());
+}
+
+#[test]
+fn fail_from_slice_at_path_not_found() {
+    // {"a": [1, 2], "b": 3}
+    let buf = [0x82, 0xa1, b'a', 0x92, 0x01, 0x02, 0xa1, b'b', 0x03];
+
+    for (path, pos) in [
+        (&["c".into()][..], 0),
+        (&["a".into(), 2.into()][..], 1),
+        (&["a".into(), "x".into()][..], 1),
+        (&["b".into(), 0.into()][..], 1),
+        (&[0.into()][..], 0),
+    ] {
+        match rmps::from_slice_at::<u8>(&buf, path) {
+            Err(Error::Syntax(ref msg)) => assert_eq!(format!("path not found at segment {}", pos), *msg),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(None, rmps::from_slice_at_opt::<u8>(&buf, path).unwrap());
+    }
+
+    assert_eq!(Some(2), rmps::from_slice_at_opt::<u8>(&buf, &["a".into(), 1.into()]).unwrap());
+    // Malformed input and mismatched values are still errors.
+    assert!(rmps::from_slice_at_opt::<u8>(&buf[..4], &["b".into()]).is_err());
+    assert!(rmps::from_slice_at_opt::<u8>(&buf, &["a".into()]).is_err());
+}
+
+#[test]
+fn pass_from_slice_each() {
+    // [[1, "a"], [2, 3], "x", [[[]], "b"], [4, "c"]], where the middle three elements don't match.
+    let buf = [
+        0x95,
+        0x92, 0x01, 0xa1, b'a',
+        0x92, 0x02, 0x03,
+        0xa1, b'x',
+        0x92, 0x91, 0x90, 0xa1, b'b',
+        0x92, 0x04, 0xa1, b'c',
+    ];
+
+    let mut iter = decode::from_slice_each::<(u32, &str)>(&buf).unwrap();
+    assert_eq!((1, "a"), iter.next().unwrap().unwrap());
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().unwrap().is_err());
+    assert_eq!((4, "c"), iter.next().unwrap().unwrap());
+    assert!(iter.next().is_none());
+    assert!(iter.remaining_slice().is_empty());
+}
+
+#[test]
+fn fail_from_slice_each() {
+    match decode::from_slice_each::<u8>(&[0x81, 0x01, 0x02]) {
+        Err(Error::TypeMismatch(Marker::FixMap(1))) => {}
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+
+    // [1, <truncated str>, 3] can't be skipped past the second element.
+    let buf = [0x93, 0x01, 0xa5, b'a'];
+    let vals: Vec<_> = decode::from_slice_each::<u8>(&buf).unwrap().collect();
+    assert_eq!(2, vals.len());
+    assert_eq!(1, *vals[0].as_ref().unwrap());
+    assert!(vals[1].is_err());
+}
@@ -535,3 +535,213 @@ fn pass_from_ref() {
 
     assert_eq!(Dog { name: "Bobby", age: 8 }, rmps::from_read_ref(&buf).unwrap());
 }
+
+#[test]
+fn fail_strict_duplicate_struct_map_key() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        a: u8,
+    }
+
+    // {"a": 1, "b": 2, "b": 3}: duplicates of unknown fields are ignored by serde itself.
+    let buf = [0x83, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02, 0xa1, 0x62, 0x03];
+
+    let mut de = Deserializer::new(&buf[..]);
+    assert_eq!(Struct { a: 1 }, Deserialize::deserialize(&mut de).unwrap());
+
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    match Struct::deserialize(&mut de) {
+        Err(Error::Syntax(msg)) => assert_eq!("duplicate key \"b\" in struct", msg),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // {"a": 1}
+    let buf = [0x81, 0xa1, 0x61, 0x01];
+    let mut de = Deserializer::from_read_ref(&buf[..]).with_strict();
+    assert_eq!(Struct { a: 1 }, Deserialize::deserialize(&mut de).unwrap());
+
+    // Structs encoded as arrays are unaffected.
+    let buf = [0x91, 0x01];
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    assert_eq!(Struct { a: 1 }, Deserialize::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_strict_duplicate_flattened_struct_key() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Inner {
+        b: u8,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Outer {
+        a: u8,
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    // {"a": 1, "b": 2, "a": 3}
+    let buf = [0x83, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02, 0xa1, 0x61, 0x03];
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    match Outer::deserialize(&mut de) {
+        Err(Error::Syntax(msg)) => assert_eq!("duplicate key \"a\" in map", msg),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // {"a": 1, "b": 2, "b": 3}
+    let buf = [0x83, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02, 0xa1, 0x62, 0x03];
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    match Outer::deserialize(&mut de) {
+        Err(Error::Syntax(msg)) => assert_eq!("duplicate key \"b\" in map", msg),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // {"a": 1, "b": 2}
+    let buf = [0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02];
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    assert_eq!(Outer { a: 1, inner: Inner { b: 2 } }, Deserialize::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_strict_duplicate_map_key() {
+    use std::collections::HashMap;
+
+    // {1: 2, 1: 3}
+    let buf = [0x82, 0x01, 0x02, 0x01, 0x03];
+    let mut de = Deserializer::new(&buf[..]);
+    assert_eq!(1, HashMap::<u8, u8>::deserialize(&mut de).unwrap().len());
+
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    match HashMap::<u8, u8>::deserialize(&mut de) {
+        Err(Error::Syntax(msg)) => assert_eq!("duplicate key 1 in map", msg),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // Keys of other types are read as usual: {[1]: 2, [3]: 4}
+    let buf = [0x82, 0x91, 0x01, 0x02, 0x91, 0x03, 0x04];
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    assert_eq!(2, HashMap::<Vec<u8>, u8>::deserialize(&mut de).unwrap().len());
+}
+
+#[test]
+fn pass_skip_unknown_fields() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        a: u8,
+        b: u8,
+    }
+
+    // {"a": 1, "x": [{"k": [nil, 1.0, -1]}, ext(1, [0]), "\xff"], "b": 2}: the unknown value
+    // isn't validated, so the invalid UTF-8 is fine.
+    let buf = [
+        0x83, 0xa1, 0x61, 0x01,
+        0xa1, 0x78, 0x93,
+            0x81, 0xa1, 0x6b, 0x93, 0xc0, 0xcb, 0x3f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff,
+            0xd4, 0x01, 0x00,
+            0xa1, 0xff,
+        0xa1, 0x62, 0x02,
+    ];
+
+    let mut de = Deserializer::from_slice(&buf);
+    assert_eq!(Struct { a: 1, b: 2 }, Deserialize::deserialize(&mut de).unwrap());
+    assert!(de.remaining_slice().is_empty());
+
+    let mut de = Deserializer::new(&buf[..]);
+    assert_eq!(Struct { a: 1, b: 2 }, Deserialize::deserialize(&mut de).unwrap());
+
+    // Strict mode still validates the ignored value.
+    let mut de = Deserializer::new(&buf[..]).with_strict();
+    match Struct::deserialize(&mut de) {
+        Err(Error::Utf8Error(..)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_skip_unknown_field_too_deep() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        a: u8,
+    }
+
+    // {"x": [[[...]]], "a": 1}
+    let mut buf = vec![0x82, 0xa1, 0x78];
+    buf.extend([0x91; 1000]);
+    buf.push(0xc0);
+    buf.extend([0xa1, 0x61, 0x01]);
+
+    let mut de = Deserializer::from_slice(&buf);
+    de.set_max_depth(100);
+    match Struct::deserialize(&mut de) {
+        Err(Error::DepthLimitExceeded) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let mut de = Deserializer::from_slice(&buf);
+    de.set_max_depth(1024);
+    assert_eq!(Struct { a: 1 }, Deserialize::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_skip_unknown_field_truncated() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        a: u8,
+    }
+
+    // {"x": bin8 of 16 bytes, cut short}
+    let buf = [0x81, 0xa1, 0x78, 0xc4, 0x10, 0x00, 0x00];
+
+    match rmps::from_slice::<Struct>(&buf) {
+        Err(Error::InvalidDataRead(..)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    match rmps::from_read::<_, Struct>(&buf[..]) {
+        Err(Error::InvalidDataRead(ref err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // {"x": reserved}
+    let buf = [0x81, 0xa1, 0x78, 0xc1];
+
+    match rmps::from_slice::<Struct>(&buf) {
+        Err(Error::TypeMismatch(rmp::Marker::Reserved)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_skip_unknown_field_from_reader_in_chunks() {
+    use std::io::{self, Read};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        a: u8,
+    }
+
+    // Records the largest buffer that the reader is asked to fill.
+    struct Reader<'a> {
+        buf: &'a [u8],
+        max_read: usize,
+    }
+
+    impl Read for Reader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.max_read = self.max_read.max(buf.len());
+            self.buf.read(buf)
+        }
+    }
+
+    // {"x": bin32 of 1 MiB, "a": 1}
+    let len = 1 << 20;
+    let mut buf = vec![0x82, 0xa1, 0x78, 0xc6];
+    buf.extend((len as u32).to_be_bytes());
+    buf.resize(buf.len() + len, 0);
+    buf.extend([0xa1, 0x61, 0x01]);
+
+    let mut rd = Reader { buf: &buf, max_read: 0 };
+    let mut de = Deserializer::new(&mut rd);
+    assert_eq!(Struct { a: 1 }, Deserialize::deserialize(&mut de).unwrap());
+    assert!(rd.max_read < len / 16, "read {} bytes at once", rd.max_read);
+}
@@ -368,3 +368,597 @@ fn pass_raw_ref_invalid_utf8() {
 fn serializer_one_type_arg() {
     let _s: rmp_serde::Serializer<&mut dyn std::io::Write>;
 }
+
+#[test]
+fn fail_options_size_limit() {
+    let opts = rmp_serde::Options::new().with_limit(4);
+
+    assert_eq!(vec![0xa3, 0x6b, 0x65, 0x79], opts.serialize("key").unwrap());
+    match opts.serialize("keys") {
+        Err(Error::Syntax(ref msg)) if msg == "size limit exceeded" => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_options_size_limit_while_writing() {
+    use serde::ser::SerializeSeq;
+    use std::cell::Cell;
+
+    /// Counts the elements serialized so far.
+    struct Counted<'a>(&'a Cell<u32>, Option<usize>);
+
+    impl Serialize for Counted<'_> {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            let mut seq = se.serialize_seq(self.1)?;
+            for i in 0..1000u32 {
+                self.0.set(i + 1);
+                seq.serialize_element(&i)?;
+            }
+            seq.end()
+        }
+    }
+
+    // Serialization stops at the element crossing the limit, whether the length is known or not.
+    for len in [Some(1000), None] {
+        let count = Cell::new(0);
+        let opts = rmp_serde::Options::new().with_limit(100);
+        match opts.serialize(&Counted(&count, len)) {
+            Err(Error::Syntax(ref msg)) if msg == "size limit exceeded" => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(count.get() <= 100, "{} elements serialized", count.get());
+
+        count.set(0);
+        let mut buf = Vec::new();
+        assert!(opts.to_writer(&mut buf, &Counted(&count, len)).is_err());
+        assert!(count.get() <= 100, "{} elements serialized", count.get());
+        assert!(buf.is_empty());
+    }
+}
+
+#[test]
+fn fail_options_depth_limit() {
+    let val = vec![vec![vec![()]]];
+    let opts = rmp_serde::Options::new().with_max_depth(3);
+
+    match opts.serialize(&val) {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match opts.to_writer(&mut Vec::new(), &val) {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let opts = opts.with_max_depth(4);
+    assert_eq!(vec![0x91, 0x91, 0x91, 0xc0], opts.serialize(&val).unwrap());
+    // Leaving a sequence restores the depth for its siblings.
+    assert!(opts.serialize(&(vec![vec![()]], vec![vec![()]])).is_ok());
+}
+
+#[test]
+fn pass_no_depth_limit_by_default() {
+    /// Nests a sequence the given number of levels deep.
+    struct Nested(usize);
+
+    impl Serialize for Nested {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            match self.0 {
+                0 => se.serialize_unit(),
+                n => se.collect_seq(Some(Nested(n - 1))),
+            }
+        }
+    }
+
+    // Debug builds need more stack than the test thread has for this many levels.
+    let check = || {
+        let val = Nested(1100);
+        let buf = rmp_serde::to_vec(&val).unwrap();
+        assert_eq!(1101, buf.len());
+
+        let mut se = Serializer::new(Vec::new());
+        val.serialize(&mut se).unwrap();
+        assert_eq!(buf, se.into_inner());
+        assert_eq!(buf, rmp_serde::Options::new().serialize(&val).unwrap());
+
+        let mut se = Serializer::new(Vec::new());
+        se.unstable_set_max_depth(1100);
+        assert!(matches!(val.serialize(&mut se), Err(Error::DepthLimitExceeded)));
+    };
+    std::thread::Builder::new().stack_size(64 << 20).spawn(check).unwrap().join().unwrap();
+}
+
+#[test]
+fn pass_options_same_as_to_vec() {
+    let seqs: Vec<Vec<u32>> = vec![vec![1, 2], (0..20).collect(), vec![]];
+    let inner: Vec<_> = seqs.iter().map(|v| UnknownLen(v)).collect();
+    let val = UnknownLen(&inner);
+    let expected = rmp_serde::to_vec(&val).unwrap();
+
+    for opts in [rmp_serde::Options::new(), rmp_serde::Options::new().with_limit(expected.len() as u64)] {
+        assert_eq!(expected, opts.serialize(&val).unwrap());
+        let mut buf = Vec::new();
+        opts.to_writer(&mut buf, &val).unwrap();
+        assert_eq!(expected, buf);
+    }
+
+    // Nothing is written if serialization fails halfway.
+    let mut buf = Vec::new();
+    assert!(rmp_serde::Options::new().with_max_depth(2).to_writer(&mut buf, &val).is_err());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn pass_canonical_map_sorted_by_encoded_key() {
+    use std::collections::HashMap;
+
+    let mut map = HashMap::new();
+    for key in ["b", "a", "aa", "c"] {
+        map.insert(key, 1);
+    }
+    map.insert("", 0);
+
+    let mut buf = Vec::new();
+    map.serialize(&mut Serializer::new(&mut buf).with_canonical()).unwrap();
+
+    // {"": 0, "a": 1, "b": 1, "c": 1, "aa": 1}, shorter strings have smaller markers.
+    assert_eq!(
+        vec![0x85, 0xa0, 0x00, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x01, 0xa1, 0x63, 0x01, 0xa2, 0x61, 0x61, 0x01],
+        buf
+    );
+}
+
+#[test]
+fn pass_canonical_nested_maps() {
+    use std::collections::HashMap;
+
+    let mut inner = HashMap::new();
+    inner.insert(2u8, ());
+    inner.insert(1u8, ());
+    let mut map = HashMap::new();
+    map.insert(1u8, inner.clone());
+    map.insert(0u8, inner);
+
+    let mut buf = Vec::new();
+    map.serialize(&mut Serializer::new(&mut buf).with_canonical()).unwrap();
+
+    assert_eq!(
+        vec![0x82, 0x00, 0x82, 0x01, 0xc0, 0x02, 0xc0, 0x01, 0x82, 0x01, 0xc0, 0x02, 0xc0],
+        buf
+    );
+}
+
+#[test]
+fn pass_canonical_nan() {
+    let nan = f64::from_bits(0x7ff8_0000_dead_beef);
+    let mut buf = Vec::new();
+    nan.serialize(&mut Serializer::new(&mut buf).with_canonical()).unwrap();
+    assert_eq!(vec![0xcb, 0x7f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], buf);
+
+    let nan = -f32::NAN;
+    let mut buf = Vec::new();
+    nan.serialize(&mut Serializer::new(&mut buf).with_canonical()).unwrap();
+    assert_eq!(vec![0xca, 0x7f, 0xc0, 0x00, 0x00], buf);
+}
+
+#[test]
+fn fail_canonical_duplicate_keys() {
+    use serde::ser::SerializeMap;
+
+    struct Duplicates;
+
+    impl Serialize for Duplicates {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            // Different Rust values with the same encoding.
+            let mut map = se.serialize_map(Some(2))?;
+            map.serialize_entry(&1u8, &())?;
+            map.serialize_entry(&1u64, &())?;
+            map.end()
+        }
+    }
+
+    match Duplicates.serialize(&mut Serializer::new(Vec::new()).with_canonical()) {
+        Err(Error::InvalidDataModel(..)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_legacy_raw_str() {
+    let val = "a".repeat(32);
+    let mut buf = Vec::new();
+    val.serialize(&mut Serializer::new(&mut buf).with_legacy_raw()).unwrap();
+
+    assert_eq!([0xda, 0x00, 0x20], buf[..3]);
+    assert_eq!(val.as_bytes(), &buf[3..]);
+
+    let mut buf = Vec::new();
+    "le".serialize(&mut Serializer::new(&mut buf).with_legacy_raw()).unwrap();
+    assert_eq!(vec![0xa2, 0x6c, 0x65], buf);
+}
+
+#[test]
+fn pass_legacy_raw_bytes() {
+    let val = serde_bytes::Bytes::new(&[0xff, 0x00]);
+    let mut buf = Vec::new();
+    val.serialize(&mut Serializer::new(&mut buf).with_legacy_raw()).unwrap();
+
+    assert_eq!(vec![0xa2, 0xff, 0x00], buf);
+}
+
+#[test]
+fn pass_bytes_mode_force_iterables() {
+    use rmps::config::BytesMode;
+
+    let mut buf = Vec::new();
+    vec![1u8, 2, 0xff].serialize(&mut Serializer::new(&mut buf).with_bytes(BytesMode::ForceIterables)).unwrap();
+    assert_eq!(vec![0xc4, 0x03, 0x01, 0x02, 0xff], buf);
+
+    // Tuples, including arrays, are left alone.
+    let mut buf = Vec::new();
+    [1u8, 2].serialize(&mut Serializer::new(&mut buf).with_bytes(BytesMode::ForceIterables)).unwrap();
+    assert_eq!(vec![0x92, 0x01, 0x02], buf);
+
+    // Other integer types are never written as bin.
+    let mut buf = Vec::new();
+    vec![1u16, 2].serialize(&mut Serializer::new(&mut buf).with_bytes(BytesMode::ForceIterables)).unwrap();
+    assert_eq!(vec![0x92, 0x01, 0x02], buf);
+
+    let mut buf = Vec::new();
+    Vec::<u8>::new().serialize(&mut Serializer::new(&mut buf).with_bytes(BytesMode::ForceIterables)).unwrap();
+    assert_eq!(vec![0x90], buf);
+}
+
+#[test]
+fn pass_bytes_mode_force_all() {
+    use rmps::config::BytesMode;
+
+    let mut buf = Vec::new();
+    [1u8, 2].serialize(&mut Serializer::new(&mut buf).with_bytes(BytesMode::ForceAll)).unwrap();
+    assert_eq!(vec![0xc4, 0x02, 0x01, 0x02], buf);
+
+    let mut buf = Vec::new();
+    (1u8, 2u8).serialize(&mut Serializer::new(&mut buf).with_bytes(BytesMode::ForceAll)).unwrap();
+    assert_eq!(vec![0xc4, 0x02, 0x01, 0x02], buf);
+}
+
+#[test]
+fn pass_bytes_mode_mixed_falls_back_to_array() {
+    use rmps::config::BytesMode;
+
+    let mut buf = Vec::new();
+    (1u8, 2u8, "a").serialize(&mut Serializer::new(&mut buf).with_bytes(BytesMode::ForceAll)).unwrap();
+    assert_eq!(vec![0x93, 0x01, 0x02, 0xa1, 0x61], buf);
+}
+
+#[test]
+fn pass_bytes_mode_unknown_len() {
+    use rmps::config::BytesMode;
+    use serde::Serializer as _;
+
+    let mut buf = Vec::new();
+    let mut se = Serializer::new(&mut buf).with_bytes(BytesMode::ForceIterables);
+    (&mut se).collect_seq([1u8, 0xcc].iter().filter(|_| true)).unwrap();
+    assert_eq!(vec![0xc4, 0x02, 0x01, 0xcc], buf);
+
+    let mut buf = Vec::new();
+    let mut se = Serializer::new(&mut buf).with_bytes(BytesMode::ForceIterables);
+    (&mut se).collect_seq([Some(1u8), None].iter().filter(|_| true)).unwrap();
+    assert_eq!(vec![0x92, 0x01, 0xc0], buf);
+}
+
+/// Serializes the elements without telling their number upfront.
+struct UnknownLen<'a, T>(&'a [T]);
+
+impl<T: Serialize> Serialize for UnknownLen<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+        se.collect_seq(self.0.iter().filter(|_| true))
+    }
+}
+
+#[test]
+fn pass_nested_unknown_len() {
+    // Fixed, 16-bit and 32-bit headers, and an empty sequence.
+    let seqs: Vec<Vec<u32>> = vec![vec![1, 2], (0..20).collect(), (0..70_000).collect(), vec![]];
+    let inner: Vec<_> = seqs.iter().map(|v| UnknownLen(v)).collect();
+    let val = UnknownLen(&inner);
+    let expected = rmps::to_vec(&seqs).unwrap();
+
+    assert_eq!(expected, rmps::to_vec(&val).unwrap());
+
+    let mut buf = Vec::new();
+    val.serialize(&mut Serializer::new(&mut buf)).unwrap();
+    assert_eq!(expected, buf);
+}
+
+#[test]
+fn pass_canonical_map_of_unknown_len() {
+    use std::collections::BTreeMap;
+
+    let seqs: Vec<Vec<u32>> = vec![(0..20).collect(), vec![3]];
+    let mut map = BTreeMap::new();
+    map.insert("long key", UnknownLen(&seqs[0]));
+    map.insert("b", UnknownLen(&seqs[1]));
+    let mut expected = BTreeMap::new();
+    expected.insert("long key", &seqs[0]);
+    expected.insert("b", &seqs[1]);
+
+    let mut buf = Vec::new();
+    UnknownLen(&[&map]).serialize(&mut Serializer::new(&mut buf).with_canonical()).unwrap();
+    let mut expected_buf = Vec::new();
+    [&expected].serialize(&mut Serializer::new(&mut expected_buf).with_canonical()).unwrap();
+
+    // "b" sorts first, since shorter strings have smaller markers.
+    assert_eq!(&[0x91, 0x82, 0xa1, 0x62, 0x91, 0x03][..], &buf[..6]);
+    assert_eq!(expected_buf, buf);
+}
+
+#[test]
+fn pass_to_vec_same_as_writer() {
+    fn check<T: Serialize + ?Sized>(val: &T) {
+        let mut buf = Vec::new();
+        val.serialize(&mut Serializer::new(&mut buf)).unwrap();
+        assert_eq!(buf, rmps::to_vec(val).unwrap());
+    }
+
+    for v in [0u64, 127, 128, 255, 256, 65535, 65536, 4294967295, 4294967296, u64::MAX] {
+        check(&v);
+    }
+    for v in [-1i64, -32, -33, -128, -129, -32768, -32769, -2147483648, -2147483649, i64::MIN, i64::MAX] {
+        check(&v);
+    }
+    check(&(true, false, (), None::<u8>, 1.5f32, -0.25f64));
+    for len in [0, 31, 32, 255, 256, 65535, 65536] {
+        check(&"a".repeat(len));
+        check(serde_bytes::Bytes::new(&vec![1; len]));
+        check(&vec![(); len]);
+        check(&(0..len as u32).map(|i| (i, ())).collect::<std::collections::BTreeMap<_, _>>());
+    }
+}
+
+#[test]
+fn pass_to_vec_same_as_rmp_encode() {
+    use rmp::encode as enc;
+
+    fn check<T: Serialize + ?Sized>(val: &T, write: impl FnOnce(&mut Vec<u8>)) {
+        let mut expected = Vec::new();
+        write(&mut expected);
+        assert_eq!(expected, rmps::to_vec(val).unwrap());
+    }
+
+    check(&(), |buf| enc::write_nil(buf).unwrap());
+    check(&true, |buf| enc::write_bool(buf, true).unwrap());
+    check(&false, |buf| enc::write_bool(buf, false).unwrap());
+    for v in [0u64, 127, 128, 255, 256, 65535, 65536, 4294967295, 4294967296, u64::MAX] {
+        check(&v, |buf| { enc::write_uint(buf, v).unwrap(); });
+    }
+    for v in [-1i64, -32, -33, -128, -129, -32768, -32769, -2147483648, -2147483649, i64::MIN, i64::MAX] {
+        check(&v, |buf| { enc::write_sint(buf, v).unwrap(); });
+    }
+    for v in [0.0f32, -1.5, f32::MAX, f32::NAN] {
+        check(&v, |buf| enc::write_f32(buf, v).unwrap());
+    }
+    for v in [0.0f64, -1.5, f64::MAX, f64::INFINITY] {
+        check(&v, |buf| enc::write_f64(buf, v).unwrap());
+    }
+    for len in [0, 15, 16, 31, 32, 255, 256, 65535, 65536] {
+        let s = "a".repeat(len);
+        check(&s, |buf| enc::write_str(buf, &s).unwrap());
+        let b = vec![1; len];
+        check(serde_bytes::Bytes::new(&b), |buf| enc::write_bin(buf, &b).unwrap());
+
+        let array_header = |buf: &mut Vec<u8>| {
+            enc::write_array_len(buf, len as u32).unwrap();
+            buf.extend(std::iter::repeat(0xc0).take(len));
+        };
+        let nils = vec![(); len];
+        check(&nils, array_header);
+        check(&UnknownLen(&nils), array_header);
+
+        let map = (0..len as u32).map(|i| (i, ())).collect::<std::collections::BTreeMap<_, _>>();
+        check(&map, |buf| {
+            enc::write_map_len(buf, len as u32).unwrap();
+            for i in 0..len as u64 {
+                enc::write_uint(buf, i).unwrap();
+                enc::write_nil(buf).unwrap();
+            }
+        });
+    }
+}
+
+#[test]
+fn pass_encoded_size() {
+    let val = (70_000u32, "a name", vec!["x"; 20], serde_bytes::Bytes::new(&[1; 300]));
+    assert_eq!(rmps::to_vec(&val).unwrap().len(), rmps::encoded_size(&val).unwrap());
+
+    // Headers of unknown length are counted at their final size.
+    let seqs: Vec<Vec<u32>> = vec![vec![1, 2], (0..20).collect(), (0..70_000).collect(), vec![]];
+    let inner: Vec<_> = seqs.iter().map(|v| UnknownLen(v)).collect();
+    assert_eq!(rmps::to_vec(&seqs).unwrap().len(), rmps::encoded_size(&UnknownLen(&inner)).unwrap());
+
+    let opts = rmps::Options::new().with_canonical();
+    let map: std::collections::HashMap<_, _> = (0..100u32).map(|i| (i, ())).collect();
+    assert_eq!(opts.serialize(&map).unwrap().len(), opts.encoded_size(&map).unwrap());
+}
+
+#[test]
+fn fail_encoded_size_limit() {
+    let opts = rmps::Options::new().with_limit(8);
+    assert_eq!(8, opts.encoded_size(&"1234567").unwrap());
+    match opts.encoded_size(&"12345678") {
+        Err(Error::Syntax(ref msg)) if msg == "size limit exceeded" => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_encoded_size_depth_limit() {
+    let val = vec![vec![vec![()]]];
+
+    let opts = rmps::Options::new().with_max_depth(3);
+    match opts.encoded_size(&val) {
+        Err(Error::DepthLimitExceeded) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    // The depth limit applies along with a size limit.
+    match opts.with_limit(100).serialize(&val) {
+        Err(Error::DepthLimitExceeded) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let opts = rmps::Options::new().with_max_depth(4);
+    assert_eq!(4, opts.encoded_size(&val).unwrap());
+}
+
+#[test]
+fn pass_gather_writer() {
+    use std::io::{IoSlice, Write};
+
+    /// Accepts at most 1000 bytes per write, and records the address of every slice given.
+    #[derive(Default)]
+    struct Recorder {
+        buf: Vec<u8>,
+        addrs: Vec<*const u8>,
+        writes: usize,
+    }
+
+    impl Write for Recorder {
+        fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+            self.write_vectored(&[IoSlice::new(data)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+            self.writes += 1;
+            let mut len = 0;
+            for buf in bufs.iter().filter(|buf| !buf.is_empty()) {
+                self.addrs.push(buf.as_ptr());
+                let n = buf.len().min(1000 - len);
+                self.buf.extend_from_slice(&buf[..n]);
+                len += n;
+            }
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let blob = vec![0x55; 2500];
+    let text = "x".repeat(300);
+    let val = (1, serde_bytes::Bytes::new(&blob), "small", text.as_str(), 2);
+
+    let mut wr = rmps::encode::GatherWriter::with_threshold(256, Recorder::default());
+    val.serialize(&mut Serializer::new(&mut wr)).unwrap();
+    // Only the last integer is left buffered, the rest went out with the two large payloads in
+    // writes of at most 1000 bytes.
+    assert_eq!([0x02], wr.buffer());
+    assert_eq!(4, wr.get_ref().writes);
+    let rec = wr.into_inner().unwrap();
+
+    assert_eq!(rmps::to_vec(&val).unwrap(), rec.buf);
+    // Both large payloads were written from their original memory.
+    assert!(rec.addrs.contains(&blob.as_ptr()));
+    assert!(rec.addrs.contains(&text.as_ptr()));
+}
+
+#[test]
+fn pass_gather_writer_small_writes() {
+    use std::io::Write;
+
+    /// Counts the writes it is given.
+    #[derive(Default)]
+    struct Counter {
+        buf: Vec<u8>,
+        writes: usize,
+    }
+
+    impl Write for Counter {
+        fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+            self.writes += 1;
+            self.buf.extend_from_slice(data);
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Thousands of writes of at most 5 bytes each.
+    let val: Vec<u32> = (0..10_000).map(|i| i * 1000).collect();
+    let expected = rmps::to_vec(&val).unwrap();
+
+    let mut wr = rmps::encode::GatherWriter::with_threshold(256, Counter::default());
+    val.serialize(&mut Serializer::new(&mut wr)).unwrap();
+    assert!(wr.buffer().len() < 256);
+    assert!(wr.get_ref().writes <= expected.len() / (256 - 5), "{} writes", wr.get_ref().writes);
+    let counter = wr.into_inner().unwrap();
+
+    assert_eq!(expected, counter.buf);
+}
+
+#[test]
+fn fail_gather_writer_partial_write() {
+    use std::io::{ErrorKind, IoSlice, Write};
+
+    /// Accepts 100 bytes, then fails.
+    #[derive(Default)]
+    struct Failing {
+        buf: Vec<u8>,
+    }
+
+    impl Write for Failing {
+        fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+            self.write_vectored(&[IoSlice::new(data)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+            if self.buf.len() == 100 {
+                return Err(ErrorKind::Other.into());
+            }
+            let mut len = 0;
+            for buf in bufs {
+                let n = buf.len().min(100 - self.buf.len());
+                self.buf.extend_from_slice(&buf[..n]);
+                len += n;
+            }
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut wr = rmps::encode::GatherWriter::with_threshold(16, Failing::default());
+    assert_eq!(10, wr.write(&[1; 10]).unwrap());
+    // The buffered bytes and the first 90 bytes of the payload fit, which is reported as progress.
+    assert_eq!(90, wr.write(&[2; 200]).unwrap());
+    assert!(wr.buffer().is_empty());
+    // Nothing of the rest fits, which is an error.
+    assert_eq!(ErrorKind::Other, wr.write(&[2; 110]).unwrap_err().kind());
+    assert!(wr.write_all(&[3; 20]).is_err());
+
+    let mut expected = vec![1; 10];
+    expected.resize(100, 2);
+    assert_eq!(expected, wr.get_ref().buf);
+}
+
+#[test]
+fn pass_to_slice() {
+    let mut buf = [0; 8];
+    let len = rmps::to_slice(&(42, "abc"), &mut buf).unwrap();
+    assert_eq!(6, len);
+    assert_eq!([0x92, 0x2a, 0xa3, 0x61, 0x62, 0x63], buf[..len]);
+}
+
+#[test]
+fn fail_to_slice_too_small() {
+    let mut buf = [0; 5];
+    match rmps::to_slice(&(42, "abc"), &mut buf) {
+        Err(rmps::encode::Error::InvalidValueWrite(..)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
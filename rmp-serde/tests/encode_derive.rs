@@ -360,3 +360,40 @@ fn pass_struct_as_map_using_triple_ext_many_times() {
     assert_eq!(vec![0x82, 0xa4, 0x6e, 0x61, 0x6d, 0x65, 0xa5, 0x42, 0x6f, 0x62, 0x62, 0x79, 0xa3, 0x61, 0x67, 0x65, 0x08],
                se.into_inner());
 }
+
+#[test]
+fn pass_options_struct_map_and_variant_index() {
+    #[derive(Serialize)]
+    struct Struct {
+        et: Enum,
+    }
+
+    #[derive(Serialize)]
+    enum Enum {
+        _V1,
+        V2,
+    }
+
+    let opts = rmp_serde::Options::new().with_struct_map().with_variant_index();
+    let buf = opts.serialize(&Struct { et: Enum::V2 }).unwrap();
+
+    // {"et": 1}
+    assert_eq!(vec![0x81, 0xa2, 0x65, 0x74, 0x01], buf);
+}
+
+#[test]
+fn pass_struct_encoded_size() {
+    #[derive(Serialize)]
+    struct Struct<'a> {
+        id: u32,
+        name: &'a str,
+        tags: Vec<&'a str>,
+    }
+
+    let val = Struct { id: 70_000, name: "a name", tags: vec!["x"; 20] };
+    assert_eq!(rmps::to_vec(&val).unwrap().len(), rmps::encoded_size(&val).unwrap());
+    assert_eq!(rmps::to_vec_named(&val).unwrap().len(), rmps::encoded_size_named(&val).unwrap());
+
+    let opts = rmps::Options::new().with_struct_map().with_canonical();
+    assert_eq!(opts.serialize(&val).unwrap().len(), opts.encoded_size(&val).unwrap());
+}
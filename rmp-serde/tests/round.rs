@@ -698,3 +698,103 @@ fn assert_roundtrips_config<T, CSF, SC, CDF, DC>(
 
     assert_eq!(val, &val2, "Config: {}", desc);
 }
+
+#[test]
+fn roundtrip_options() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Human {
+        age: u32,
+        name: String,
+        pet: Pet,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Pet {
+        Dog { name: String },
+        None,
+    }
+
+    let val = Human {
+        age: 42,
+        name: "John".into(),
+        pet: Pet::Dog { name: "Bobby".into() },
+    };
+
+    for struct_map in [false, true] {
+        for variant_index in [false, true] {
+            let mut opts = rmps::Options::new();
+            if struct_map {
+                opts = opts.with_struct_map();
+            }
+            if variant_index {
+                opts = opts.with_variant_index();
+            }
+
+            let buf = opts.serialize(&val).unwrap();
+            assert_eq!(val, opts.deserialize::<Human>(&buf).unwrap());
+            assert_eq!(val, rmps::from_slice::<Human>(&buf).unwrap());
+        }
+    }
+
+    let buf = rmps::Options::new().with_variant_index().serialize(&Pet::None).unwrap();
+    assert_eq!(vec![0x01], buf);
+    assert_eq!(Pet::None, rmps::from_slice::<Pet>(&buf).unwrap());
+}
+
+#[test]
+fn roundtrip_options_human_readable() {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    let addr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let opts = rmps::Options::new().with_human_readable();
+
+    let buf = opts.serialize(&addr).unwrap();
+    assert_eq!(rmps::encode::to_vec(&"127.0.0.1").unwrap(), buf);
+    assert_eq!(addr, opts.deserialize::<IpAddr>(&buf).unwrap());
+}
+
+#[test]
+fn roundtrip_legacy_raw() {
+    use serde_bytes::ByteBuf;
+
+    let val = (
+        "x".repeat(200),
+        ByteBuf::from(vec![0xc1; 40]),
+        u128::MAX - 1,
+        -42i128,
+    );
+    let opts = rmp_serde::Options::new().with_legacy_raw();
+    let buf = opts.serialize(&val).unwrap();
+
+    assert!(!buf.contains(&0xd9));
+    assert!(!buf.iter().any(|&b| (0xc4..=0xc6).contains(&b)));
+    assert_eq!(val, opts.deserialize(&buf).unwrap());
+}
+
+#[test]
+fn roundtrip_bytes_mode() {
+    use rmp_serde::config::BytesMode;
+    use std::collections::VecDeque;
+
+    let val = (vec![0u8; 300], [7u8; 4], VecDeque::from(vec![1u8, 2]), vec![vec![3u8]]);
+    for mode in [BytesMode::Normal, BytesMode::ForceIterables, BytesMode::ForceAll] {
+        let opts = rmp_serde::Options::new().with_bytes(mode);
+        let buf = opts.serialize(&val).unwrap();
+        assert_eq!(val, opts.deserialize(&buf).unwrap());
+    }
+}
+
+#[test]
+fn roundtrip_strict() {
+    use std::collections::BTreeMap;
+
+    let ints: Vec<i64> = vec![0, 127, 128, 255, 256, 65535, 65536, -1, -32, -33, -128, -129, -32768, -32769, i64::MIN, i64::MAX];
+    let strs: Vec<String> = [0, 31, 32, 255, 256, 65536].iter().map(|&n| "a".repeat(n)).collect();
+    let vecs: Vec<Vec<u8>> = [0, 15, 16, 65536].iter().map(|&n| vec![1; n]).collect();
+    let map: BTreeMap<u32, f64> = (0..16).map(|n| (n * 1000, n as f64)).collect();
+    let val = (ints, strs, vecs, map, u64::MAX);
+
+    let opts = rmp_serde::Options::new().with_strict();
+    let buf = opts.serialize(&val).unwrap();
+    assert_eq!(val, opts.deserialize(&buf).unwrap());
+}
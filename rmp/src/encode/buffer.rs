@@ -37,6 +37,13 @@ impl Display for FixedBufCapacityOverflow {
 }
 #[cfg(not(feature = "std"))]
 impl crate::encode::RmpWriteErr for FixedBufCapacityOverflow {}
+#[cfg(not(feature = "std"))]
+impl From<core::convert::Infallible> for FixedBufCapacityOverflow {
+    #[inline]
+    fn from(err: core::convert::Infallible) -> Self {
+        match err {}
+    }
+}
 
 /// Fallback implementation for fixed-capacity buffers
 ///
@@ -40,3 +40,43 @@ pub fn write_str<W: RmpWrite>(wr: &mut W, data: &str) -> Result<(), ValueWriteEr
     write_str_len(wr, data.len() as u32)?;
     wr.write_bytes(data.as_bytes()).map_err(ValueWriteError::InvalidDataWrite)
 }
+
+/// Encodes and attempts to write the most efficient raw length implementation of the old
+/// MessagePack specification to the given write, returning the marker used.
+///
+/// The old specification had a single "raw" family for both strings and binaries, and no `str8`
+/// marker, which is why lengths in `32..256` are written as `str16`. Use it to talk to peers
+/// that predate the `str8` and `bin` types.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+pub fn write_raw_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueWriteError<W::Error>> {
+    if len < 32 {
+        write_marker(wr, Marker::FixStr(len as u8))?;
+        Ok(Marker::FixStr(len as u8))
+    } else if len <= u16::MAX as u32 {
+        write_marker(wr, Marker::Str16)?;
+        wr.write_data_u16(len as u16)?;
+        Ok(Marker::Str16)
+    } else {
+        write_marker(wr, Marker::Str32)?;
+        wr.write_data_u32(len)?;
+        Ok(Marker::Str32)
+    }
+}
+
+/// Encodes and attempts to write the given bytes as an old MessagePack specification raw value
+/// to the given `Write`.
+///
+/// See [`write_raw_len`] for details.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+pub fn write_raw<W: RmpWrite>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteError<W::Error>> {
+    write_raw_len(wr, data.len() as u32)?;
+    wr.write_bytes(data).map_err(ValueWriteError::InvalidDataWrite)
+}
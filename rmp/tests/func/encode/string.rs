@@ -36,3 +36,40 @@ fn pass_pack_len_u32() {
 
     assert_eq!([0xdb, 0xff, 0xff, 0xff, 0xff], buf);
 }
+
+#[test]
+fn pass_pack_raw_len_fix() {
+    let mut buf = [0x00];
+
+    assert_eq!(Marker::FixStr(31), write_raw_len(&mut &mut buf[..], 31).unwrap());
+
+    assert_eq!([0xbf], buf);
+}
+
+#[test]
+fn pass_pack_raw_len_u8_as_u16() {
+    let mut buf = [0x00, 0x00, 0x00];
+
+    assert_eq!(Marker::Str16, write_raw_len(&mut &mut buf[..], 255).unwrap());
+
+    assert_eq!([0xda, 0x00, 0xff], buf);
+}
+
+#[test]
+fn pass_pack_raw_len_u32() {
+    let mut buf = [0x00, 0x00, 0x00, 0x00, 0x00];
+
+    assert_eq!(Marker::Str32, write_raw_len(&mut &mut buf[..], 65536).unwrap());
+
+    assert_eq!([0xdb, 0x00, 0x01, 0x00, 0x00], buf);
+}
+
+#[test]
+fn pass_pack_raw() {
+    let mut buf = Vec::new();
+
+    write_raw(&mut buf, &[0xff; 32]).unwrap();
+
+    assert_eq!([0xda, 0x00, 0x20], buf[..3]);
+    assert_eq!(&[0xff; 32][..], &buf[3..]);
+}
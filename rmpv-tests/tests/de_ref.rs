@@ -92,35 +92,35 @@ fn pass_value_map() {
 
 #[test]
 fn pass_uint_from_value() {
-    assert_eq!(i8::min_value(), deserialize_from(ValueRef::from(i8::min_value())).unwrap());
-    assert_eq!(i8::max_value(), deserialize_from(ValueRef::from(i8::max_value())).unwrap());
-    assert_eq!(i16::min_value(), deserialize_from(ValueRef::from(i16::min_value())).unwrap());
-    assert_eq!(i16::max_value(), deserialize_from(ValueRef::from(i16::max_value())).unwrap());
-    assert_eq!(i32::min_value(), deserialize_from(ValueRef::from(i32::min_value())).unwrap());
-    assert_eq!(i32::max_value(), deserialize_from(ValueRef::from(i32::max_value())).unwrap());
-    assert_eq!(i64::min_value(), deserialize_from(ValueRef::from(i64::min_value())).unwrap());
-    assert_eq!(i64::max_value(), deserialize_from(ValueRef::from(i64::max_value())).unwrap());
+    assert_eq!(i8::min_value(), deserialize_from::<i8, _>(ValueRef::from(i8::min_value())).unwrap());
+    assert_eq!(i8::max_value(), deserialize_from::<i8, _>(ValueRef::from(i8::max_value())).unwrap());
+    assert_eq!(i16::min_value(), deserialize_from::<i16, _>(ValueRef::from(i16::min_value())).unwrap());
+    assert_eq!(i16::max_value(), deserialize_from::<i16, _>(ValueRef::from(i16::max_value())).unwrap());
+    assert_eq!(i32::min_value(), deserialize_from::<i32, _>(ValueRef::from(i32::min_value())).unwrap());
+    assert_eq!(i32::max_value(), deserialize_from::<i32, _>(ValueRef::from(i32::max_value())).unwrap());
+    assert_eq!(i64::min_value(), deserialize_from::<i64, _>(ValueRef::from(i64::min_value())).unwrap());
+    assert_eq!(i64::max_value(), deserialize_from::<i64, _>(ValueRef::from(i64::max_value())).unwrap());
 }
 
 #[test]
 fn pass_sint_from_value() {
-    assert_eq!(0, deserialize_from(ValueRef::from(0)).unwrap());
-    assert_eq!(u8::max_value(), deserialize_from(ValueRef::from(u8::max_value())).unwrap());
-    assert_eq!(u16::max_value(), deserialize_from(ValueRef::from(u16::max_value())).unwrap());
-    assert_eq!(u32::max_value(), deserialize_from(ValueRef::from(u32::max_value())).unwrap());
-    assert_eq!(u64::max_value(), deserialize_from(ValueRef::from(u64::max_value())).unwrap());
+    assert_eq!(0, deserialize_from::<i32, _>(ValueRef::from(0)).unwrap());
+    assert_eq!(u8::max_value(), deserialize_from::<u8, _>(ValueRef::from(u8::max_value())).unwrap());
+    assert_eq!(u16::max_value(), deserialize_from::<u16, _>(ValueRef::from(u16::max_value())).unwrap());
+    assert_eq!(u32::max_value(), deserialize_from::<u32, _>(ValueRef::from(u32::max_value())).unwrap());
+    assert_eq!(u64::max_value(), deserialize_from::<u64, _>(ValueRef::from(u64::max_value())).unwrap());
 }
 
 #[test]
 fn pass_f32_from_value() {
-    assert_eq!(0.0f32, deserialize_from(ValueRef::from(0.0f32)).unwrap());
-    assert_eq!(std::f32::consts::PI, deserialize_from(ValueRef::from(std::f32::consts::PI)).unwrap());
+    assert_eq!(0.0f32, deserialize_from::<f32, _>(ValueRef::from(0.0f32)).unwrap());
+    assert_eq!(std::f32::consts::PI, deserialize_from::<f32, _>(ValueRef::from(std::f32::consts::PI)).unwrap());
 }
 
 #[test]
 fn pass_f64_from_value() {
-    assert_eq!(0.0, deserialize_from(ValueRef::from(0.0)).unwrap());
-    assert_eq!(std::f64::consts::PI, deserialize_from(ValueRef::from(std::f64::consts::PI)).unwrap());
+    assert_eq!(0.0, deserialize_from::<f64, _>(ValueRef::from(0.0)).unwrap());
+    assert_eq!(std::f64::consts::PI, deserialize_from::<f64, _>(ValueRef::from(std::f64::consts::PI)).unwrap());
 }
 
 #[test]
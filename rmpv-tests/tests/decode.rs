@@ -109,35 +109,35 @@ fn pass_value_map() {
 
 #[test]
 fn pass_uint_from_value() {
-    assert_eq!(i8::min_value(), from_value(Value::from(i8::min_value())).unwrap());
-    assert_eq!(i8::max_value(), from_value(Value::from(i8::max_value())).unwrap());
-    assert_eq!(i16::min_value(), from_value(Value::from(i16::min_value())).unwrap());
-    assert_eq!(i16::max_value(), from_value(Value::from(i16::max_value())).unwrap());
-    assert_eq!(i32::min_value(), from_value(Value::from(i32::min_value())).unwrap());
-    assert_eq!(i32::max_value(), from_value(Value::from(i32::max_value())).unwrap());
-    assert_eq!(i64::min_value(), from_value(Value::from(i64::min_value())).unwrap());
-    assert_eq!(i64::max_value(), from_value(Value::from(i64::max_value())).unwrap());
+    assert_eq!(i8::min_value(), from_value::<i8>(Value::from(i8::min_value())).unwrap());
+    assert_eq!(i8::max_value(), from_value::<i8>(Value::from(i8::max_value())).unwrap());
+    assert_eq!(i16::min_value(), from_value::<i16>(Value::from(i16::min_value())).unwrap());
+    assert_eq!(i16::max_value(), from_value::<i16>(Value::from(i16::max_value())).unwrap());
+    assert_eq!(i32::min_value(), from_value::<i32>(Value::from(i32::min_value())).unwrap());
+    assert_eq!(i32::max_value(), from_value::<i32>(Value::from(i32::max_value())).unwrap());
+    assert_eq!(i64::min_value(), from_value::<i64>(Value::from(i64::min_value())).unwrap());
+    assert_eq!(i64::max_value(), from_value::<i64>(Value::from(i64::max_value())).unwrap());
 }
 
 #[test]
 fn pass_sint_from_value() {
-    assert_eq!(0, from_value(Value::from(0)).unwrap());
-    assert_eq!(u8::max_value(), from_value(Value::from(u8::max_value())).unwrap());
-    assert_eq!(u16::max_value(), from_value(Value::from(u16::max_value())).unwrap());
-    assert_eq!(u32::max_value(), from_value(Value::from(u32::max_value())).unwrap());
-    assert_eq!(u64::max_value(), from_value(Value::from(u64::max_value())).unwrap());
+    assert_eq!(0, from_value::<i32>(Value::from(0)).unwrap());
+    assert_eq!(u8::max_value(), from_value::<u8>(Value::from(u8::max_value())).unwrap());
+    assert_eq!(u16::max_value(), from_value::<u16>(Value::from(u16::max_value())).unwrap());
+    assert_eq!(u32::max_value(), from_value::<u32>(Value::from(u32::max_value())).unwrap());
+    assert_eq!(u64::max_value(), from_value::<u64>(Value::from(u64::max_value())).unwrap());
 }
 
 #[test]
 fn pass_f32_from_value() {
-    assert_eq!(0.0f32, from_value(Value::from(0.0f32)).unwrap());
-    assert_eq!(std::f32::consts::PI, from_value(Value::from(std::f32::consts::PI)).unwrap());
+    assert_eq!(0.0f32, from_value::<f32>(Value::from(0.0f32)).unwrap());
+    assert_eq!(std::f32::consts::PI, from_value::<f32>(Value::from(std::f32::consts::PI)).unwrap());
 }
 
 #[test]
 fn pass_f64_from_value() {
-    assert_eq!(0.0, from_value(Value::from(0.0)).unwrap());
-    assert_eq!(std::f64::consts::PI, from_value(Value::from(std::f64::consts::PI)).unwrap());
+    assert_eq!(0.0, from_value::<f64>(Value::from(0.0)).unwrap());
+    assert_eq!(std::f64::consts::PI, from_value::<f64>(Value::from(std::f64::consts::PI)).unwrap());
 }
 
 #[test]
@@ -0,0 +1,106 @@
+use std::convert::TryFrom;
+
+use serde_json::json;
+
+use rmpv::decode::read_value;
+use rmpv::json::ToJsonError;
+use rmpv::Value;
+
+#[test]
+fn pass_from_json() {
+    let json = json!({
+        "nil": null,
+        "bool": true,
+        "uint": u64::MAX,
+        "int": i64::MIN,
+        "float": 1.5,
+        "str": "le message",
+        "arr": [1, "two"],
+    });
+
+    let val = Value::Map(vec![
+        (Value::from("arr"), Value::Array(vec![Value::from(1), Value::from("two")])),
+        (Value::from("bool"), Value::Boolean(true)),
+        (Value::from("float"), Value::F64(1.5)),
+        (Value::from("int"), Value::from(i64::MIN)),
+        (Value::from("nil"), Value::Nil),
+        (Value::from("str"), Value::from("le message")),
+        (Value::from("uint"), Value::from(u64::MAX)),
+    ]);
+
+    assert_eq!(val, Value::try_from(json).unwrap());
+}
+
+#[test]
+fn pass_json_round_trip() {
+    let json = json!({
+        "id": 42,
+        "neg": -1,
+        "float": 0.25,
+        "tags": ["a", null, false],
+        "nested": {"empty": {}, "list": []},
+    });
+
+    let val = Value::try_from(json.clone()).unwrap();
+    assert_eq!(json, serde_json::Value::try_from(val).unwrap());
+}
+
+#[test]
+fn pass_to_json_widens_f32() {
+    assert_eq!(json!(0.5), serde_json::Value::try_from(Value::F32(0.5)).unwrap());
+}
+
+#[test]
+fn pass_to_json_binary_as_array() {
+    let val = Value::Binary(vec![0, 1, 255]);
+    assert_eq!(json!([0, 1, 255]), serde_json::Value::try_from(val).unwrap());
+}
+
+#[test]
+fn fail_to_json_non_string_key() {
+    let val = Value::Map(vec![
+        (Value::from("ok"), Value::Nil),
+        (Value::from(1), Value::Nil),
+    ]);
+
+    assert_eq!(Err(ToJsonError::NonStringKey(Value::from(1))), serde_json::Value::try_from(val));
+}
+
+#[test]
+fn fail_to_json_duplicate_key() {
+    let val = Value::Map(vec![
+        (Value::from("k"), Value::from(1)),
+        (Value::from("j"), Value::from(2)),
+        (Value::from("k"), Value::from(3)),
+    ]);
+
+    assert_eq!(Err(ToJsonError::DuplicateKey(Value::from("k"))), serde_json::Value::try_from(val));
+}
+
+#[test]
+fn fail_to_json_nested_ext() {
+    let val = Value::Array(vec![Value::Nil, Value::Ext(-1, vec![1, 2])]);
+
+    assert_eq!(Err(ToJsonError::Ext(-1, vec![1, 2])), serde_json::Value::try_from(val));
+}
+
+#[test]
+fn fail_to_json_non_finite_float() {
+    let err = serde_json::Value::try_from(Value::F32(f32::INFINITY)).unwrap_err();
+    assert_eq!(ToJsonError::NonFiniteFloat(f64::INFINITY), err);
+
+    match serde_json::Value::try_from(Value::F64(f64::NAN)) {
+        Err(ToJsonError::NonFiniteFloat(val)) => assert!(val.is_nan()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_to_json_invalid_utf8() {
+    let val = read_value(&mut &[0xa2, 0xc3, 0x28][..]).unwrap();
+    assert_eq!(Err(ToJsonError::InvalidUtf8(vec![0xc3, 0x28])), serde_json::Value::try_from(val));
+
+    let key = read_value(&mut &[0xa1, 0xff][..]).unwrap();
+    let val = Value::Map(vec![(key.clone(), Value::Nil)]);
+    assert_eq!(Err(ToJsonError::NonStringKey(key)), serde_json::Value::try_from(val));
+}
@@ -0,0 +1,664 @@
+//! A diagnostic notation for `Value`, a human-readable text format in the spirit of CBOR's.
+//!
+//! Unlike the `Display` impl, the notation keeps every detail of a value, so that parsing the
+//! printed text results in an equal `Value`:
+//!
+//! | Value          | Notation |
+//! |----------------|----------|
+//! | Nil, Boolean   | `nil`, `true`, `false` |
+//! | Integer        | `42`, `-1` |
+//! | F64            | `1.5`, `1e300`, `-0.0`, `Infinity`, `-Infinity`, `NaN` |
+//! | F32            | `f32(1.5)`, `f32(NaN)` |
+//! | String         | `"text"`, with `\"`, `\\`, `\n`, `\r`, `\t` and `\u{7f}` escapes |
+//! | Invalid String | `str(h'c328')`, which holds the bytes in hex |
+//! | Binary         | `h'00ff'` |
+//! | Ext            | `ext(-1, h'00ff')` |
+//! | Array          | `[1, 2]` |
+//! | Map            | `{"a": 1, 2: [nil]}` |
+//!
+//! A float always has a decimal point or an exponent, which tells it apart from an Integer. A NaN
+//! other than `f64::NAN` or `f32::NAN` carries its bits, as in `NaN(0x7ff8000000000001)`.
+//! Whitespace is allowed between tokens, and a trailing comma in Arrays and Maps is accepted.
+//!
+//! # Examples
+//!
+//! ```
+//! use rmpv::{diag, msgpack, Value};
+//!
+//! let val = msgpack!({"id": 42, "ratio": Value::F32(0.5), "raw": bin(vec![0, 255]), 1: nil});
+//!
+//! let text = diag::to_string(&val);
+//! assert_eq!(r#"{"id": 42, "ratio": f32(0.5), "raw": h'00ff', 1: nil}"#, text);
+//! assert_eq!(val, diag::from_str(&text).unwrap());
+//!
+//! assert_eq!("{\n  \"id\": 42,\n  \"ratio\": f32(0.5),\n  \"raw\": h'00ff',\n  1: nil\n}",
+//!     diag::to_string_pretty(&val));
+//! ```
+
+use std::error;
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::decode::MAX_DEPTH;
+use crate::{Utf8String, Value};
+
+/// Prints values in the diagnostic notation.
+///
+/// By default, values are printed compactly on a single line and in full.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{diag::Printer, msgpack};
+///
+/// let val = msgpack!({"list": [1, 2], "blob": bin(vec![0; 100])});
+/// let printer = Printer::new().with_indent(4).with_truncation(4);
+///
+/// assert_eq!("{\n    \"list\": [\n        1,\n        2\n    ],\n    \"blob\": h'00000000'...\n}",
+///     printer.print(&val));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Printer {
+    indent: Option<usize>,
+    max_len: Option<usize>,
+}
+
+impl Printer {
+    /// Creates a printer for the compact, untruncated notation.
+    #[inline]
+    pub fn new() -> Self {
+        Printer::default()
+    }
+
+    /// Prints each element of non-empty Arrays and Maps on its own line, indented by the given
+    /// number of spaces per level.
+    #[inline]
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = Some(indent);
+        self
+    }
+
+    /// Truncates Strings to `max_len` characters and Binary, Ext data and invalid Strings to
+    /// `max_len` bytes. A truncated value is followed by `...`, and fails to parse.
+    #[inline]
+    pub fn with_truncation(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Prints the value into a `String`.
+    pub fn print(&self, val: &Value) -> String {
+        let mut buf = String::new();
+        self.write(&mut buf, val).expect("writing into a String should not fail");
+        buf
+    }
+
+    /// Prints the value into the given writer.
+    pub fn write<W: Write>(&self, wr: &mut W, val: &Value) -> fmt::Result {
+        self.write_value(wr, val, 0)
+    }
+
+    fn write_value<W: Write>(&self, wr: &mut W, val: &Value, level: usize) -> fmt::Result {
+        match *val {
+            Value::Nil => wr.write_str("nil"),
+            Value::Boolean(val) => write!(wr, "{}", val),
+            Value::Integer(val) => write!(wr, "{}", val),
+            Value::F32(val) => {
+                wr.write_str("f32(")?;
+                write_f32(wr, val)?;
+                wr.write_char(')')
+            }
+            Value::F64(val) => write_f64(wr, val),
+            Value::String(ref val) => match val.as_str() {
+                Some(s) => self.write_str(wr, s),
+                None => {
+                    wr.write_str("str(")?;
+                    self.write_bytes(wr, val.as_bytes())?;
+                    wr.write_char(')')
+                }
+            },
+            Value::Binary(ref val) => self.write_bytes(wr, val),
+            Value::Array(ref vec) => {
+                self.write_container(wr, ('[', ']'), vec, level, |wr, val, level| {
+                    self.write_value(wr, val, level)
+                })
+            }
+            Value::Map(ref vec) => {
+                self.write_container(wr, ('{', '}'), vec, level, |wr, (k, v), level| {
+                    self.write_value(wr, k, level)?;
+                    wr.write_str(": ")?;
+                    self.write_value(wr, v, level)
+                })
+            }
+            Value::Ext(ty, ref data) => {
+                write!(wr, "ext({}, ", ty)?;
+                self.write_bytes(wr, data)?;
+                wr.write_char(')')
+            }
+        }
+    }
+
+    fn write_container<W, T, F>(&self, wr: &mut W, delims: (char, char), items: &[T], level: usize,
+                                mut write_item: F) -> fmt::Result
+        where W: Write,
+              F: FnMut(&mut W, &T, usize) -> fmt::Result
+    {
+        wr.write_char(delims.0)?;
+        if items.is_empty() {
+            return wr.write_char(delims.1);
+        }
+
+        for (idx, item) in items.iter().enumerate() {
+            if idx > 0 {
+                wr.write_char(',')?;
+                if self.indent.is_none() {
+                    wr.write_char(' ')?;
+                }
+            }
+            self.write_line_break(wr, level + 1)?;
+            write_item(wr, item, level + 1)?;
+        }
+        self.write_line_break(wr, level)?;
+        wr.write_char(delims.1)
+    }
+
+    fn write_line_break<W: Write>(&self, wr: &mut W, level: usize) -> fmt::Result {
+        if let Some(indent) = self.indent {
+            wr.write_char('\n')?;
+            for _ in 0..indent * level {
+                wr.write_char(' ')?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_str<W: Write>(&self, wr: &mut W, s: &str) -> fmt::Result {
+        let (s, truncated) = match self.max_len.and_then(|max_len| s.char_indices().nth(max_len)) {
+            Some((idx, ..)) => (&s[..idx], true),
+            None => (s, false),
+        };
+
+        wr.write_char('"')?;
+        for c in s.chars() {
+            match c {
+                '"' => wr.write_str("\\\"")?,
+                '\\' => wr.write_str("\\\\")?,
+                '\n' => wr.write_str("\\n")?,
+                '\r' => wr.write_str("\\r")?,
+                '\t' => wr.write_str("\\t")?,
+                c if c.is_control() => write!(wr, "\\u{{{:x}}}", c as u32)?,
+                c => wr.write_char(c)?,
+            }
+        }
+        wr.write_char('"')?;
+
+        if truncated {
+            wr.write_str("...")?;
+        }
+        Ok(())
+    }
+
+    fn write_bytes<W: Write>(&self, wr: &mut W, bytes: &[u8]) -> fmt::Result {
+        let (bytes, truncated) = match self.max_len {
+            Some(max_len) if bytes.len() > max_len => (&bytes[..max_len], true),
+            _ => (bytes, false),
+        };
+
+        wr.write_str("h'")?;
+        for byte in bytes {
+            write!(wr, "{:02x}", byte)?;
+        }
+        wr.write_char('\'')?;
+
+        if truncated {
+            wr.write_str("...")?;
+        }
+        Ok(())
+    }
+}
+
+fn write_f32<W: Write>(wr: &mut W, val: f32) -> fmt::Result {
+    if val.is_nan() {
+        if val.to_bits() == f32::NAN.to_bits() {
+            wr.write_str("NaN")
+        } else {
+            write!(wr, "NaN(0x{:08x})", val.to_bits())
+        }
+    } else if val.is_infinite() {
+        wr.write_str(if val > 0.0 { "Infinity" } else { "-Infinity" })
+    } else {
+        // Prints the shortest representation that parses back exactly, with a decimal point or
+        // an exponent.
+        write!(wr, "{:?}", val)
+    }
+}
+
+fn write_f64<W: Write>(wr: &mut W, val: f64) -> fmt::Result {
+    if val.is_nan() {
+        if val.to_bits() == f64::NAN.to_bits() {
+            wr.write_str("NaN")
+        } else {
+            write!(wr, "NaN(0x{:016x})", val.to_bits())
+        }
+    } else if val.is_infinite() {
+        wr.write_str(if val > 0.0 { "Infinity" } else { "-Infinity" })
+    } else {
+        write!(wr, "{:?}", val)
+    }
+}
+
+/// Prints the value in the compact notation, on a single line.
+#[inline]
+pub fn to_string(val: &Value) -> String {
+    Printer::new().print(val)
+}
+
+/// Prints the value in the pretty notation, with an indentation of two spaces.
+#[inline]
+pub fn to_string_pretty(val: &Value) -> String {
+    Printer::new().with_indent(2).print(val)
+}
+
+/// An error parsing the diagnostic notation, with the position where it occurred.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    msg: &'static str,
+    line: usize,
+    column: usize,
+}
+
+impl ParseError {
+    /// Returns the line of the error, starting at 1.
+    #[inline]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column of the error in characters, starting at 1.
+    #[inline]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Display for ParseError {
+    #[cold]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(fmt, "{} at line {} column {}", self.msg, self.line, self.column)
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Parses a value in the diagnostic notation, compact or pretty.
+///
+/// # Errors
+///
+/// Returns a [`ParseError`] if the text isn't a single valid value, if it contains a value that
+/// was truncated by the printer, or if Arrays and Maps are nested deeper than
+/// [`MAX_DEPTH`](crate::decode::MAX_DEPTH).
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{diag, Value};
+///
+/// let val = diag::from_str("[f32(1.5), 1.5, ext(1, h'ff')]").unwrap();
+/// assert_eq!(Value::Array(vec![
+///     Value::F32(1.5),
+///     Value::F64(1.5),
+///     Value::Ext(1, vec![0xff]),
+/// ]), val);
+///
+/// let err = diag::from_str("[1,\n 2 3]").unwrap_err();
+/// assert_eq!("expected `,` or `]` at line 2 column 4", err.to_string());
+/// ```
+pub fn from_str(s: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser { input: s, pos: 0, depth: 0 };
+    let val = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.pos < s.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(val)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &'static str) -> ParseError {
+        let consumed = &self.input[..self.pos];
+        let line_start = consumed.rfind('\n').map_or(0, |idx| idx + 1);
+        ParseError {
+            msg,
+            line: consumed.matches('\n').count() + 1,
+            column: consumed[line_start..].chars().count() + 1,
+        }
+    }
+
+    #[inline]
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Consumes the given character after optional whitespace, or fails with the given message.
+    fn expect(&mut self, c: u8, msg: &'static str) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(msg))
+        }
+    }
+
+    /// Consumes characters while the predicate holds and returns them.
+    fn scan(&mut self, pred: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&pred) {
+            self.pos += 1;
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn check_truncated(&self) -> Result<(), ParseError> {
+        if self.rest().starts_with("...") {
+            Err(self.error("truncated value"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_map(),
+            Some(b'"') => Ok(Value::String(Utf8String::from(self.parse_str()?))),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(c) if c.is_ascii_alphabetic() => self.parse_word(),
+            Some(..) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("depth limit exceeded"));
+        }
+        self.depth += 1;
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_array(&mut self) -> Result<Value, ParseError> {
+        self.enter()?;
+        let mut vec = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b']') {
+                break;
+            }
+            vec.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => break,
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+        self.pos += 1;
+        self.depth -= 1;
+        Ok(Value::Array(vec))
+    }
+
+    fn parse_map(&mut self) -> Result<Value, ParseError> {
+        self.enter()?;
+        let mut vec = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b'}') {
+                break;
+            }
+            let key = self.parse_value()?;
+            self.expect(b':', "expected `:`")?;
+            vec.push((key, self.parse_value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => break,
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+        self.pos += 1;
+        self.depth -= 1;
+        Ok(Value::Map(vec))
+    }
+
+    fn parse_str(&mut self) -> Result<String, ParseError> {
+        self.pos += 1;
+        let mut buf = String::new();
+        loop {
+            let c = match self.rest().chars().next() {
+                Some(c) => c,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => break,
+                '\\' => buf.push(self.parse_escape()?),
+                c => buf.push(c),
+            }
+        }
+        self.check_truncated()?;
+        Ok(buf)
+    }
+
+    fn parse_escape(&mut self) -> Result<char, ParseError> {
+        let c = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.pos += 1;
+                self.expect(b'{', "expected `{` in unicode escape")?;
+                let digits = self.scan(|c| c.is_ascii_hexdigit());
+                let c = u32::from_str_radix(digits, 16).ok().and_then(char::from_u32);
+                return match c {
+                    Some(c) => {
+                        self.expect(b'}', "expected `}` in unicode escape")?;
+                        Ok(c)
+                    }
+                    None => Err(self.error("invalid unicode escape")),
+                };
+            }
+            _ => return Err(self.error("invalid escape")),
+        };
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn parse_bytes(&mut self) -> Result<Vec<u8>, ParseError> {
+        self.skip_whitespace();
+        if !self.rest().starts_with("h'") {
+            return Err(self.error("expected bytes"));
+        }
+        self.pos += 2;
+
+        let mut buf = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b'\'') {
+                break;
+            }
+            let digits = self.rest().get(..2).filter(|d| d.bytes().all(|c| c.is_ascii_hexdigit()));
+            match digits {
+                Some(digits) => {
+                    buf.push(u8::from_str_radix(digits, 16).expect("hex digits should parse"));
+                    self.pos += 2;
+                }
+                None => return Err(self.error("expected a pair of hex digits")),
+            }
+        }
+        self.pos += 1;
+        self.check_truncated()?;
+        Ok(buf)
+    }
+
+    fn parse_number(&mut self) -> Result<Value, ParseError> {
+        if self.rest().starts_with("-Infinity") {
+            self.pos += "-Infinity".len();
+            return Ok(Value::F64(f64::NEG_INFINITY));
+        }
+
+        let start = self.pos;
+        let token = self.scan_number();
+        if token.contains(['.', 'e', 'E']) {
+            return match token.parse::<f64>() {
+                // Infinities are only written as words, so this literal was too large.
+                Ok(val) if val.is_infinite() => Err(self.error_at(start, "float out of range")),
+                Ok(val) => Ok(Value::F64(val)),
+                Err(..) => Err(self.error_at(start, "invalid number")),
+            };
+        }
+
+        let val = if token.starts_with('-') {
+            token.parse::<i64>().map(Value::from)
+        } else {
+            token.parse::<u64>().map(Value::from)
+        };
+        val.map_err(|_| {
+            let digits = token.strip_prefix('-').unwrap_or(token);
+            if !digits.is_empty() && digits.bytes().all(|c| c.is_ascii_digit()) {
+                self.error_at(start, "integer out of range")
+            } else {
+                self.error_at(start, "invalid number")
+            }
+        })
+    }
+
+    fn scan_number(&mut self) -> &'a str {
+        self.scan(|c| matches!(c, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'))
+    }
+
+    fn error_at(&self, pos: usize, msg: &'static str) -> ParseError {
+        Parser { input: self.input, pos, depth: self.depth }.error(msg)
+    }
+
+    fn parse_word(&mut self) -> Result<Value, ParseError> {
+        let start = self.pos;
+        let word = self.scan(|c| c.is_ascii_alphanumeric() || c == b'_');
+        let val = match word {
+            "nil" => Value::Nil,
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            "Infinity" => Value::F64(f64::INFINITY),
+            "NaN" => match self.parse_nan_bits()? {
+                Some(bits) if f64::from_bits(bits).is_nan() => Value::F64(f64::from_bits(bits)),
+                Some(..) => return Err(self.error_at(start, "NaN bits don't encode a NaN")),
+                None => Value::F64(f64::NAN),
+            },
+            "h" if self.peek() == Some(b'\'') => {
+                self.pos = start;
+                Value::Binary(self.parse_bytes()?)
+            }
+            "f32" => {
+                self.expect(b'(', "expected `(`")?;
+                let val = self.parse_f32()?;
+                self.expect(b')', "expected `)`")?;
+                Value::F32(val)
+            }
+            "str" => {
+                self.expect(b'(', "expected `(`")?;
+                let buf = self.parse_bytes()?;
+                self.expect(b')', "expected `)`")?;
+                let s = match String::from_utf8(buf) {
+                    Ok(s) => Utf8String::from(s),
+                    Err(err) => {
+                        let e = err.utf8_error();
+                        Utf8String { s: Err((err.into_bytes(), e)) }
+                    }
+                };
+                Value::String(s)
+            }
+            "ext" => {
+                self.expect(b'(', "expected `(`")?;
+                self.skip_whitespace();
+                let pos = self.pos;
+                let ty = self.scan_number().parse::<i8>()
+                    .map_err(|_| self.error_at(pos, "invalid ext type"))?;
+                self.expect(b',', "expected `,`")?;
+                let data = self.parse_bytes()?;
+                self.expect(b')', "expected `)`")?;
+                Value::Ext(ty, data)
+            }
+            _ => return Err(self.error_at(start, "expected a value")),
+        };
+        Ok(val)
+    }
+
+    fn parse_f32(&mut self) -> Result<f32, ParseError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.rest().starts_with("-Infinity") {
+            self.pos += "-Infinity".len();
+            return Ok(f32::NEG_INFINITY);
+        }
+
+        match self.scan(|c| c.is_ascii_alphabetic()) {
+            "Infinity" => return Ok(f32::INFINITY),
+            "NaN" => {
+                let bits = match self.parse_nan_bits()? {
+                    Some(bits) => u32::try_from(bits).ok(),
+                    None => return Ok(f32::NAN),
+                };
+                return match bits.map(f32::from_bits) {
+                    Some(val) if val.is_nan() => Ok(val),
+                    _ => Err(self.error_at(start, "NaN bits don't encode a NaN")),
+                };
+            }
+            "" => {}
+            _ => return Err(self.error_at(start, "expected a float")),
+        }
+
+        match self.scan_number().parse::<f32>() {
+            Ok(val) if val.is_infinite() => Err(self.error_at(start, "float out of range")),
+            Ok(val) => Ok(val),
+            Err(..) => Err(self.error_at(start, "expected a float")),
+        }
+    }
+
+    /// Parses the optional `(0x...)` suffix of a NaN.
+    fn parse_nan_bits(&mut self) -> Result<Option<u64>, ParseError> {
+        if self.peek() != Some(b'(') {
+            return Ok(None);
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+        if !self.rest().starts_with("0x") {
+            return Err(self.error("expected `0x`"));
+        }
+        self.pos += 2;
+        let start = self.pos;
+        let bits = u64::from_str_radix(self.scan(|c| c.is_ascii_hexdigit()), 16)
+            .map_err(|_| self.error_at(start, "invalid NaN bits"))?;
+        self.expect(b')', "expected `)`")?;
+        Ok(Some(bits))
+    }
+}
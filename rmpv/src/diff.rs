@@ -0,0 +1,314 @@
+//! Structural diffs between values and patches that apply them.
+
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::Value;
+
+/// A step of the path to a value nested in Arrays and Maps.
+///
+/// As MessagePack map keys can be of any type, a Map entry is selected by a key `Value` rather
+/// than a string, and the first entry with an equal key is used.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// Selects the entry of a Map with the enclosed key.
+    Key(Value),
+    /// Selects the element of an Array at the enclosed position.
+    Index(usize),
+}
+
+impl From<Value> for PathSegment {
+    #[inline]
+    fn from(key: Value) -> Self {
+        PathSegment::Key(key)
+    }
+}
+
+impl<'a> From<&'a str> for PathSegment {
+    #[inline]
+    fn from(key: &'a str) -> Self {
+        PathSegment::Key(Value::from(key))
+    }
+}
+
+impl From<usize> for PathSegment {
+    #[inline]
+    fn from(idx: usize) -> Self {
+        PathSegment::Index(idx)
+    }
+}
+
+/// A single change of a patch, which addresses the value it changes by a path.
+///
+/// An empty path addresses the root value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    /// Inserts the value into an Array at the position the last segment gives, shifting the
+    /// elements after it, or inserts it into a Map under the key the last segment gives,
+    /// replacing the value of an existing entry with that key.
+    Add { path: Vec<PathSegment>, value: Value },
+    /// Removes the Array element or Map entry at the path. Removing the root leaves Nil.
+    Remove { path: Vec<PathSegment> },
+    /// Replaces the existing value at the path.
+    Replace { path: Vec<PathSegment>, value: Value },
+}
+
+/// An error applying a patch, which carries the path of the value that couldn't be changed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchError {
+    /// The path addresses an Array element or a Map entry that doesn't exist.
+    NotFound(Vec<PathSegment>),
+    /// The path steps into a value with a segment of the wrong kind, such as an `Index` into a
+    /// Map, or into a value that is neither an Array nor a Map.
+    TypeMismatch(Vec<PathSegment>),
+}
+
+impl Display for PatchError {
+    #[cold]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let (msg, path) = match *self {
+            PatchError::NotFound(ref path) => ("no value at path", path),
+            PatchError::TypeMismatch(ref path) => ("type mismatch at path", path),
+        };
+        fmt.write_str(msg)?;
+        fmt.write_str(" ")?;
+        if path.is_empty() {
+            return fmt.write_str("[]");
+        }
+        for segment in path {
+            match *segment {
+                PathSegment::Key(ref key) => write!(fmt, "[{}]", key)?,
+                PathSegment::Index(idx) => write!(fmt, "[{}]", idx)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for PatchError {}
+
+/// Computes the operations that turn `a` into `b`, such that applying them to `a` with
+/// [`apply_patch`] results in a value equal to `b`.
+///
+/// Arrays are compared element by element, followed by the removal of trailing elements or the
+/// addition of new ones. Maps are compared entry by entry, matching entries by their keys. A Map
+/// that has duplicate keys, or whose entries changed their order, is replaced as a whole, as is
+/// any other value that differs.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{apply_patch, diff, msgpack, Operation, PathSegment};
+///
+/// let a = msgpack!({"name": "server", "ports": [80], 1: true});
+/// let b = msgpack!({"name": "server", "ports": [80, 443]});
+///
+/// let patch = diff(&a, &b);
+/// assert_eq!(vec![
+///     Operation::Add {
+///         path: vec![PathSegment::from("ports"), PathSegment::Index(1)],
+///         value: msgpack!(443),
+///     },
+///     Operation::Remove { path: vec![PathSegment::Key(msgpack!(1))] },
+/// ], patch);
+///
+/// let mut val = a.clone();
+/// apply_patch(&mut val, &patch).unwrap();
+/// assert_eq!(b, val);
+/// ```
+pub fn diff(a: &Value, b: &Value) -> Vec<Operation> {
+    let mut ops = Vec::new();
+    diff_into(&mut ops, &mut Vec::new(), a, b);
+    ops
+}
+
+fn diff_into(ops: &mut Vec<Operation>, path: &mut Vec<PathSegment>, a: &Value, b: &Value) {
+    if a == b {
+        return;
+    }
+
+    match (a, b) {
+        (Value::Array(a), Value::Array(b)) => {
+            for (idx, (a, b)) in a.iter().zip(b).enumerate() {
+                path.push(PathSegment::Index(idx));
+                diff_into(ops, path, a, b);
+                path.pop();
+            }
+            // Remove from the back, so that the positions of the remaining removals stay valid.
+            for idx in (b.len()..a.len()).rev() {
+                ops.push(Operation::Remove { path: with_segment(path, PathSegment::Index(idx)) });
+            }
+            for (idx, val) in b.iter().enumerate().skip(a.len()) {
+                ops.push(Operation::Add {
+                    path: with_segment(path, PathSegment::Index(idx)),
+                    value: val.clone(),
+                });
+            }
+        }
+        (Value::Map(a), Value::Map(b)) if is_map_diffable(a, b) => {
+            let a_keys: HashSet<&Value> = a.iter().map(|(k, _)| k).collect();
+            let b_index: HashMap<&Value, &Value> = b.iter().map(|(k, v)| (k, v)).collect();
+
+            for (key, val) in a {
+                match b_index.get(key) {
+                    Some(other) => {
+                        path.push(PathSegment::Key(key.clone()));
+                        diff_into(ops, path, val, other);
+                        path.pop();
+                    }
+                    None => {
+                        let path = with_segment(path, PathSegment::Key(key.clone()));
+                        ops.push(Operation::Remove { path });
+                    }
+                }
+            }
+            for (key, val) in b {
+                if !a_keys.contains(key) {
+                    ops.push(Operation::Add {
+                        path: with_segment(path, PathSegment::Key(key.clone())),
+                        value: val.clone(),
+                    });
+                }
+            }
+        }
+        _ => {
+            ops.push(Operation::Replace { path: path.clone(), value: b.clone() });
+        }
+    }
+}
+
+/// Returns whether Map `a` turns into `b` by changing, removing and appending entries by key,
+/// which requires unique keys, the kept entries in the same order and the new ones at the end.
+fn is_map_diffable(a: &[(Value, Value)], b: &[(Value, Value)]) -> bool {
+    let a_keys: HashSet<&Value> = a.iter().map(|(k, _)| k).collect();
+    let b_keys: HashSet<&Value> = b.iter().map(|(k, _)| k).collect();
+    if a_keys.len() != a.len() || b_keys.len() != b.len() {
+        return false;
+    }
+
+    let kept = a.iter().map(|(k, _)| k).filter(|k| b_keys.contains(k));
+    let mut b_iter = b.iter().map(|(k, _)| k);
+    let in_order = kept.zip(b_iter.by_ref()).all(|(a, b)| a == b);
+
+    in_order && b_iter.all(|k| !a_keys.contains(k))
+}
+
+fn with_segment(path: &[PathSegment], segment: PathSegment) -> Vec<PathSegment> {
+    let mut path = path.to_vec();
+    path.push(segment);
+    path
+}
+
+/// Applies the operations of a patch in order.
+///
+/// The patch is applied atomically: if an operation fails, the value is left unchanged.
+///
+/// # Errors
+///
+/// Returns [`PatchError::NotFound`] if an operation addresses a value that doesn't exist, and
+/// [`PatchError::TypeMismatch`] if a path segment doesn't fit the value it steps into.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{apply_patch, msgpack, Operation, PatchError, PathSegment};
+///
+/// let mut val = msgpack!({"a": [1, 2]});
+///
+/// let patch = vec![Operation::Replace {
+///     path: vec![PathSegment::from("a"), PathSegment::Index(0)],
+///     value: msgpack!("one"),
+/// }];
+/// apply_patch(&mut val, &patch).unwrap();
+/// assert_eq!(msgpack!({"a": ["one", 2]}), val);
+///
+/// let path = vec![PathSegment::from("b")];
+/// let patch = vec![Operation::Remove { path: path.clone() }];
+/// assert_eq!(Err(PatchError::NotFound(path)), apply_patch(&mut val, &patch));
+/// assert_eq!(msgpack!({"a": ["one", 2]}), val);
+/// ```
+pub fn apply_patch(val: &mut Value, patch: &[Operation]) -> Result<(), PatchError> {
+    let mut patched = val.clone();
+    for op in patch {
+        apply_operation(&mut patched, op)?;
+    }
+    *val = patched;
+    Ok(())
+}
+
+fn apply_operation(root: &mut Value, op: &Operation) -> Result<(), PatchError> {
+    let path = match *op {
+        Operation::Add { ref path, .. } |
+        Operation::Remove { ref path } |
+        Operation::Replace { ref path, .. } => path,
+    };
+
+    let (last, parent_path) = match path.split_last() {
+        Some(split) => split,
+        None => {
+            *root = match *op {
+                Operation::Add { ref value, .. } |
+                Operation::Replace { ref value, .. } => value.clone(),
+                Operation::Remove { .. } => Value::Nil,
+            };
+            return Ok(());
+        }
+    };
+
+    let mut parent = root;
+    for (idx, segment) in parent_path.iter().enumerate() {
+        let pos = position(parent, segment, &path[..=idx])?;
+        parent = parent.child_mut(pos);
+    }
+
+    match *op {
+        Operation::Add { ref value, .. } => match (parent, last) {
+            (Value::Array(array), &PathSegment::Index(idx)) => {
+                if idx > array.len() {
+                    return Err(PatchError::NotFound(path.clone()));
+                }
+                array.insert(idx, value.clone());
+            }
+            (Value::Map(map), PathSegment::Key(key)) => {
+                match map.iter_mut().find(|(k, _)| k == key) {
+                    Some(entry) => entry.1 = value.clone(),
+                    None => map.push((key.clone(), value.clone())),
+                }
+            }
+            _ => return Err(PatchError::TypeMismatch(path.clone())),
+        },
+        Operation::Remove { .. } => {
+            let pos = position(parent, last, path)?;
+            match *parent {
+                Value::Array(ref mut array) => {
+                    array.remove(pos);
+                }
+                Value::Map(ref mut map) => {
+                    map.remove(pos);
+                }
+                _ => unreachable!("only arrays and maps have children"),
+            }
+        }
+        Operation::Replace { ref value, .. } => {
+            let pos = position(parent, last, path)?;
+            *parent.child_mut(pos) = value.clone();
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the position of the Array element or Map entry a segment selects, reporting errors
+/// with the path up to and including it.
+fn position(val: &Value, segment: &PathSegment, path: &[PathSegment]) -> Result<usize, PatchError> {
+    match (val, segment) {
+        (Value::Array(array), &PathSegment::Index(idx)) if idx < array.len() => Ok(idx),
+        (Value::Map(map), PathSegment::Key(key)) => match map.iter().position(|(k, _)| k == key) {
+            Some(pos) => Ok(pos),
+            None => Err(PatchError::NotFound(path.to_vec())),
+        },
+        (Value::Array(..), &PathSegment::Index(..)) => Err(PatchError::NotFound(path.to_vec())),
+        _ => Err(PatchError::TypeMismatch(path.to_vec())),
+    }
+}
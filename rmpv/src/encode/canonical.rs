@@ -0,0 +1,85 @@
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::io::Write;
+
+use super::value::write_value_with;
+use super::Error;
+use crate::Value;
+
+/// An error of [`write_value_canonical`].
+#[derive(Debug)]
+pub enum CanonicalError {
+    /// Error while writing the encoded value.
+    Write(Error),
+    /// A Map has more than one entry with the enclosed key.
+    DuplicateKey(Value),
+}
+
+impl From<Error> for CanonicalError {
+    #[cold]
+    fn from(err: Error) -> Self {
+        CanonicalError::Write(err)
+    }
+}
+
+impl Display for CanonicalError {
+    #[cold]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            CanonicalError::Write(ref err) => Display::fmt(err, fmt),
+            CanonicalError::DuplicateKey(ref key) => write!(fmt, "duplicate map key {}", key),
+        }
+    }
+}
+
+impl error::Error for CanonicalError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            CanonicalError::Write(ref err) => Some(err),
+            CanonicalError::DuplicateKey(..) => None,
+        }
+    }
+}
+
+/// Encodes and writes the canonical representation of the given Value, such that values that
+/// are equal after [`Value::canonicalize`] are encoded into the same bytes.
+///
+/// This is the most efficient representation that `write_value` writes, with these differences:
+///
+/// - Map entries are sorted by the canonical encoding of their keys, compared bytewise.
+/// - Every NaN is written with the bits of `f32::NAN` or `f64::NAN`.
+/// - A String with invalid UTF-8 is written as a String rather than as Binary, so that it
+///   doesn't collide with the Binary of the same bytes.
+///
+/// The value itself is left unchanged, and the types of numbers are kept: an F32 is not narrowed
+/// or widened, and an Integer is not written as a float or vice versa.
+///
+/// # Errors
+///
+/// Returns [`CanonicalError::DuplicateKey`] if a Map has more than one entry whose key encodes
+/// into the same bytes, as their order would be undefined.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::encode::{write_value, write_value_canonical};
+/// use rmpv::msgpack;
+///
+/// let a = msgpack!({"b": 1, "a": 2});
+/// let b = msgpack!({"a": 2, "b": 1});
+///
+/// let mut buf_a = Vec::new();
+/// let mut buf_b = Vec::new();
+/// write_value_canonical(&mut buf_a, &a).unwrap();
+/// write_value_canonical(&mut buf_b, &b).unwrap();
+/// assert_eq!(buf_a, buf_b);
+///
+/// let mut buf = Vec::new();
+/// write_value(&mut buf, &b).unwrap();
+/// assert_eq!(buf, buf_a);
+/// ```
+pub fn write_value_canonical<W>(wr: &mut W, val: &Value) -> Result<(), CanonicalError>
+    where W: Write
+{
+    write_value_with(wr, val, true)
+}
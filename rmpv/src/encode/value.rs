@@ -2,10 +2,10 @@ use std::io::Write;
 
 use rmp::encode::{
     write_array_len, write_bin, write_bool, write_ext_meta, write_f32, write_f64, write_map_len,
-    write_nil, write_sint, write_str, write_uint,
+    write_nil, write_sint, write_str, write_str_len, write_uint,
 };
 
-use super::Error;
+use super::{CanonicalError, Error};
 use crate::{IntPriv, Integer, Utf8String, Value};
 
 /// Encodes and attempts to write the most efficient representation of the given Value.
@@ -16,6 +16,18 @@ use crate::{IntPriv, Integer, Utf8String, Value};
 /// operation is retried.
 pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
     where W: Write
+{
+    match write_value_with(wr, val, false) {
+        Ok(()) => Ok(()),
+        Err(CanonicalError::Write(err)) => Err(err),
+        Err(CanonicalError::DuplicateKey(..)) => unreachable!("keys are only compared when canonical"),
+    }
+}
+
+/// Encodes and writes the given Value, in the canonical representation that
+/// [`write_value_canonical`](super::write_value_canonical) describes if `canonical` is set.
+pub(super) fn write_value_with<W>(wr: &mut W, val: &Value, canonical: bool) -> Result<(), CanonicalError>
+    where W: Write
 {
     match *val {
         Value::Nil => {
@@ -35,14 +47,18 @@ pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
             }
         }
         Value::F32(val) => {
-            write_f32(wr, val)?;
+            write_f32(wr, if canonical && val.is_nan() { f32::NAN } else { val })?;
         }
         Value::F64(val) => {
-            write_f64(wr, val)?;
+            write_f64(wr, if canonical && val.is_nan() { f64::NAN } else { val })?;
         }
         Value::String(Utf8String { ref s }) => {
             match *s {
                 Ok(ref val) => write_str(wr, val)?,
+                Err(ref err) if canonical => {
+                    write_str_len(wr, err.0.len() as u32)?;
+                    wr.write_all(&err.0).map_err(Error::InvalidDataWrite)?;
+                }
                 Err(ref err) => write_bin(wr, &err.0)?,
             }
         }
@@ -52,14 +68,32 @@ pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
         Value::Array(ref vec) => {
             write_array_len(wr, vec.len() as u32)?;
             for v in vec {
-                write_value(wr, v)?;
+                write_value_with(wr, v, canonical)?;
+            }
+        }
+        Value::Map(ref map) if canonical => {
+            let mut entries = Vec::with_capacity(map.len());
+            for (key, val) in map {
+                let mut buf = Vec::new();
+                write_value_with(&mut buf, key, true)?;
+                entries.push((buf, key, val));
+            }
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            if let Some(dup) = entries.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+                return Err(CanonicalError::DuplicateKey(dup[0].1.clone()));
+            }
+
+            write_map_len(wr, map.len() as u32)?;
+            for (buf, _, val) in entries {
+                wr.write_all(&buf).map_err(Error::InvalidDataWrite)?;
+                write_value_with(wr, val, true)?;
             }
         }
         Value::Map(ref map) => {
             write_map_len(wr, map.len() as u32)?;
             for (key, val) in map {
-                write_value(wr, key)?;
-                write_value(wr, val)?;
+                write_value_with(wr, key, false)?;
+                write_value_with(wr, val, false)?;
             }
         }
         Value::Ext(ty, ref data) => {
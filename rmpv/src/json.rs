@@ -0,0 +1,188 @@
+//! Conversions between `Value` and `serde_json::Value`.
+//!
+//! Almost every JSON value has a MessagePack counterpart, so `Value::try_from(serde_json::Value)`
+//! fails only with a [`FromJsonError`] for a number that no MessagePack number can hold:
+//!
+//! - null, booleans, strings and arrays map to Nil, Boolean, String and Array.
+//! - Numbers that fit into an `u64` or `i64` become an Integer, and other numbers an F64.
+//!   With the `arbitrary_precision` feature of `serde_json` a number may be too large for an
+//!   `f64`, such as `1e400`, which is rejected rather than turned into an infinity.
+//! - Objects become a Map with String keys, in the order the object iterates them.
+//!
+//! The other way around, `serde_json::Value::try_from(Value)` fails with a [`ToJsonError`] rather
+//! than silently changing data that JSON can't represent:
+//!
+//! - Map keys must be valid UTF-8 Strings, as JSON object keys are strings. Integer or other keys
+//!   are not turned into strings, as that could merge distinct keys such as `1` and `"1"`. A key
+//!   must not occur more than once, as a JSON object keeps only one of the entries.
+//! - Binary becomes an array of byte numbers, the way `serde_json` serializes bytes. It is not
+//!   encoded as base64, as that would be indistinguishable from a String. Converting back
+//!   results in an Array of Integers.
+//! - Ext has no JSON equivalent and is rejected.
+//! - F32 and F64 must be finite, as JSON has no NaN or infinity. An F32 is widened to `f64`.
+//! - Strings must be valid UTF-8.
+//!
+//! # Type inference
+//!
+//! Enabling the `with-serde-json` feature links `serde_json`, whose `PartialEq` impls between
+//! its `Value` and the primitive types apply to every crate in the build. Comparing a primitive
+//! against the result of a generic function, such as
+//! `assert_eq!(0u8, rmpv::ext::from_value(val).unwrap())`, then becomes ambiguous and needs the
+//! type spelled out, as in `from_value::<u8>`. The same holds for `Value::try_into` compared
+//! against a primitive, as `Value` now also converts into `serde_json::Value`.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::convert::TryFrom;
+//! use rmpv::Value;
+//! use rmpv::json::ToJsonError;
+//!
+//! let val = Value::Map(vec![
+//!     (Value::from("id"), Value::from(42)),
+//!     (Value::from("raw"), Value::Binary(vec![1, 2])),
+//! ]);
+//!
+//! let json = serde_json::Value::try_from(val).unwrap();
+//! assert_eq!(r#"{"id":42,"raw":[1,2]}"#, json.to_string());
+//!
+//! let val = Value::Map(vec![(Value::from(1), Value::Nil)]);
+//! assert_eq!(Err(ToJsonError::NonStringKey(Value::from(1))), serde_json::Value::try_from(val));
+//! ```
+
+use std::convert::TryFrom;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+use serde_json::{Map, Number};
+
+use crate::{IntPriv, Utf8String, Value};
+
+/// An error converting a `serde_json::Value` into a `Value`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FromJsonError {
+    /// The enclosed number is out of range of `u64`, `i64` and `f64`, which only happens with the
+    /// `arbitrary_precision` feature of `serde_json`.
+    NumberOutOfRange(Number),
+}
+
+impl Display for FromJsonError {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            FromJsonError::NumberOutOfRange(ref n) => write!(fmt, "number {} is out of range", n),
+        }
+    }
+}
+
+impl error::Error for FromJsonError {}
+
+/// An error converting a `Value` into a `serde_json::Value`, which names the part that JSON
+/// can't represent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ToJsonError {
+    /// A map had the enclosed key, which is not a valid UTF-8 String.
+    NonStringKey(Value),
+    /// A map had the enclosed key more than once.
+    DuplicateKey(Value),
+    /// The enclosed Ext type and data.
+    Ext(i8, Vec<u8>),
+    /// The enclosed float is NaN or infinite.
+    NonFiniteFloat(f64),
+    /// A String had the enclosed bytes, which are not valid UTF-8.
+    InvalidUtf8(Vec<u8>),
+}
+
+impl Display for ToJsonError {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            ToJsonError::NonStringKey(ref key) => write!(fmt, "map key {} is not a string", key),
+            ToJsonError::DuplicateKey(ref key) => write!(fmt, "map key {} occurs more than once", key),
+            ToJsonError::Ext(ty, ..) => write!(fmt, "ext type {} has no JSON equivalent", ty),
+            ToJsonError::NonFiniteFloat(val) => write!(fmt, "float {} is not finite", val),
+            ToJsonError::InvalidUtf8(..) => fmt.write_str("string is not valid UTF-8"),
+        }
+    }
+}
+
+impl error::Error for ToJsonError {}
+
+impl TryFrom<serde_json::Value> for Value {
+    type Error = FromJsonError;
+
+    fn try_from(val: serde_json::Value) -> Result<Self, Self::Error> {
+        Ok(match val {
+            serde_json::Value::Null => Value::Nil,
+            serde_json::Value::Bool(val) => Value::Boolean(val),
+            serde_json::Value::Number(n) => {
+                if let Some(n) = n.as_u64() {
+                    Value::from(n)
+                } else if let Some(n) = n.as_i64() {
+                    Value::from(n)
+                } else if let Some(val) = n.as_f64().filter(|val| val.is_finite()) {
+                    Value::F64(val)
+                } else {
+                    return Err(FromJsonError::NumberOutOfRange(n));
+                }
+            }
+            serde_json::Value::String(val) => Value::String(val.into()),
+            serde_json::Value::Array(vals) => Value::Array(
+                vals.into_iter().map(Value::try_from).collect::<Result<_, _>>()?,
+            ),
+            serde_json::Value::Object(map) => Value::Map(
+                map.into_iter()
+                    .map(|(k, v)| Ok((Value::from(k), Value::try_from(v)?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
+
+impl TryFrom<Value> for serde_json::Value {
+    type Error = ToJsonError;
+
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        Ok(match val {
+            Value::Nil => serde_json::Value::Null,
+            Value::Boolean(val) => serde_json::Value::Bool(val),
+            Value::Integer(n) => match n.n {
+                IntPriv::PosInt(n) => serde_json::Value::Number(n.into()),
+                IntPriv::NegInt(n) => serde_json::Value::Number(n.into()),
+            },
+            Value::F32(val) => float_to_json(val.into())?,
+            Value::F64(val) => float_to_json(val)?,
+            Value::String(val) => serde_json::Value::String(string_to_json(val)?),
+            Value::Binary(val) => {
+                serde_json::Value::Array(val.into_iter().map(serde_json::Value::from).collect())
+            }
+            Value::Array(vals) => serde_json::Value::Array(
+                vals.into_iter().map(serde_json::Value::try_from).collect::<Result<_, _>>()?,
+            ),
+            Value::Map(entries) => {
+                let mut map = Map::new();
+                for (key, val) in entries {
+                    let key = match key {
+                        Value::String(Utf8String { s: Ok(key) }) => key,
+                        key => return Err(ToJsonError::NonStringKey(key)),
+                    };
+                    if map.contains_key(&key) {
+                        return Err(ToJsonError::DuplicateKey(Value::from(key)));
+                    }
+                    map.insert(key, serde_json::Value::try_from(val)?);
+                }
+                serde_json::Value::Object(map)
+            }
+            Value::Ext(ty, data) => return Err(ToJsonError::Ext(ty, data)),
+        })
+    }
+}
+
+fn float_to_json(val: f64) -> Result<serde_json::Value, ToJsonError> {
+    match Number::from_f64(val) {
+        Some(n) => Ok(serde_json::Value::Number(n)),
+        None => Err(ToJsonError::NonFiniteFloat(val)),
+    }
+}
+
+fn string_to_json(val: Utf8String) -> Result<String, ToJsonError> {
+    val.s.map_err(|(buf, _)| ToJsonError::InvalidUtf8(buf))
+}
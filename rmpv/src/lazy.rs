@@ -0,0 +1,319 @@
+//! A lazily decoded view of an encoded value.
+
+use std::cell::OnceCell;
+use std::convert::TryFrom;
+use std::io::{self, ErrorKind};
+
+use rmp::decode::{read_marker, RmpRead};
+use rmp::Marker;
+
+use crate::decode::{read_value_ref_with_max_depth, Error, MAX_DEPTH};
+use crate::ValueRef;
+
+/// A view of an encoded value that decodes only the parts that are accessed.
+///
+/// Creating a `LazyValue` skips over the bytes once, checking that they hold a well-formed value
+/// and finding where it ends. This reads every header, so it takes time proportional to the size
+/// of the value, but it neither allocates nor decodes anything. When an Array or a Map is accessed
+/// for the first time, its elements or entries are skipped over again to find where each of them
+/// starts, and memoized, so that repeated lookups don't scan them again. Reaching a nested value
+/// thus scans each container on the way to it once. Scalars are decoded on every access,
+/// borrowing strings and binaries from the bytes.
+///
+/// Use [`to_value_ref`](LazyValue::to_value_ref) to decode a part completely.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::encode::write_value;
+/// use rmpv::{msgpack, LazyValue};
+///
+/// let doc = msgpack!({"id": 42, "tags": ["a", "b"], "blob": bin(vec![0; 1000])});
+/// let mut buf = Vec::new();
+/// write_value(&mut buf, &doc).unwrap();
+///
+/// let val = LazyValue::new(&buf).unwrap();
+///
+/// assert_eq!(Some(3), val.len());
+/// assert_eq!(Some(42), val.get("id").and_then(|v| v.as_u64()));
+/// assert_eq!(Some("b"), val.get("tags").and_then(|v| v.index(1)).and_then(|v| v.as_str()));
+/// assert!(val.get("missing").is_none());
+///
+/// let tags: Vec<_> = val.get("tags").unwrap().as_array().unwrap()
+///     .iter()
+///     .filter_map(|v| v.as_str())
+///     .collect();
+/// assert_eq!(vec!["a", "b"], tags);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LazyValue<'a> {
+    /// The encoded value, without trailing bytes.
+    buf: &'a [u8],
+    /// The elements of an Array or the entries of a Map, once they are accessed.
+    children: OnceCell<Children<'a>>,
+}
+
+#[derive(Clone, Debug)]
+enum Children<'a> {
+    Array(Vec<LazyValue<'a>>),
+    Map(Vec<(LazyValue<'a>, LazyValue<'a>)>),
+}
+
+/// The part of an encoded value after its header.
+enum Layout {
+    /// The given number of data bytes.
+    Data(usize),
+    /// The given number of Array elements.
+    Array(usize),
+    /// The given number of Map entries.
+    Map(usize),
+}
+
+impl<'a> LazyValue<'a> {
+    /// Creates a view of the value at the start of the given bytes. Trailing bytes are ignored,
+    /// and [`as_bytes`](LazyValue::as_bytes) tells where the value ends.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes end before the value does, or if Arrays and Maps are nested
+    /// deeper than [`MAX_DEPTH`](crate::decode::MAX_DEPTH).
+    pub fn new(buf: &'a [u8]) -> Result<Self, Error> {
+        let len = skip_value(buf, 0, MAX_DEPTH)?;
+        Ok(LazyValue { buf: &buf[..len], children: OnceCell::new() })
+    }
+
+    /// Returns the encoded bytes of the value.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    #[inline]
+    fn layout(&self) -> (usize, Layout) {
+        read_layout(self.buf).expect("layout should have been checked on creation")
+    }
+
+    /// Returns true if the value is an Array.
+    #[inline]
+    pub fn is_array(&self) -> bool {
+        matches!(self.layout().1, Layout::Array(..))
+    }
+
+    /// Returns true if the value is a Map.
+    #[inline]
+    pub fn is_map(&self) -> bool {
+        matches!(self.layout().1, Layout::Map(..))
+    }
+
+    /// Returns true if the value is Nil.
+    #[inline]
+    pub fn is_nil(&self) -> bool {
+        matches!(self.scalar(), Some(ValueRef::Nil))
+    }
+
+    /// Returns the number of elements of an Array or entries of a Map, read from its header
+    /// without scanning its contents. Returns None for other values.
+    #[inline]
+    pub fn len(&self) -> Option<usize> {
+        match self.layout().1 {
+            Layout::Array(len) | Layout::Map(len) => Some(len),
+            Layout::Data(..) => None,
+        }
+    }
+
+    /// Returns true if the value is an empty Array or Map. Returns None for other values.
+    #[inline]
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Returns the elements of an Array, which can be iterated over. Returns None for other
+    /// values.
+    pub fn as_array(&self) -> Option<&[LazyValue<'a>]> {
+        match *self.children()? {
+            Children::Array(ref vec) => Some(vec),
+            Children::Map(..) => None,
+        }
+    }
+
+    /// Returns the entries of a Map, which can be iterated over. Returns None for other values.
+    pub fn as_map(&self) -> Option<&[(LazyValue<'a>, LazyValue<'a>)]> {
+        match *self.children()? {
+            Children::Map(ref vec) => Some(vec),
+            Children::Array(..) => None,
+        }
+    }
+
+    /// Returns the element of an Array at the given position, or None if the value is not an
+    /// Array or the position is out of bounds.
+    #[inline]
+    pub fn index(&self, idx: usize) -> Option<&LazyValue<'a>> {
+        self.as_array()?.get(idx)
+    }
+
+    /// Returns the value of the first Map entry with the given key, or the Array element at the
+    /// position given by an integer key, like [`Value::get`](crate::Value::get).
+    ///
+    /// Map keys are decoded to be compared, which allocates only for Array and Map keys.
+    pub fn get<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&LazyValue<'a>> {
+        let key = key.into();
+        match *self.children()? {
+            Children::Array(ref vec) => match key {
+                ValueRef::Integer(n) => vec.get(usize::try_from(n.as_u64()?).ok()?),
+                _ => None,
+            },
+            Children::Map(ref vec) => {
+                vec.iter().find(|(k, _)| k.to_value_ref() == key).map(|(_, v)| v)
+            }
+        }
+    }
+
+    /// If the value is a boolean, returns it. Returns None otherwise.
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        self.scalar()?.as_bool()
+    }
+
+    /// If the value is an integer that fits into an `i64`, returns it. Returns None otherwise.
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.scalar()?.as_i64()
+    }
+
+    /// If the value is an integer that fits into an `u64`, returns it. Returns None otherwise.
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.scalar()?.as_u64()
+    }
+
+    /// If the value is a number, returns it as an `f64`. Returns None otherwise.
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        self.scalar()?.as_f64()
+    }
+
+    /// If the value is a valid UTF-8 String, returns it, borrowed from the encoded bytes. Returns
+    /// None otherwise.
+    #[inline]
+    pub fn as_str(&self) -> Option<&'a str> {
+        self.scalar()?.as_str()
+    }
+
+    /// If the value is a Binary or a String, returns its bytes, borrowed from the encoded bytes.
+    /// Returns None otherwise.
+    #[inline]
+    pub fn as_slice(&self) -> Option<&'a [u8]> {
+        self.scalar()?.as_slice()
+    }
+
+    /// If the value is an Ext, returns its type and data, borrowed from the encoded bytes.
+    /// Returns None otherwise.
+    #[inline]
+    pub fn as_ext(&self) -> Option<(i8, &'a [u8])> {
+        self.scalar()?.as_ext()
+    }
+
+    /// Decodes the value completely, including all nested values.
+    pub fn to_value_ref(&self) -> ValueRef<'a> {
+        // The nesting depth was limited on creation, so that decoding needs no further limit.
+        read_value_ref_with_max_depth(&mut &self.buf[..], usize::MAX)
+            .expect("value should have been checked on creation")
+    }
+
+    /// Decodes the value if it is neither an Array nor a Map.
+    #[inline]
+    fn scalar(&self) -> Option<ValueRef<'a>> {
+        match self.layout().1 {
+            Layout::Data(..) => Some(self.to_value_ref()),
+            Layout::Array(..) | Layout::Map(..) => None,
+        }
+    }
+
+    /// Returns the memoized elements or entries of an Array or Map, finding them on first use.
+    fn children(&self) -> Option<&Children<'a>> {
+        let (header_len, layout) = self.layout();
+        if let Layout::Data(..) = layout {
+            return None;
+        }
+
+        Some(self.children.get_or_init(|| {
+            let mut pos = header_len;
+            let mut next = || {
+                // The depth was limited on creation, so that skipping needs no further limit.
+                let end = skip_value(self.buf, pos, usize::MAX)
+                    .expect("value should have been checked on creation");
+                let val = LazyValue { buf: &self.buf[pos..end], children: OnceCell::new() };
+                pos = end;
+                val
+            };
+            match layout {
+                Layout::Array(len) => Children::Array((0..len).map(|_| next()).collect()),
+                Layout::Map(len) => Children::Map((0..len).map(|_| (next(), next())).collect()),
+                Layout::Data(..) => unreachable!("only arrays and maps have children"),
+            }
+        }))
+    }
+}
+
+/// Reads the header of the value at the start of the bytes, returning its length and the layout
+/// of the rest.
+fn read_layout(buf: &[u8]) -> Result<(usize, Layout), Error> {
+    let mut rd = buf;
+    let layout = match read_marker(&mut rd)? {
+        Marker::Null | Marker::True | Marker::False | Marker::Reserved => Layout::Data(0),
+        Marker::FixPos(..) | Marker::FixNeg(..) => Layout::Data(0),
+        Marker::U8 | Marker::I8 => Layout::Data(1),
+        Marker::U16 | Marker::I16 => Layout::Data(2),
+        Marker::U32 | Marker::I32 | Marker::F32 => Layout::Data(4),
+        Marker::U64 | Marker::I64 | Marker::F64 => Layout::Data(8),
+        Marker::FixStr(len) => Layout::Data(len as usize),
+        Marker::Str8 | Marker::Bin8 => Layout::Data(rd.read_data_u8()? as usize),
+        Marker::Str16 | Marker::Bin16 => Layout::Data(rd.read_data_u16()? as usize),
+        Marker::Str32 | Marker::Bin32 => Layout::Data(rd.read_data_u32()? as usize),
+        Marker::FixArray(len) => Layout::Array(len as usize),
+        Marker::Array16 => Layout::Array(rd.read_data_u16()? as usize),
+        Marker::Array32 => Layout::Array(rd.read_data_u32()? as usize),
+        Marker::FixMap(len) => Layout::Map(len as usize),
+        Marker::Map16 => Layout::Map(rd.read_data_u16()? as usize),
+        Marker::Map32 => Layout::Map(rd.read_data_u32()? as usize),
+        // The data of an Ext is preceded by its type.
+        Marker::FixExt1 => Layout::Data(1 + 1),
+        Marker::FixExt2 => Layout::Data(1 + 2),
+        Marker::FixExt4 => Layout::Data(1 + 4),
+        Marker::FixExt8 => Layout::Data(1 + 8),
+        Marker::FixExt16 => Layout::Data(1 + 16),
+        Marker::Ext8 => Layout::Data(1 + rd.read_data_u8()? as usize),
+        Marker::Ext16 => Layout::Data(1 + rd.read_data_u16()? as usize),
+        Marker::Ext32 => Layout::Data(1 + rd.read_data_u32()? as usize),
+    };
+    Ok((buf.len() - rd.len(), layout))
+}
+
+/// Checks that the bytes hold a value starting at `pos`, with Arrays and Maps nested less than
+/// `depth` deep, and returns where it ends.
+fn skip_value(buf: &[u8], pos: usize, depth: usize) -> Result<usize, Error> {
+    if depth == 0 {
+        return Err(Error::DepthLimitExceeded);
+    }
+
+    let (header_len, layout) = read_layout(&buf[pos..])?;
+    let mut end = pos + header_len;
+    let (count, values) = match layout {
+        Layout::Data(data_len) => {
+            if data_len > buf.len() - end {
+                let err = io::Error::new(ErrorKind::UnexpectedEof, "unexpected EOF");
+                return Err(Error::InvalidDataRead(err));
+            }
+            return Ok(end + data_len);
+        }
+        Layout::Array(count) => (count, 1),
+        Layout::Map(count) => (count, 2),
+    };
+
+    for _ in 0..count {
+        for _ in 0..values {
+            end = skip_value(buf, end, depth - 1)?;
+        }
+    }
+    Ok(end)
+}
@@ -11,21 +11,38 @@
 extern crate serde;
 
 use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::convert::TryFrom;
 use std::fmt::{self, Debug, Display};
+use std::error;
+use std::hash::{Hash, Hasher};
+use std::io;
 use std::iter::FromIterator;
-use std::ops::Index;
+use std::mem;
+use std::ops::{Index, IndexMut};
 use std::str::Utf8Error;
 
 use num_traits::NumCast;
 
+use crate::encode::{write_value_canonical, CanonicalError};
+
 pub mod decode;
+pub mod diag;
 pub mod encode;
+mod diff;
+mod lazy;
+mod macros;
+
+pub use crate::diff::{apply_patch, diff, Operation, PatchError, PathSegment};
+pub use crate::lazy::LazyValue;
 
 #[cfg(feature = "with-serde")]
 pub mod ext;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg(feature = "with-serde-json")]
+pub mod json;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 enum IntPriv {
     /// Always non-less than zero.
     PosInt(u64),
@@ -51,7 +68,9 @@ pub const MSGPACK_EXT_STRUCT_NAME: &str = "_ExtStruct";
 /// Represents a MessagePack integer, whether signed or unsigned.
 ///
 /// A `Value` or `ValueRef` that contains integer can be constructed using `From` trait.
-#[derive(Copy, Clone, PartialEq)]
+///
+/// Integers are ordered by their numeric value.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Integer {
     n: IntPriv,
 }
@@ -103,6 +122,25 @@ impl Integer {
     }
 }
 
+impl PartialOrd for Integer {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Integer {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.n, other.n) {
+            (IntPriv::PosInt(a), IntPriv::PosInt(b)) => a.cmp(&b),
+            (IntPriv::NegInt(a), IntPriv::NegInt(b)) => a.cmp(&b),
+            (IntPriv::NegInt(..), IntPriv::PosInt(..)) => Ordering::Less,
+            (IntPriv::PosInt(..), IntPriv::NegInt(..)) => Ordering::Greater,
+        }
+    }
+}
+
 impl Debug for Integer {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         Debug::fmt(&self.n, fmt)
@@ -220,7 +258,9 @@ impl From<isize> for Integer {
 /// it is possible to obtain an underlying bytes that were attempted to convert to a `String`. This
 /// may happen when trying to unpack strings that were decoded using older MessagePack spec with
 /// raw types instead of string/binary.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Strings are ordered and hashed by their bytes, whether they are valid UTF-8 or not.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Utf8String {
     s: Result<String, (Vec<u8>, Utf8Error)>,
 }
@@ -290,6 +330,27 @@ impl Utf8String {
     }
 }
 
+impl PartialOrd for Utf8String {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Utf8String {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl Hash for Utf8String {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
 impl Display for Utf8String {
     #[cold]
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
@@ -328,7 +389,7 @@ impl<'a> From<Cow<'a, str>> for Utf8String {
 }
 
 /// A non-owning evil twin of `Utf8String`. Does exactly the same thing except ownership.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Utf8StringRef<'a> {
     s: Result<&'a str, (&'a [u8], Utf8Error)>,
 }
@@ -386,6 +447,16 @@ impl<'a> Utf8StringRef<'a> {
         self.s.ok()
     }
 
+    /// Consumes this object, yielding the bytes of the string no matter whether it's valid or not
+    /// UTF-8.
+    #[inline]
+    pub fn into_bytes_ref(self) -> &'a [u8] {
+        match self.s {
+            Ok(s) => s.as_bytes(),
+            Err(err) => err.0,
+        }
+    }
+
     /// Converts a `Utf8StringRef` into a byte vector.
     #[inline]
     pub fn into_bytes(self) -> Vec<u8> {
@@ -396,6 +467,27 @@ impl<'a> Utf8StringRef<'a> {
     }
 }
 
+impl<'a> PartialOrd for Utf8StringRef<'a> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for Utf8StringRef<'a> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl<'a> Hash for Utf8StringRef<'a> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
 impl<'a> Display for Utf8StringRef<'a> {
     #[cold]
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
@@ -425,7 +517,27 @@ impl<'a> From<Utf8StringRef<'a>> for Utf8String {
 }
 
 /// Represents any valid MessagePack value.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// # Equality and ordering
+///
+/// Values implement `Eq`, `Ord` and `Hash`, so that they can be used as keys of a `HashMap` or a
+/// `BTreeMap` and be sorted. Values of different types are never equal, and are ordered by type
+/// first, as listed:
+///
+/// 1. Nil.
+/// 2. Boolean, with `false` before `true`.
+/// 3. Integer, by numeric value.
+/// 4. F32, then F64, by [`f64::total_cmp`], so that `-0.0` comes before and is unequal to `0.0`
+///    and NaN equals itself if it has the same bits.
+/// 5. String, by bytes, whether they are valid UTF-8 or not.
+/// 6. Binary, by bytes.
+/// 7. Array, lexicographically by elements.
+/// 8. Map, lexicographically by entries in their stored order, comparing keys, then values.
+/// 9. Ext, by type, then data bytes.
+///
+/// In particular `Value::from(1)`, `Value::F32(1.0)` and `Value::F64(1.0)` are all unequal. Use
+/// [`Value::numeric_eq`] to compare numbers of different types by value.
+#[derive(Clone, Debug)]
 pub enum Value {
     /// Nil represents nil.
     Nil,
@@ -872,6 +984,51 @@ impl Value {
         }
     }
 
+    /// If the `Value` is an Array, returns a mutable reference to the associated vector.
+    /// Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Array(vec![Value::Nil]);
+    /// val.as_array_mut().unwrap().push(Value::Boolean(true));
+    ///
+    /// assert_eq!(Value::Array(vec![Value::Nil, Value::Boolean(true)]), val);
+    /// ```
+    #[inline]
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        if let Value::Array(ref mut array) = *self {
+            Some(array)
+        } else {
+            None
+        }
+    }
+
+    /// If the `Value` is a Map, returns a mutable reference to the associated vector of key-value
+    /// tuples.
+    /// Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![]);
+    /// val.as_map_mut().unwrap().push((Value::Nil, Value::Boolean(true)));
+    ///
+    /// assert_eq!(Value::Map(vec![(Value::Nil, Value::Boolean(true))]), val);
+    /// ```
+    #[inline]
+    pub fn as_map_mut(&mut self) -> Option<&mut Vec<(Value, Value)>> {
+        if let Value::Map(ref mut map) = *self {
+            Some(map)
+        } else {
+            None
+        }
+    }
+
     /// If the `Value` is an Ext, returns the associated tuple with a ty and slice.
     /// Returns None otherwise.
     ///
@@ -892,143 +1049,901 @@ impl Value {
             None
         }
     }
-}
-
-static NIL: Value = Value::Nil;
-static NIL_REF: ValueRef<'static> = ValueRef::Nil;
-
-impl Index<usize> for Value {
-    type Output = Value;
-
-    fn index(&self, index: usize) -> &Value {
-        self.as_array().and_then(|v| v.get(index)).unwrap_or(&NIL)
-    }
-}
-
-impl Index<&str> for Value {
-    type Output = Value;
-    fn index(&self, index: &str) -> &Value {
-        if let Value::Map(ref map) = *self {
-            if let Some(found) = map.iter().find(
-                |(key, _val)|  {
-                if let Value::String(ref strval) = *key {
-                    if let Some(s) = strval.as_str() {
-                        if s == index { return true; }
-                    }
-                }
-                false
-                })
-            {
-                return &found.1;
-            }
-        }
-        &NIL
-    }
-}
 
-impl From<bool> for Value {
+    /// If the `Value` is a Map, returns the value of the first entry with the given key, or if it
+    /// is an Array, returns the element at the position given by an integer key.
+    /// Returns None otherwise.
+    ///
+    /// Unlike indexing, this tells a missing key apart from a key with a Nil value. The key can
+    /// be any `Value`, or anything that converts into a `ValueRef`, such as a `&str` or an
+    /// integer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("name"), Value::from("John")),
+    ///     (Value::from(42), Value::Nil),
+    /// ]);
+    ///
+    /// assert_eq!(Some(&Value::from("John")), val.get("name"));
+    /// assert_eq!(Some(&Value::Nil), val.get(&Value::from(42)));
+    /// assert_eq!(None, val.get("age"));
+    ///
+    /// assert_eq!(Some(&Value::from(2)), Value::from(vec![Value::from(1), Value::from(2)]).get(1));
+    /// ```
     #[inline]
-    fn from(v: bool) -> Self {
-        Value::Boolean(v)
+    pub fn get<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&Value> {
+        let idx = self.key_position(&key.into())?;
+        Some(self.child(idx))
     }
-}
 
-impl From<u8> for Value {
+    /// Like [`get`](Value::get), but returns a mutable reference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![(Value::from("id"), Value::from(1))]);
+    /// *val.get_mut("id").unwrap() = Value::from(2);
+    ///
+    /// assert_eq!(Some(&Value::from(2)), val.get("id"));
+    /// ```
     #[inline]
-    fn from(v: u8) -> Self {
-        Value::Integer(From::from(v))
+    pub fn get_mut<'k, K: Into<ValueRef<'k>>>(&mut self, key: K) -> Option<&mut Value> {
+        let idx = self.key_position(&key.into())?;
+        Some(self.child_mut(idx))
     }
-}
 
-impl From<u16> for Value {
-    #[inline]
-    fn from(v: u16) -> Self {
-        Value::Integer(From::from(v))
+    /// Looks up a nested value by a JSON Pointer ([RFC 6901]), such as `/a/0/b`.
+    /// Returns None if the pointer is malformed or any of its tokens doesn't match.
+    ///
+    /// Each token selects an element of an Array by its position, or an entry of a Map by its
+    /// String key. As MessagePack maps may also have integer keys, a token that matches no String
+    /// key selects the entry with the integer key it spells, if any. `~1` and `~0` escape `/` and
+    /// `~` in tokens. The empty pointer refers to the whole value.
+    ///
+    /// [RFC 6901]: https://tools.ietf.org/html/rfc6901
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("a"), Value::Array(vec![
+    ///         Value::Map(vec![(Value::from("b"), Value::from(42))]),
+    ///     ])),
+    ///     (Value::from(7), Value::from("seven")),
+    /// ]);
+    ///
+    /// assert_eq!(Some(&Value::from(42)), val.pointer("/a/0/b"));
+    /// assert_eq!(Some(&Value::from("seven")), val.pointer("/7"));
+    /// assert_eq!(None, val.pointer("/a/1"));
+    /// assert_eq!(Some(&val), val.pointer(""));
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer.strip_prefix('/')?.split('/').try_fold(self, |val, token| {
+            let idx = val.token_position(&unescape_token(token))?;
+            Some(val.child(idx))
+        })
     }
-}
 
-impl From<u32> for Value {
-    #[inline]
-    fn from(v: u32) -> Self {
-        Value::Integer(From::from(v))
+    /// Like [`pointer`](Value::pointer), but returns a mutable reference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![(Value::from("a"), Value::Array(vec![Value::Nil]))]);
+    /// *val.pointer_mut("/a/0").unwrap() = Value::from(true);
+    ///
+    /// assert_eq!(Some(&Value::from(true)), val.pointer("/a/0"));
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer.strip_prefix('/')?.split('/').try_fold(self, |val, token| {
+            let idx = val.token_position(&unescape_token(token))?;
+            Some(val.child_mut(idx))
+        })
     }
-}
 
-impl From<u64> for Value {
+    /// Looks up a key like [`get`](Value::get), and returns its value if it is a Boolean.
     #[inline]
-    fn from(v: u64) -> Self {
-        Value::Integer(From::from(v))
+    pub fn get_bool<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<bool> {
+        self.get(key).and_then(Value::as_bool)
     }
-}
 
-impl From<usize> for Value {
+    /// Looks up a key like [`get`](Value::get), and returns its value if it is an integer
+    /// representable as `i64`.
     #[inline]
-    fn from(v: usize) -> Self {
-        Value::Integer(From::from(v))
+    pub fn get_i64<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<i64> {
+        self.get(key).and_then(Value::as_i64)
     }
-}
 
-impl From<i8> for Value {
+    /// Looks up a key like [`get`](Value::get), and returns its value if it is an integer
+    /// representable as `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("id"), Value::from(42))]);
+    ///
+    /// assert_eq!(Some(42), val.get_u64("id"));
+    /// assert_eq!(None, val.get_str("id"));
+    /// ```
     #[inline]
-    fn from(v: i8) -> Self {
-        Value::Integer(From::from(v))
+    pub fn get_u64<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<u64> {
+        self.get(key).and_then(Value::as_u64)
     }
-}
 
-impl From<i16> for Value {
+    /// Looks up a key like [`get`](Value::get), and returns its value cast to `f64` if it is a
+    /// number.
     #[inline]
-    fn from(v: i16) -> Self {
-        Value::Integer(From::from(v))
+    pub fn get_f64<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<f64> {
+        self.get(key).and_then(Value::as_f64)
     }
-}
 
-impl From<i32> for Value {
+    /// Looks up a key like [`get`](Value::get), and returns its value if it is a valid UTF-8
+    /// String.
     #[inline]
-    fn from(v: i32) -> Self {
-        Value::Integer(From::from(v))
+    pub fn get_str<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
     }
-}
 
-impl From<i64> for Value {
+    /// Looks up a key like [`get`](Value::get), and returns the bytes of its value if it is a
+    /// Binary or a String.
     #[inline]
-    fn from(v: i64) -> Self {
-        Value::Integer(From::from(v))
+    pub fn get_slice<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&[u8]> {
+        self.get(key).and_then(Value::as_slice)
     }
-}
 
-impl From<isize> for Value {
+    /// Looks up a key like [`get`](Value::get), and returns its value if it is an Array.
     #[inline]
-    fn from(v: isize) -> Self {
-        Value::Integer(From::from(v))
+    pub fn get_array<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&Vec<Value>> {
+        self.get(key).and_then(Value::as_array)
     }
-}
 
-impl From<f32> for Value {
+    /// Looks up a key like [`get`](Value::get), and returns its value if it is a Map.
     #[inline]
-    fn from(v: f32) -> Self {
-        Value::F32(v)
+    pub fn get_map<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&Vec<(Value, Value)>> {
+        self.get(key).and_then(Value::as_map)
     }
-}
 
-impl From<f64> for Value {
-    #[inline]
-    fn from(v: f64) -> Self {
-        Value::F64(v)
+    /// Inserts a key-value pair into a Map, returning the previous value of the key if any.
+    ///
+    /// An existing entry with the same key keeps its position and gets the new value, so that the
+    /// Map stays free of duplicate keys and in insertion order. Nil is turned into an empty Map
+    /// first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Value` is neither a Map nor Nil.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Nil;
+    ///
+    /// assert_eq!(None, val.insert("a", 1));
+    /// assert_eq!(None, val.insert("b", 2));
+    /// assert_eq!(Some(Value::from(1)), val.insert("a", 3));
+    /// assert_eq!(Value::Map(vec![
+    ///     (Value::from("a"), Value::from(3)),
+    ///     (Value::from("b"), Value::from(2)),
+    /// ]), val);
+    /// ```
+    pub fn insert<K: Into<Value>, V: Into<Value>>(&mut self, key: K, val: V) -> Option<Value> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(val)),
+            Entry::Vacant(entry) => {
+                entry.insert(val);
+                None
+            }
+        }
     }
-}
 
-impl From<String> for Value {
-    #[inline]
-    fn from(v: String) -> Self {
-        Value::String(Utf8String::from(v))
+    /// Removes the first entry with the given key from a Map and returns its value, or removes
+    /// the element at the position given by an integer key from an Array.
+    /// Returns None if there is no such entry or element.
+    ///
+    /// The remaining entries or elements keep their order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![
+    ///     (Value::from("a"), Value::from(1)),
+    ///     (Value::from("b"), Value::from(2)),
+    /// ]);
+    ///
+    /// assert_eq!(Some(Value::from(1)), val.remove("a"));
+    /// assert_eq!(None, val.remove("a"));
+    /// assert_eq!(Value::Map(vec![(Value::from("b"), Value::from(2))]), val);
+    /// ```
+    pub fn remove<'k, K: Into<ValueRef<'k>>>(&mut self, key: K) -> Option<Value> {
+        let idx = self.key_position(&key.into())?;
+        match *self {
+            Value::Array(ref mut array) => Some(array.remove(idx)),
+            Value::Map(ref mut map) => Some(map.remove(idx).1),
+            _ => unreachable!("only arrays and maps have children"),
+        }
     }
-}
 
-impl<'a> From<&'a str> for Value {
-    #[inline]
-    fn from(v: &str) -> Self {
-        Value::String(Utf8String::from(v))
-    }
+    /// Gets the entry of a Map with the given key for in-place manipulation. Nil is turned into
+    /// an empty Map first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Value` is neither a Map nor Nil.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![]);
+    /// for word in ["a", "b", "a"] {
+    ///     let count = val.entry(word).or_insert(0);
+    ///     *count = Value::from(count.as_u64().unwrap() + 1);
+    /// }
+    ///
+    /// assert_eq!(Some(2), val.get_u64("a"));
+    /// assert_eq!(Some(1), val.get_u64("b"));
+    /// ```
+    pub fn entry<K: Into<Value>>(&mut self, key: K) -> Entry<'_> {
+        let key = key.into();
+        if self.is_nil() {
+            *self = Value::Map(Vec::new());
+        }
+        let map = match *self {
+            Value::Map(ref mut map) => map,
+            ref val => panic!("cannot get an entry of non-map value {}", val),
+        };
+        match map.iter().position(|(k, _)| *k == key) {
+            Some(idx) => Entry::Occupied(OccupiedEntry { map, idx }),
+            None => Entry::Vacant(VacantEntry { map, key }),
+        }
+    }
+
+    /// Compares with another `Value`, treating numbers of the Integer, F32 and F64 types as equal
+    /// if they have the same numeric value.
+    ///
+    /// Arrays and maps compare their elements and entries the same way, and other values compare
+    /// like `==`. Unlike `==`, floats compare as IEEE 754 numbers, so that NaN is unequal to
+    /// everything and `-0.0` equals `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert!(Value::from(1).numeric_eq(&Value::F64(1.0)));
+    /// assert!(Value::F32(0.5).numeric_eq(&Value::F64(0.5)));
+    /// assert!(!Value::from(1).numeric_eq(&Value::F64(1.5)));
+    /// assert!(!Value::F64(f64::NAN).numeric_eq(&Value::F64(f64::NAN)));
+    ///
+    /// assert_ne!(Value::from(1), Value::F64(1.0));
+    /// ```
+    pub fn numeric_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.numeric_eq(b))
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|((ak, av), (bk, bv))| {
+                    ak.numeric_eq(bk) && av.numeric_eq(bv)
+                })
+            }
+            _ => match (self.as_number(), other.as_number()) {
+                (Some(a), Some(b)) => a.numeric_eq(b),
+                (None, None) => self == other,
+                _ => false,
+            },
+        }
+    }
+
+    #[inline]
+    fn as_number(&self) -> Option<Number> {
+        match *self {
+            Value::Integer(n) => Some(Number::Int(n)),
+            Value::F32(n) => Some(Number::Float(n.into())),
+            Value::F64(n) => Some(Number::Float(n)),
+            _ => None,
+        }
+    }
+
+    /// Takes the value out, leaving Nil in its place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![(Value::from("a"), Value::from("text"))]);
+    ///
+    /// assert_eq!(Value::from("text"), val.get_mut("a").unwrap().take());
+    /// assert_eq!(Some(&Value::Nil), val.get("a"));
+    /// ```
+    #[inline]
+    pub fn take(&mut self) -> Value {
+        mem::take(self)
+    }
+
+    /// Merges a patch into the value, in the manner of a JSON Merge Patch (RFC 7386).
+    ///
+    /// If the patch is a Map, its entries are merged into the entries with the same keys,
+    /// recursively, and an entry whose value is Nil removes all entries with its key. The value
+    /// is turned into an empty Map first if it isn't one. Any other patch replaces the value.
+    ///
+    /// As Nil deletes, a merge can't set an entry to Nil, and Arrays are replaced rather than
+    /// merged. Keys of any type are matched, not only strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::msgpack;
+    ///
+    /// let mut val = msgpack!({"title": "Goodbye!", "author": {"name": "John", "age": 42}, 1: []});
+    /// val.merge(msgpack!({
+    ///     "title": "Hello!",
+    ///     "author": {"age": nil},
+    ///     1: [true],
+    ///     "new": {"a": nil},
+    /// }));
+    ///
+    /// assert_eq!(msgpack!({
+    ///     "title": "Hello!",
+    ///     "author": {"name": "John"},
+    ///     1: [true],
+    ///     "new": {},
+    /// }), val);
+    /// ```
+    pub fn merge(&mut self, patch: Value) {
+        let entries = match patch {
+            Value::Map(entries) => entries,
+            patch => {
+                *self = patch;
+                return;
+            }
+        };
+
+        if !self.is_map() {
+            *self = Value::Map(Vec::new());
+        }
+        for (key, val) in entries {
+            if val.is_nil() {
+                if let Value::Map(ref mut map) = *self {
+                    map.retain(|(k, _)| *k != key);
+                }
+            } else {
+                self.entry(key).or_insert(Value::Nil).merge(val);
+            }
+        }
+    }
+
+    /// Brings the value into its canonical form, in which Map entries are sorted by the
+    /// canonical encoding of their keys and every NaN has the bits of `f32::NAN` or `f64::NAN`.
+    ///
+    /// Values that only differ in the order of Map entries or in NaN payloads are equal after
+    /// canonicalization, and [`write_value_canonical`](encode::write_value_canonical) writes the
+    /// same bytes for them. Arrays keep their order, and numbers keep their types.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DuplicateKeyError`] if a Map has more than one entry with the same key, as
+    /// their order would be undefined. No entries are lost: the Map with the duplicate is sorted
+    /// all the same, the Maps that contain it keep their order, and the values that were already
+    /// processed stay canonicalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{msgpack, Value};
+    ///
+    /// let mut val = msgpack!({"b": [f64::NAN], "a": {2: nil, 1: nil}});
+    /// val.canonicalize().unwrap();
+    ///
+    /// assert_eq!(msgpack!({"a": {1: nil, 2: nil}, "b": [f64::NAN]}), val);
+    ///
+    /// let mut val = msgpack!({"a": 1, "a": 2});
+    /// assert_eq!(Value::from("a"), val.canonicalize().unwrap_err().into_key());
+    /// ```
+    pub fn canonicalize(&mut self) -> Result<(), DuplicateKeyError> {
+        match *self {
+            Value::F32(ref mut val) if val.is_nan() => *val = f32::NAN,
+            Value::F64(ref mut val) if val.is_nan() => *val = f64::NAN,
+            Value::Array(ref mut vec) => {
+                for val in vec {
+                    val.canonicalize()?;
+                }
+            }
+            Value::Map(ref mut map) => {
+                // The entries are only taken out of the map once they all succeeded, so that an
+                // error doesn't lose them.
+                let mut keys = Vec::with_capacity(map.len());
+                for (key, val) in map.iter_mut() {
+                    key.canonicalize()?;
+                    val.canonicalize()?;
+                    let mut buf = Vec::new();
+                    canonical_write(&mut buf, key)?;
+                    keys.push(buf);
+                }
+                let mut entries: Vec<_> = keys.into_iter().zip(mem::take(map)).collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+                let dup = entries.windows(2).position(|pair| pair[0].0 == pair[1].0);
+                *map = entries.into_iter().map(|(_, entry)| entry).collect();
+                if let Some(idx) = dup {
+                    return Err(DuplicateKeyError(map[idx].0.clone()));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Returns a hash of the canonical encoding of the value, which is equal for values that are
+    /// equal after [`canonicalize`](Value::canonicalize).
+    ///
+    /// Unlike the `Hash` impl, the hash is stable: it is the 64-bit FNV-1a hash of the bytes
+    /// [`write_value_canonical`](encode::write_value_canonical) writes, which doesn't change
+    /// between program runs, platforms or versions of this crate. It is not a cryptographic hash,
+    /// so values whose hashes are equal should still be compared when deduplicating.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DuplicateKeyError`] if a Map has more than one entry with the same key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::msgpack;
+    ///
+    /// let a = msgpack!({"id": 1, "tags": ["x"]});
+    /// let b = msgpack!({"tags": ["x"], "id": 1});
+    ///
+    /// assert_ne!(a, b);
+    /// assert_eq!(a.canonical_hash().unwrap(), b.canonical_hash().unwrap());
+    /// ```
+    pub fn canonical_hash(&self) -> Result<u64, DuplicateKeyError> {
+        let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+        canonical_write(&mut hasher, self)?;
+        Ok(hasher.0)
+    }
+
+    /// Returns the position of the Array element or the Map entry with the given key.
+    fn key_position(&self, key: &ValueRef<'_>) -> Option<usize> {
+        match *self {
+            Value::Array(ref array) => match *key {
+                ValueRef::Integer(n) => n.as_u64()
+                    .and_then(|idx| usize::try_from(idx).ok())
+                    .filter(|&idx| idx < array.len()),
+                _ => None,
+            },
+            Value::Map(ref map) => map.iter().position(|(k, _)| k.as_ref() == *key),
+            _ => None,
+        }
+    }
+
+    /// Returns the position of the Array element or the Map entry selected by a pointer token.
+    fn token_position(&self, token: &str) -> Option<usize> {
+        match *self {
+            Value::Array(..) => match parse_index(token) {
+                Some(idx) => self.key_position(&ValueRef::from(idx)),
+                None => None,
+            },
+            Value::Map(..) => self.key_position(&ValueRef::from(token)).or_else(|| {
+                let key = match token.parse::<i64>() {
+                    Ok(n) => ValueRef::from(n),
+                    Err(..) => ValueRef::from(token.parse::<u64>().ok()?),
+                };
+                self.key_position(&key)
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the Array element or the value of the Map entry at the given position.
+    fn child(&self, idx: usize) -> &Value {
+        match *self {
+            Value::Array(ref array) => &array[idx],
+            Value::Map(ref map) => &map[idx].1,
+            _ => unreachable!("only arrays and maps have children"),
+        }
+    }
+
+    fn child_mut(&mut self, idx: usize) -> &mut Value {
+        match *self {
+            Value::Array(ref mut array) => &mut array[idx],
+            Value::Map(ref mut map) => &mut map[idx].1,
+            _ => unreachable!("only arrays and maps have children"),
+        }
+    }
+}
+
+/// Replaces the `~1` and `~0` escapes of a JSON Pointer token.
+fn unescape_token(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Parses a JSON Pointer array index, which has no sign or leading zeros.
+fn parse_index(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.starts_with('0') && token.len() > 1) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+static NIL: Value = Value::Nil;
+static NIL_REF: ValueRef<'static> = ValueRef::Nil;
+
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        self.as_array().and_then(|v| v.get(index)).unwrap_or(&NIL)
+    }
+}
+
+impl Index<&str> for Value {
+    type Output = Value;
+    fn index(&self, index: &str) -> &Value {
+        if let Value::Map(ref map) = *self {
+            if let Some(found) = map.iter().find(
+                |(key, _val)|  {
+                if let Value::String(ref strval) = *key {
+                    if let Some(s) = strval.as_str() {
+                        if s == index { return true; }
+                    }
+                }
+                false
+                })
+            {
+                return &found.1;
+            }
+        }
+        &NIL
+    }
+}
+
+/// Indexes into an Array mutably.
+///
+/// # Panics
+///
+/// Unlike indexing immutably, which results in Nil, panics if the `Value` is not an Array or the
+/// index is out of bounds.
+impl IndexMut<usize> for Value {
+    fn index_mut(&mut self, index: usize) -> &mut Value {
+        match *self {
+            Value::Array(ref mut array) => {
+                let len = array.len();
+                match array.get_mut(index) {
+                    Some(val) => val,
+                    None => panic!("index {} out of bounds for array of length {}", index, len),
+                }
+            }
+            ref val => panic!("cannot index non-array value {} with {}", val, index),
+        }
+    }
+}
+
+/// Indexes into a Map mutably, inserting a Nil value if the key is missing, like
+/// [`Value::entry`].
+///
+/// # Panics
+///
+/// Panics if the `Value` is neither a Map nor Nil.
+impl IndexMut<&str> for Value {
+    fn index_mut(&mut self, index: &str) -> &mut Value {
+        self.entry(index).or_insert(Value::Nil)
+    }
+}
+
+impl Default for Value {
+    /// Returns Nil.
+    #[inline]
+    fn default() -> Self {
+        Value::Nil
+    }
+}
+
+/// An entry of a Map, which is either occupied or vacant, see [`Value::entry`].
+#[derive(Debug)]
+pub enum Entry<'a> {
+    /// An entry with the key.
+    Occupied(OccupiedEntry<'a>),
+    /// A missing entry, where the key would be appended.
+    Vacant(VacantEntry<'a>),
+}
+
+impl<'a> Entry<'a> {
+    /// Returns the key of the entry.
+    #[inline]
+    pub fn key(&self) -> &Value {
+        match *self {
+            Entry::Occupied(ref entry) => entry.key(),
+            Entry::Vacant(ref entry) => entry.key(),
+        }
+    }
+
+    /// Inserts the default if the entry is vacant, and returns a mutable reference to its value.
+    #[inline]
+    pub fn or_insert<V: Into<Value>>(self, default: V) -> &'a mut Value {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Inserts the result of `default` if the entry is vacant, and returns a mutable reference to
+    /// its value.
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> Value>(self, default: F) -> &'a mut Value {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Calls `f` on the value if the entry is occupied.
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut Value)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(ref mut entry) = self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// An entry of a Map with the key, see [`Entry`].
+#[derive(Debug)]
+pub struct OccupiedEntry<'a> {
+    map: &'a mut Vec<(Value, Value)>,
+    idx: usize,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// Returns the key of the entry.
+    #[inline]
+    pub fn key(&self) -> &Value {
+        &self.map[self.idx].0
+    }
+
+    /// Returns the value of the entry.
+    #[inline]
+    pub fn get(&self) -> &Value {
+        &self.map[self.idx].1
+    }
+
+    /// Returns a mutable reference to the value of the entry.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut Value {
+        &mut self.map[self.idx].1
+    }
+
+    /// Converts the entry into a mutable reference to its value, which lives as long as the Map.
+    #[inline]
+    pub fn into_mut(self) -> &'a mut Value {
+        &mut self.map[self.idx].1
+    }
+
+    /// Replaces the value of the entry, returning the previous one.
+    #[inline]
+    pub fn insert<V: Into<Value>>(&mut self, val: V) -> Value {
+        mem::replace(self.get_mut(), val.into())
+    }
+
+    /// Removes the entry from the Map, keeping the order of the others, and returns its value.
+    #[inline]
+    pub fn remove(self) -> Value {
+        self.map.remove(self.idx).1
+    }
+}
+
+/// A missing entry of a Map, see [`Entry`].
+#[derive(Debug)]
+pub struct VacantEntry<'a> {
+    map: &'a mut Vec<(Value, Value)>,
+    key: Value,
+}
+
+impl<'a> VacantEntry<'a> {
+    /// Returns the key the entry would have.
+    #[inline]
+    pub fn key(&self) -> &Value {
+        &self.key
+    }
+
+    /// Returns the key back.
+    #[inline]
+    pub fn into_key(self) -> Value {
+        self.key
+    }
+
+    /// Appends the entry with the given value to the Map, and returns a mutable reference to the
+    /// value.
+    #[inline]
+    pub fn insert<V: Into<Value>>(self, val: V) -> &'a mut Value {
+        self.map.push((self.key, val.into()));
+        &mut self.map.last_mut().unwrap().1
+    }
+}
+
+/// An error canonicalizing a Map with more than one entry with the enclosed key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateKeyError(Value);
+
+impl DuplicateKeyError {
+    /// Returns the duplicate key.
+    #[inline]
+    pub fn key(&self) -> &Value {
+        &self.0
+    }
+
+    /// Returns the duplicate key, consuming the error.
+    #[inline]
+    pub fn into_key(self) -> Value {
+        self.0
+    }
+}
+
+impl Display for DuplicateKeyError {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(fmt, "duplicate map key {}", self.0)
+    }
+}
+
+impl error::Error for DuplicateKeyError {}
+
+/// Writes the canonical encoding into an infallible writer.
+fn canonical_write<W: io::Write>(wr: &mut W, val: &Value) -> Result<(), DuplicateKeyError> {
+    match write_value_canonical(wr, val) {
+        Ok(()) => Ok(()),
+        Err(CanonicalError::DuplicateKey(key)) => Err(DuplicateKeyError(key)),
+        Err(CanonicalError::Write(err)) => unreachable!("infallible writer failed: {}", err),
+    }
+}
+
+/// The 64-bit FNV-1a hash of the written bytes.
+struct Fnv1a(u64);
+
+impl io::Write for Fnv1a {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl From<bool> for Value {
+    #[inline]
+    fn from(v: bool) -> Self {
+        Value::Boolean(v)
+    }
+}
+
+impl From<u8> for Value {
+    #[inline]
+    fn from(v: u8) -> Self {
+        Value::Integer(From::from(v))
+    }
+}
+
+impl From<u16> for Value {
+    #[inline]
+    fn from(v: u16) -> Self {
+        Value::Integer(From::from(v))
+    }
+}
+
+impl From<u32> for Value {
+    #[inline]
+    fn from(v: u32) -> Self {
+        Value::Integer(From::from(v))
+    }
+}
+
+impl From<u64> for Value {
+    #[inline]
+    fn from(v: u64) -> Self {
+        Value::Integer(From::from(v))
+    }
+}
+
+impl From<usize> for Value {
+    #[inline]
+    fn from(v: usize) -> Self {
+        Value::Integer(From::from(v))
+    }
+}
+
+impl From<i8> for Value {
+    #[inline]
+    fn from(v: i8) -> Self {
+        Value::Integer(From::from(v))
+    }
+}
+
+impl From<i16> for Value {
+    #[inline]
+    fn from(v: i16) -> Self {
+        Value::Integer(From::from(v))
+    }
+}
+
+impl From<i32> for Value {
+    #[inline]
+    fn from(v: i32) -> Self {
+        Value::Integer(From::from(v))
+    }
+}
+
+impl From<i64> for Value {
+    #[inline]
+    fn from(v: i64) -> Self {
+        Value::Integer(From::from(v))
+    }
+}
+
+impl From<isize> for Value {
+    #[inline]
+    fn from(v: isize) -> Self {
+        Value::Integer(From::from(v))
+    }
+}
+
+impl From<f32> for Value {
+    #[inline]
+    fn from(v: f32) -> Self {
+        Value::F32(v)
+    }
+}
+
+impl From<f64> for Value {
+    #[inline]
+    fn from(v: f64) -> Self {
+        Value::F64(v)
+    }
+}
+
+impl From<String> for Value {
+    #[inline]
+    fn from(v: String) -> Self {
+        Value::String(Utf8String::from(v))
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    #[inline]
+    fn from(v: &str) -> Self {
+        Value::String(Utf8String::from(v))
+    }
 }
 
 impl<'a> From<Cow<'a, str>> for Value {
@@ -1229,7 +2144,10 @@ impl Display for Value {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// A non-owning `Value`, which borrows strings, binaries and ext data.
+///
+/// Compares, orders and hashes like `Value`, see [Equality and ordering](Value#equality-and-ordering).
+#[derive(Clone, Debug)]
 pub enum ValueRef<'a> {
     /// Nil represents nil.
     Nil,
@@ -1256,61 +2174,220 @@ pub enum ValueRef<'a> {
     Ext(i8, &'a [u8]),
 }
 
-impl<'a> ValueRef<'a> {
-    /// Converts the current non-owning value to an owned Value.
-    ///
-    /// This is achieved by deep copying all underlying structures and borrowed buffers.
+impl<'a> ValueRef<'a> {
+    /// Converts the current non-owning value to an owned Value.
+    ///
+    /// This is achieved by deep copying all underlying structures and borrowed buffers.
+    ///
+    /// # Panics
+    ///
+    /// Panics in unable to allocate memory to keep all internal structures and buffers.
+    ///
+    /// # Examples
+    /// ```
+    /// use rmpv::{Value, ValueRef};
+    ///
+    /// let val = ValueRef::Array(vec![
+    ///    ValueRef::Nil,
+    ///    ValueRef::from(42),
+    ///    ValueRef::Array(vec![
+    ///        ValueRef::from("le message"),
+    ///    ])
+    /// ]);
+    ///
+    /// let expected = Value::Array(vec![
+    ///     Value::Nil,
+    ///     Value::from(42),
+    ///     Value::Array(vec![
+    ///         Value::String("le message".into())
+    ///     ])
+    /// ]);
+    ///
+    /// assert_eq!(expected, val.to_owned());
+    /// ```
+    pub fn to_owned(&self) -> Value {
+        match *self {
+            ValueRef::Nil => Value::Nil,
+            ValueRef::Boolean(val) => Value::Boolean(val),
+            ValueRef::Integer(val) => Value::Integer(val),
+            ValueRef::F32(val) => Value::F32(val),
+            ValueRef::F64(val) => Value::F64(val),
+            ValueRef::String(val) => Value::String(val.into()),
+            ValueRef::Binary(val) => Value::Binary(val.to_vec()),
+            ValueRef::Array(ref val) => {
+                Value::Array(val.iter().map(|v| v.to_owned()).collect())
+            }
+            ValueRef::Map(ref val) => {
+                Value::Map(val.iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect())
+            }
+            ValueRef::Ext(ty, buf) => Value::Ext(ty, buf.to_vec()),
+        }
+    }
+
+    pub fn index(&self, index: usize) -> &ValueRef<'_> {
+        self.as_array().and_then(|v| v.get(index)).unwrap_or(&NIL_REF)
+    }
+
+    /// Returns true if the `ValueRef` is a Null. Returns false otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::ValueRef;
+    ///
+    /// assert!(ValueRef::Nil.is_nil());
+    /// ```
+    #[inline]
+    pub fn is_nil(&self) -> bool {
+        matches!(*self, ValueRef::Nil)
+    }
+
+    /// Returns true if the `ValueRef` is a Boolean. Returns false otherwise.
+    #[inline]
+    pub fn is_bool(&self) -> bool {
+        self.as_bool().is_some()
+    }
+
+    /// Returns true if the `ValueRef` is convertible to an i64. Returns false otherwise.
+    #[inline]
+    pub fn is_i64(&self) -> bool {
+        if let ValueRef::Integer(ref v) = *self {
+            v.is_i64()
+        } else {
+            false
+        }
+    }
+
+    /// Returns true if the `ValueRef` is convertible to an u64. Returns false otherwise.
+    #[inline]
+    pub fn is_u64(&self) -> bool {
+        if let ValueRef::Integer(ref v) = *self {
+            v.is_u64()
+        } else {
+            false
+        }
+    }
+
+    /// Returns true if (and only if) the `ValueRef` is a f32. Returns false otherwise.
+    #[inline]
+    pub fn is_f32(&self) -> bool {
+        matches!(*self, ValueRef::F32(..))
+    }
+
+    /// Returns true if (and only if) the `ValueRef` is a f64. Returns false otherwise.
+    #[inline]
+    pub fn is_f64(&self) -> bool {
+        matches!(*self, ValueRef::F64(..))
+    }
+
+    /// Returns true if the `ValueRef` is a Number. Returns false otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::ValueRef;
+    ///
+    /// assert!(ValueRef::from(42).is_number());
+    /// assert!(ValueRef::F32(42.0).is_number());
+    /// assert!(ValueRef::F64(42.0).is_number());
+    ///
+    /// assert!(!ValueRef::Nil.is_number());
+    /// ```
+    #[inline]
+    pub fn is_number(&self) -> bool {
+        matches!(*self, ValueRef::Integer(..) | ValueRef::F32(..) | ValueRef::F64(..))
+    }
+
+    /// Returns true if the `ValueRef` is a String. Returns false otherwise.
+    #[inline]
+    pub fn is_str(&self) -> bool {
+        self.as_str().is_some()
+    }
+
+    /// Returns true if the `ValueRef` is a Binary. Returns false otherwise.
+    #[inline]
+    pub fn is_bin(&self) -> bool {
+        self.as_slice().is_some()
+    }
+
+    /// Returns true if the `ValueRef` is an Array. Returns false otherwise.
+    #[inline]
+    pub fn is_array(&self) -> bool {
+        self.as_array().is_some()
+    }
+
+    /// Returns true if the `ValueRef` is a Map. Returns false otherwise.
+    #[inline]
+    pub fn is_map(&self) -> bool {
+        self.as_map().is_some()
+    }
+
+    /// Returns true if the `ValueRef` is an Ext. Returns false otherwise.
+    #[inline]
+    pub fn is_ext(&self) -> bool {
+        self.as_ext().is_some()
+    }
+
+    /// If the `ValueRef` is a Boolean, returns the associated bool.
+    /// Returns None otherwise.
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        if let ValueRef::Boolean(val) = *self {
+            Some(val)
+        } else {
+            None
+        }
+    }
+
+    /// If the `ValueRef` is an integer, return or cast it to a i64.
+    /// Returns None otherwise.
     ///
-    /// # Panics
+    /// # Examples
     ///
-    /// Panics in unable to allocate memory to keep all internal structures and buffers.
+    /// ```
+    /// use rmpv::ValueRef;
     ///
-    /// # Examples
+    /// assert_eq!(Some(-42), ValueRef::from(-42).as_i64());
+    /// assert_eq!(None, ValueRef::from(u64::MAX).as_i64());
     /// ```
-    /// use rmpv::{Value, ValueRef};
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            ValueRef::Integer(ref n) => n.as_i64(),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is an integer, return or cast it to a u64.
+    /// Returns None otherwise.
     ///
-    /// let val = ValueRef::Array(vec![
-    ///    ValueRef::Nil,
-    ///    ValueRef::from(42),
-    ///    ValueRef::Array(vec![
-    ///        ValueRef::from("le message"),
-    ///    ])
-    /// ]);
+    /// # Examples
     ///
-    /// let expected = Value::Array(vec![
-    ///     Value::Nil,
-    ///     Value::from(42),
-    ///     Value::Array(vec![
-    ///         Value::String("le message".into())
-    ///     ])
-    /// ]);
+    /// ```
+    /// use rmpv::ValueRef;
     ///
-    /// assert_eq!(expected, val.to_owned());
+    /// assert_eq!(Some(42), ValueRef::from(42).as_u64());
     /// ```
-    pub fn to_owned(&self) -> Value {
+    pub fn as_u64(&self) -> Option<u64> {
         match *self {
-            ValueRef::Nil => Value::Nil,
-            ValueRef::Boolean(val) => Value::Boolean(val),
-            ValueRef::Integer(val) => Value::Integer(val),
-            ValueRef::F32(val) => Value::F32(val),
-            ValueRef::F64(val) => Value::F64(val),
-            ValueRef::String(val) => Value::String(val.into()),
-            ValueRef::Binary(val) => Value::Binary(val.to_vec()),
-            ValueRef::Array(ref val) => {
-                Value::Array(val.iter().map(|v| v.to_owned()).collect())
-            }
-            ValueRef::Map(ref val) => {
-                Value::Map(val.iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect())
-            }
-            ValueRef::Ext(ty, buf) => Value::Ext(ty, buf.to_vec()),
+            ValueRef::Integer(ref n) => n.as_u64(),
+            _ => None,
         }
     }
 
-    pub fn index(&self, index: usize) -> &ValueRef<'_> {
-        self.as_array().and_then(|v| v.get(index)).unwrap_or(&NIL_REF)
+    /// If the `ValueRef` is a number, return or cast it to a f64.
+    /// Returns None otherwise.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            ValueRef::Integer(ref n) => n.as_f64(),
+            ValueRef::F32(n) => Some(From::from(n)),
+            ValueRef::F64(n) => Some(n),
+            _ => None,
+        }
     }
 
-    /// If the `ValueRef` is an integer, return or cast it to a u64.
+    /// If the `ValueRef` is a valid UTF-8 String, returns the associated str, borrowed for as long
+    /// as the input the `ValueRef` was decoded from.
     /// Returns None otherwise.
     ///
     /// # Examples
@@ -1318,11 +2395,30 @@ impl<'a> ValueRef<'a> {
     /// ```
     /// use rmpv::ValueRef;
     ///
-    /// assert_eq!(Some(42), ValueRef::from(42).as_u64());
+    /// let buf = [0xa2, 0x68, 0x69];
+    /// let text = {
+    ///     let val = rmpv::decode::read_value_ref(&mut &buf[..]).unwrap();
+    ///     val.as_str().unwrap()
+    /// };
+    ///
+    /// assert_eq!("hi", text);
+    /// assert_eq!(None, ValueRef::Boolean(true).as_str());
     /// ```
-    pub fn as_u64(&self) -> Option<u64> {
+    #[inline]
+    pub fn as_str(&self) -> Option<&'a str> {
+        if let ValueRef::String(val) = *self {
+            val.into_str()
+        } else {
+            None
+        }
+    }
+
+    /// If the `ValueRef` is a Binary or a String, returns the associated slice.
+    /// Returns None otherwise.
+    pub fn as_slice(&self) -> Option<&'a [u8]> {
         match *self {
-            ValueRef::Integer(ref n) => n.as_u64(),
+            ValueRef::Binary(val) => Some(val),
+            ValueRef::String(val) => Some(val.into_bytes_ref()),
             _ => None,
         }
     }
@@ -1340,7 +2436,7 @@ impl<'a> ValueRef<'a> {
     /// assert_eq!(Some(&vec![ValueRef::Nil, ValueRef::Boolean(true)]), val.as_array());
     /// assert_eq!(None, ValueRef::Nil.as_array());
     /// ```
-    pub fn as_array(&self) -> Option<&Vec<ValueRef<'_>>> {
+    pub fn as_array(&self) -> Option<&Vec<ValueRef<'a>>> {
         if let ValueRef::Array(ref array) = *self {
             Some(array)
         } else {
@@ -1348,6 +2444,165 @@ impl<'a> ValueRef<'a> {
         }
     }
 
+    /// If the `ValueRef` is a Map, returns the associated vector of key-value tuples.
+    /// Returns None otherwise.
+    #[inline]
+    pub fn as_map(&self) -> Option<&Vec<(ValueRef<'a>, ValueRef<'a>)>> {
+        if let ValueRef::Map(ref map) = *self {
+            Some(map)
+        } else {
+            None
+        }
+    }
+
+    /// If the `ValueRef` is an Ext, returns the associated tuple with a ty and slice.
+    /// Returns None otherwise.
+    #[inline]
+    pub fn as_ext(&self) -> Option<(i8, &'a [u8])> {
+        if let ValueRef::Ext(ty, buf) = *self {
+            Some((ty, buf))
+        } else {
+            None
+        }
+    }
+
+    /// Looks up the value of a Map entry or an Array element, like [`Value::get`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::ValueRef;
+    ///
+    /// let val = ValueRef::Map(vec![(ValueRef::from("id"), ValueRef::from(42))]);
+    ///
+    /// assert_eq!(Some(&ValueRef::from(42)), val.get("id"));
+    /// assert_eq!(None, val.get("name"));
+    /// ```
+    pub fn get<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&ValueRef<'a>> {
+        let key = key.into();
+        match *self {
+            ValueRef::Array(ref array) => match key {
+                ValueRef::Integer(n) => array.get(usize::try_from(n.as_u64()?).ok()?),
+                _ => None,
+            },
+            ValueRef::Map(ref map) => map.iter().find(|(k, _)| *k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Looks up a key like [`get`](ValueRef::get), and returns its value if it is a Boolean.
+    #[inline]
+    pub fn get_bool<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<bool> {
+        self.get(key).and_then(ValueRef::as_bool)
+    }
+
+    /// Looks up a key like [`get`](ValueRef::get), and returns its value if it is an integer
+    /// representable as `i64`.
+    #[inline]
+    pub fn get_i64<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<i64> {
+        self.get(key).and_then(ValueRef::as_i64)
+    }
+
+    /// Looks up a key like [`get`](ValueRef::get), and returns its value if it is an integer
+    /// representable as `u64`.
+    #[inline]
+    pub fn get_u64<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<u64> {
+        self.get(key).and_then(ValueRef::as_u64)
+    }
+
+    /// Looks up a key like [`get`](ValueRef::get), and returns its value cast to `f64` if it is a
+    /// number.
+    #[inline]
+    pub fn get_f64<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<f64> {
+        self.get(key).and_then(ValueRef::as_f64)
+    }
+
+    /// Looks up a key like [`get`](ValueRef::get), and returns its value if it is a valid UTF-8
+    /// String.
+    ///
+    /// The string borrows from the underlying buffer rather than from the `ValueRef`, so it
+    /// outlives it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::ValueRef;
+    ///
+    /// let buf = [0x81, 0xa4, 0x6e, 0x61, 0x6d, 0x65, 0xa2, 0x68, 0x69];
+    /// let name = {
+    ///     let val = rmpv::decode::read_value_ref(&mut &buf[..]).unwrap();
+    ///     val.get_str("name").unwrap()
+    /// };
+    ///
+    /// assert_eq!("hi", name);
+    /// assert_eq!(None, ValueRef::Nil.get_str("name"));
+    /// ```
+    #[inline]
+    pub fn get_str<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&'a str> {
+        self.get(key).and_then(ValueRef::as_str)
+    }
+
+    /// Looks up a key like [`get`](ValueRef::get), and returns the bytes of its value if it is a
+    /// Binary or a String.
+    #[inline]
+    pub fn get_slice<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&'a [u8]> {
+        self.get(key).and_then(ValueRef::as_slice)
+    }
+
+    /// Looks up a key like [`get`](ValueRef::get), and returns its value if it is an Array.
+    #[inline]
+    pub fn get_array<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&Vec<ValueRef<'a>>> {
+        self.get(key).and_then(ValueRef::as_array)
+    }
+
+    /// Looks up a key like [`get`](ValueRef::get), and returns its value if it is a Map.
+    #[inline]
+    pub fn get_map<'k, K: Into<ValueRef<'k>>>(&self, key: K) -> Option<&Vec<(ValueRef<'a>, ValueRef<'a>)>> {
+        self.get(key).and_then(ValueRef::as_map)
+    }
+
+    /// Looks up a nested value by a JSON Pointer, like [`Value::pointer`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::ValueRef;
+    ///
+    /// let val = ValueRef::Map(vec![
+    ///     (ValueRef::from("a"), ValueRef::Array(vec![ValueRef::from("b")])),
+    /// ]);
+    ///
+    /// assert_eq!(Some("b"), val.pointer("/a/0").and_then(|v| v.as_str()));
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&ValueRef<'a>> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer.strip_prefix('/')?.split('/').try_fold(self, |val, token| {
+            let token = unescape_token(token);
+            match *val {
+                ValueRef::Array(..) => val.get(parse_index(&token)?),
+                ValueRef::Map(..) => val.get(&*token).or_else(|| match token.parse::<i64>() {
+                    Ok(n) => val.get(n),
+                    Err(..) => val.get(token.parse::<u64>().ok()?),
+                }),
+                _ => None,
+            }
+        })
+    }
+
+    /// Consumes the `ValueRef`, returning the associated vector of key-value tuples if it is a
+    /// Map.
+    /// Returns None otherwise.
+    #[inline]
+    pub fn into_map(self) -> Option<Vec<(ValueRef<'a>, ValueRef<'a>)>> {
+        if let ValueRef::Map(map) = self {
+            Some(map)
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn into_array(self) -> Option<Vec<ValueRef<'a>>> {
         if let ValueRef::Array(array) = self {
@@ -1358,6 +2613,13 @@ impl<'a> ValueRef<'a> {
     }
 }
 
+impl<'a> From<bool> for ValueRef<'a> {
+    #[inline]
+    fn from(v: bool) -> Self {
+        ValueRef::Boolean(v)
+    }
+}
+
 impl<'a> From<u8> for ValueRef<'a> {
     #[inline]
     fn from(v: u8) -> Self {
@@ -1449,6 +2711,13 @@ impl<'a> From<&'a str> for ValueRef<'a> {
     }
 }
 
+impl<'a> From<&'a Value> for ValueRef<'a> {
+    #[inline]
+    fn from(val: &'a Value) -> Self {
+        val.as_ref()
+    }
+}
+
 impl<'a> From<&'a [u8]> for ValueRef<'a> {
     #[inline]
     fn from(v: &'a [u8]) -> Self {
@@ -1496,6 +2765,62 @@ impl<'a> TryFrom<ValueRef<'a>> for u64 {
   }
 }
 
+impl<'a> TryFrom<ValueRef<'a>> for i64 {
+  type Error = ValueRef<'a>;
+
+  fn try_from(val: ValueRef<'a>) -> Result<Self, Self::Error> {
+      match val {
+        ValueRef::Integer(n) => {
+          match n.as_i64() {
+            Some(i) => Ok(i),
+            None => Err(val)
+          }
+        }
+        v => Err(v),
+      }
+  }
+}
+
+impl<'a> TryFrom<ValueRef<'a>> for f64 {
+  type Error = ValueRef<'a>;
+
+  fn try_from(val: ValueRef<'a>) -> Result<Self, Self::Error> {
+      match val {
+        ValueRef::Integer(n) => {
+          match n.as_f64() {
+            Some(i) => Ok(i),
+            None => Err(val)
+          }
+        }
+        ValueRef::F32(n) => Ok(From::from(n)),
+        ValueRef::F64(n) => Ok(n),
+        v => Err(v),
+      }
+  }
+}
+
+impl<'a> TryFrom<ValueRef<'a>> for &'a str {
+  type Error = ValueRef<'a>;
+
+  fn try_from(val: ValueRef<'a>) -> Result<Self, Self::Error> {
+    match val {
+      ValueRef::String(Utf8StringRef { s: Ok(s) }) => Ok(s),
+      _ => Err(val)
+    }
+  }
+}
+
+impl<'a> TryFrom<ValueRef<'a>> for String {
+  type Error = ValueRef<'a>;
+
+  fn try_from(val: ValueRef<'a>) -> Result<Self, Self::Error> {
+    match val {
+      ValueRef::String(Utf8StringRef { s: Ok(s) }) => Ok(s.into()),
+      _ => Err(val)
+    }
+  }
+}
+
 // The following impl was left out intentionally, see
 // https://github.com/3Hren/msgpack-rust/pull/228#discussion_r359513925
 /*
@@ -1576,3 +2901,114 @@ impl<'a> Display for ValueRef<'a> {
         }
     }
 }
+
+/// A number of any type, see [`Value::numeric_eq`].
+#[derive(Clone, Copy)]
+enum Number {
+    Int(Integer),
+    Float(f64),
+}
+
+impl Number {
+    fn numeric_eq(self, other: Number) -> bool {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => a == b,
+            (Number::Float(a), Number::Float(b)) => a == b,
+            (Number::Int(n), Number::Float(f)) | (Number::Float(f), Number::Int(n)) => {
+                // Every integer in `-(2^63)..2^64` converts to `i128` exactly, and so does every
+                // integral float in that range.
+                if f.fract() != 0.0 || !(-9223372036854775808.0..18446744073709551616.0).contains(&f) {
+                    return false;
+                }
+                let n = match n.n {
+                    IntPriv::PosInt(n) => n as i128,
+                    IntPriv::NegInt(n) => n as i128,
+                };
+                n == f as i128
+            }
+        }
+    }
+}
+
+/// Implements the total order documented on `Value`.
+macro_rules! impl_total_order {
+    ($($ty:ident $(<$lt:lifetime>)?),*) => {
+        $(
+            impl $(<$lt>)? $ty $(<$lt>)? {
+                /// Returns the position of the type in the total order.
+                #[inline]
+                fn type_rank(&self) -> u8 {
+                    match *self {
+                        $ty::Nil => 0,
+                        $ty::Boolean(..) => 1,
+                        $ty::Integer(..) => 2,
+                        $ty::F32(..) => 3,
+                        $ty::F64(..) => 4,
+                        $ty::String(..) => 5,
+                        $ty::Binary(..) => 6,
+                        $ty::Array(..) => 7,
+                        $ty::Map(..) => 8,
+                        $ty::Ext(..) => 9,
+                    }
+                }
+            }
+
+            impl $(<$lt>)? PartialEq for $ty $(<$lt>)? {
+                #[inline]
+                fn eq(&self, other: &Self) -> bool {
+                    self.cmp(other) == Ordering::Equal
+                }
+            }
+
+            impl $(<$lt>)? Eq for $ty $(<$lt>)? {}
+
+            impl $(<$lt>)? PartialOrd for $ty $(<$lt>)? {
+                #[inline]
+                fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl $(<$lt>)? Ord for $ty $(<$lt>)? {
+                fn cmp(&self, other: &Self) -> Ordering {
+                    match (self, other) {
+                        (&$ty::Nil, &$ty::Nil) => Ordering::Equal,
+                        (&$ty::Boolean(a), &$ty::Boolean(b)) => a.cmp(&b),
+                        (&$ty::Integer(a), &$ty::Integer(b)) => a.cmp(&b),
+                        (&$ty::F32(a), &$ty::F32(b)) => a.total_cmp(&b),
+                        (&$ty::F64(a), &$ty::F64(b)) => a.total_cmp(&b),
+                        (&$ty::String(ref a), &$ty::String(ref b)) => a.cmp(b),
+                        (&$ty::Binary(ref a), &$ty::Binary(ref b)) => a.cmp(b),
+                        (&$ty::Array(ref a), &$ty::Array(ref b)) => a.cmp(b),
+                        (&$ty::Map(ref a), &$ty::Map(ref b)) => a.cmp(b),
+                        (&$ty::Ext(at, ref a), &$ty::Ext(bt, ref b)) => at.cmp(&bt).then_with(|| a.cmp(b)),
+                        _ => self.type_rank().cmp(&other.type_rank()),
+                    }
+                }
+            }
+
+            impl $(<$lt>)? Hash for $ty $(<$lt>)? {
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    self.type_rank().hash(state);
+                    match *self {
+                        $ty::Nil => {}
+                        $ty::Boolean(val) => val.hash(state),
+                        $ty::Integer(val) => val.hash(state),
+                        $ty::F32(val) => val.to_bits().hash(state),
+                        $ty::F64(val) => val.to_bits().hash(state),
+                        $ty::String(ref val) => val.hash(state),
+                        $ty::Binary(ref val) => val.hash(state),
+                        $ty::Array(ref val) => val.hash(state),
+                        $ty::Map(ref val) => val.hash(state),
+                        $ty::Ext(ty, ref val) => {
+                            ty.hash(state);
+                            val.hash(state);
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_total_order!(Value, ValueRef<'a>);
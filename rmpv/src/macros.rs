@@ -0,0 +1,196 @@
+/// Constructs a `Value` from a literal in a JSON-like syntax.
+///
+/// - `nil` is Nil.
+/// - `[...]` is an Array and `{key: value, ...}` is a Map, where keys can be any value.
+/// - `bin(bytes)` is a Binary, from anything that converts into a `Vec<u8>`.
+/// - `ext(tag, bytes)` is an Ext, from an `i8` tag and anything that converts into a `Vec<u8>`.
+/// - Any other Rust expression is converted with `Value::from`, so that booleans, numbers,
+///   strings and other `Value`s can be interpolated.
+///
+/// Map entries keep their order and duplicate keys are kept as written.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{msgpack, Value};
+///
+/// let name = "John";
+/// let val = msgpack!({
+///     "name": name,
+///     "age": 42,
+///     "tags": ["a", "b"],
+///     1: nil,
+///     "raw": bin(vec![1, 2, 3]),
+///     "time": ext(-1, vec![0; 4]),
+/// });
+///
+/// assert_eq!(Value::Map(vec![
+///     (Value::from("name"), Value::from("John")),
+///     (Value::from("age"), Value::from(42)),
+///     (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::from("b")])),
+///     (Value::from(1), Value::Nil),
+///     (Value::from("raw"), Value::Binary(vec![1, 2, 3])),
+///     (Value::from("time"), Value::Ext(-1, vec![0; 4])),
+/// ]), val);
+/// ```
+#[macro_export]
+macro_rules! msgpack {
+    ($($tt:tt)+) => {
+        $crate::__msgpack_internal!(Value, $($tt)+)
+    };
+}
+
+/// Constructs a `ValueRef` from a literal, like [`msgpack!`] does for `Value`.
+///
+/// `bin(bytes)` and `ext(tag, bytes)` borrow a `&[u8]`, and other expressions are converted with
+/// `ValueRef::from`.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{msgpack_ref, ValueRef};
+///
+/// let data = vec![1, 2, 3];
+/// let val = msgpack_ref!([nil, true, "text", bin(&data), {"n": -1}]);
+///
+/// assert_eq!(ValueRef::Array(vec![
+///     ValueRef::Nil,
+///     ValueRef::Boolean(true),
+///     ValueRef::from("text"),
+///     ValueRef::Binary(&[1, 2, 3]),
+///     ValueRef::Map(vec![(ValueRef::from("n"), ValueRef::from(-1))]),
+/// ]), val);
+/// ```
+#[macro_export]
+macro_rules! msgpack_ref {
+    ($($tt:tt)+) => {
+        $crate::__msgpack_internal!(ValueRef, $($tt)+)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __msgpack_internal {
+    // Arrays are munched element by element into a list of expressions.
+
+    (@array $ty:ident [$($elems:expr,)*]) => {
+        vec![$($elems,)*]
+    };
+
+    (@array $ty:ident [$($elems:expr),*]) => {
+        vec![$($elems),*]
+    };
+
+    (@array $ty:ident [$($elems:expr,)*] nil $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array $ty [$($elems,)* $crate::__msgpack_internal!($ty, nil)] $($rest)*)
+    };
+
+    (@array $ty:ident [$($elems:expr,)*] bin($($bin:tt)+) $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array $ty [$($elems,)* $crate::__msgpack_internal!($ty, bin($($bin)+))] $($rest)*)
+    };
+
+    (@array $ty:ident [$($elems:expr,)*] ext($($ext:tt)+) $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array $ty [$($elems,)* $crate::__msgpack_internal!($ty, ext($($ext)+))] $($rest)*)
+    };
+
+    (@array $ty:ident [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array $ty [$($elems,)* $crate::__msgpack_internal!($ty, [$($array)*])] $($rest)*)
+    };
+
+    (@array $ty:ident [$($elems:expr,)*] {$($map:tt)*} $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array $ty [$($elems,)* $crate::__msgpack_internal!($ty, {$($map)*})] $($rest)*)
+    };
+
+    (@array $ty:ident [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array $ty [$($elems,)* $crate::__msgpack_internal!($ty, $next),] $($rest)*)
+    };
+
+    (@array $ty:ident [$($elems:expr,)*] $last:expr) => {
+        $crate::__msgpack_internal!(@array $ty [$($elems,)* $crate::__msgpack_internal!($ty, $last)])
+    };
+
+    (@array $ty:ident [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@array $ty [$($elems,)*] $($rest)*)
+    };
+
+    // Maps are munched entry by entry, collecting the tokens of a key up to the colon, as an
+    // expression can't be followed by one.
+
+    (@map $ty:ident [$($entries:expr,)*] ()) => {
+        vec![$($entries,)*]
+    };
+
+    (@map $ty:ident [$($entries:expr,)*] () , $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@map $ty [$($entries,)*] () $($rest)*)
+    };
+
+    (@map $ty:ident [$($entries:expr,)*] ($($key:tt)+) : nil $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@map $ty [$($entries,)* $crate::__msgpack_internal!(@entry $ty ($($key)+) nil),] () $($rest)*)
+    };
+
+    (@map $ty:ident [$($entries:expr,)*] ($($key:tt)+) : bin($($bin:tt)+) $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@map $ty [$($entries,)* $crate::__msgpack_internal!(@entry $ty ($($key)+) bin($($bin)+)),] () $($rest)*)
+    };
+
+    (@map $ty:ident [$($entries:expr,)*] ($($key:tt)+) : ext($($ext:tt)+) $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@map $ty [$($entries,)* $crate::__msgpack_internal!(@entry $ty ($($key)+) ext($($ext)+)),] () $($rest)*)
+    };
+
+    (@map $ty:ident [$($entries:expr,)*] ($($key:tt)+) : [$($array:tt)*] $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@map $ty [$($entries,)* $crate::__msgpack_internal!(@entry $ty ($($key)+) [$($array)*]),] () $($rest)*)
+    };
+
+    (@map $ty:ident [$($entries:expr,)*] ($($key:tt)+) : {$($map:tt)*} $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@map $ty [$($entries,)* $crate::__msgpack_internal!(@entry $ty ($($key)+) {$($map)*}),] () $($rest)*)
+    };
+
+    (@map $ty:ident [$($entries:expr,)*] ($($key:tt)+) : $val:expr, $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@map $ty [$($entries,)* $crate::__msgpack_internal!(@entry $ty ($($key)+) $val),] () $($rest)*)
+    };
+
+    (@map $ty:ident [$($entries:expr,)*] ($($key:tt)+) : $val:expr) => {
+        $crate::__msgpack_internal!(@map $ty [$($entries,)* $crate::__msgpack_internal!(@entry $ty ($($key)+) $val),] ())
+    };
+
+    (@map $ty:ident [$($entries:expr,)*] ($($key:tt)*) $next:tt $($rest:tt)*) => {
+        $crate::__msgpack_internal!(@map $ty [$($entries,)*] ($($key)* $next) $($rest)*)
+    };
+
+    (@entry $ty:ident ($($key:tt)+) $($val:tt)+) => {
+        ($crate::__msgpack_internal!($ty, $($key)+), $crate::__msgpack_internal!($ty, $($val)+))
+    };
+
+    // Single values.
+
+    ($ty:ident, nil) => {
+        $crate::$ty::Nil
+    };
+
+    (Value, bin($bin:expr)) => {
+        $crate::Value::Binary(::std::convert::Into::into($bin))
+    };
+
+    (ValueRef, bin($bin:expr)) => {
+        $crate::ValueRef::Binary($bin)
+    };
+
+    (Value, ext($tag:expr, $data:expr)) => {
+        $crate::Value::Ext($tag, ::std::convert::Into::into($data))
+    };
+
+    (ValueRef, ext($tag:expr, $data:expr)) => {
+        $crate::ValueRef::Ext($tag, $data)
+    };
+
+    ($ty:ident, [$($array:tt)*]) => {
+        $crate::$ty::Array($crate::__msgpack_internal!(@array $ty [] $($array)*))
+    };
+
+    ($ty:ident, {$($map:tt)*}) => {
+        $crate::$ty::Map($crate::__msgpack_internal!(@map $ty [] () $($map)*))
+    };
+
+    ($ty:ident, $other:expr) => {
+        $crate::$ty::from($other)
+    };
+}
@@ -0,0 +1,116 @@
+use rmpv::decode::read_value;
+use rmpv::encode::{write_value, write_value_canonical, CanonicalError};
+use rmpv::{msgpack, Value};
+
+fn canonical_bytes(val: &Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_value_canonical(&mut buf, val).unwrap();
+    buf
+}
+
+#[test]
+fn pass_canonicalize_sorts_by_encoded_key() {
+    // Sorted by encoding: 1 is 0x01, "a" is 0xa161, nil is 0xc0 and -1 is 0xff.
+    let mut val = msgpack!({-1: 0, nil: 1, "a": 2, 1: {"y": 3, "x": [{"q": 4, "p": 5}]}});
+    val.canonicalize().unwrap();
+
+    assert_eq!(msgpack!({1: {"x": [{"p": 5, "q": 4}], "y": 3}, "a": 2, nil: 1, -1: 0}), val);
+}
+
+#[test]
+fn pass_canonicalize_keys() {
+    let mut val = msgpack!({{"b": 1, "a": 2}: "x"});
+    val.canonicalize().unwrap();
+
+    assert_eq!(msgpack!({{"a": 2, "b": 1}: "x"}), val);
+}
+
+#[test]
+fn pass_canonicalize_normalizes_nan() {
+    let mut val = Value::Array(vec![
+        Value::F64(f64::from_bits(0xfff8_0000_0000_0001)),
+        Value::F32(f32::from_bits(0x7fc0_0001)),
+        Value::F64(-0.0),
+    ]);
+    val.canonicalize().unwrap();
+
+    assert_eq!(Value::Array(vec![
+        Value::F64(f64::NAN),
+        Value::F32(f32::NAN),
+        Value::F64(-0.0),
+    ]), val);
+}
+
+#[test]
+fn fail_canonicalize_duplicate_key() {
+    let mut val = msgpack!([{"b": 1, "a": 2, "b": 3}]);
+    let err = val.canonicalize().unwrap_err();
+
+    assert_eq!(&Value::from("b"), err.key());
+    assert_eq!("duplicate map key \"b\"", err.to_string());
+    // The entries are kept, even though their order is undefined.
+    assert_eq!(3, val[0].as_map().unwrap().len());
+}
+
+#[test]
+fn fail_canonicalize_nested_duplicate_key_keeps_siblings() {
+    let mut val = msgpack!({"keep": 1, "inner": {"a": 1, "a": 2}, "other": [f64::NAN]});
+    let err = val.canonicalize().unwrap_err();
+
+    assert_eq!(&Value::from("a"), err.key());
+    let map = val.as_map().unwrap();
+    assert_eq!(3, map.len());
+    assert_eq!(msgpack!({"keep": 1}), Value::Map(map[..1].to_vec()));
+    assert_eq!(2, map[1].1.as_map().unwrap().len());
+    assert_eq!(Value::from("other"), map[2].0);
+}
+
+#[test]
+fn pass_write_value_canonical() {
+    let a = msgpack!({"b": [1, {2: nil, 1: nil}], "a": f64::NAN});
+    let nan = Value::F64(f64::from_bits(0x7ff8_0000_0000_0002));
+    let b = msgpack!({"a": nan, "b": [1, {1: nil, 2: nil}]});
+
+    assert_eq!(canonical_bytes(&a), canonical_bytes(&b));
+    assert_eq!(msgpack!({"b": [1, {2: nil, 1: nil}], "a": f64::NAN}), a);
+
+    let mut val = a.clone();
+    val.canonicalize().unwrap();
+    let mut buf = Vec::new();
+    write_value(&mut buf, &val).unwrap();
+    assert_eq!(buf, canonical_bytes(&a));
+    assert_eq!(val, read_value(&mut &buf[..]).unwrap());
+}
+
+#[test]
+fn pass_write_value_canonical_invalid_utf8_as_str() {
+    let val = read_value(&mut &[0xa2, 0xc3, 0x28][..]).unwrap();
+    let buf = canonical_bytes(&val);
+
+    assert_eq!(vec![0xa2, 0xc3, 0x28], buf);
+    assert_ne!(buf, canonical_bytes(&Value::Binary(vec![0xc3, 0x28])));
+}
+
+#[test]
+fn fail_write_value_canonical_duplicate_key() {
+    let val = msgpack!({1: {nil: 1, nil: 2}});
+
+    match write_value_canonical(&mut Vec::new(), &val) {
+        Err(CanonicalError::DuplicateKey(key)) => assert_eq!(Value::Nil, key),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_canonical_hash() {
+    assert_eq!(0xaf64_7d4c_8602_fe1f, Value::Nil.canonical_hash().unwrap());
+    assert_eq!(0x5fe7_8f36_3860_6359, msgpack!({"b": 2, "a": 1}).canonical_hash().unwrap());
+    assert_eq!(0x5fe7_8f36_3860_6359, msgpack!({"a": 1, "b": 2}).canonical_hash().unwrap());
+
+    assert_ne!(msgpack!(1).canonical_hash().unwrap(), msgpack!(1.0).canonical_hash().unwrap());
+    assert_ne!(msgpack!([1, 2]).canonical_hash().unwrap(),
+        msgpack!([2, 1]).canonical_hash().unwrap());
+
+    let err = msgpack!({"a": 1, "a": 1}).canonical_hash().unwrap_err();
+    assert_eq!(Value::from("a"), err.into_key());
+}
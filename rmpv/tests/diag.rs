@@ -0,0 +1,168 @@
+#[macro_use]
+extern crate quickcheck;
+
+use rmpv::decode::read_value;
+use rmpv::diag::{self, Printer};
+use rmpv::{msgpack, Value};
+
+fn round_trip(val: &Value) -> Value {
+    let compact = diag::from_str(&diag::to_string(val)).unwrap();
+    let pretty = diag::from_str(&diag::to_string_pretty(val)).unwrap();
+    assert_eq!(compact, pretty);
+    compact
+}
+
+quickcheck! {
+    fn round_trip_uint(xs: u64) -> bool {
+        Value::from(xs) == round_trip(&Value::from(xs))
+    }
+
+    fn round_trip_sint(xs: i64) -> bool {
+        Value::from(xs) == round_trip(&Value::from(xs))
+    }
+
+    fn round_trip_f32(xs: f32) -> bool {
+        Value::F32(xs) == round_trip(&Value::F32(xs))
+    }
+
+    fn round_trip_f64(xs: f64) -> bool {
+        Value::F64(xs) == round_trip(&Value::F64(xs))
+    }
+
+    fn round_trip_str(xs: String) -> bool {
+        Value::from(xs.clone()) == round_trip(&Value::from(xs))
+    }
+
+    fn round_trip_bin(xs: Vec<u8>) -> bool {
+        Value::Binary(xs.clone()) == round_trip(&Value::Binary(xs))
+    }
+
+    fn round_trip_ext(ty: i8, xs: Vec<u8>) -> bool {
+        Value::Ext(ty, xs.clone()) == round_trip(&Value::Ext(ty, xs))
+    }
+}
+
+#[test]
+fn pass_print_compact() {
+    let val = msgpack!([nil, true, -1, 1.5, Value::F32(-0.0), "a\"b\\c\n\u{1}", bin(vec![]), {}, []]);
+    assert_eq!(r#"[nil, true, -1, 1.5, f32(-0.0), "a\"b\\c\n\u{1}", h'', {}, []]"#,
+        diag::to_string(&val));
+    assert_eq!(val, round_trip(&val));
+}
+
+#[test]
+fn pass_print_pretty() {
+    let val = msgpack!({"a": [1, {}], [1]: {"b": ext(5, vec![1, 2])}});
+    let text = "\
+{
+  \"a\": [
+    1,
+    {}
+  ],
+  [
+    1
+  ]: {
+    \"b\": ext(5, h'0102')
+  }
+}";
+    assert_eq!(text, diag::to_string_pretty(&val));
+    assert_eq!(val, round_trip(&val));
+}
+
+#[test]
+fn pass_print_floats() {
+    let vals = vec![
+        Value::F64(1.0),
+        Value::F64(1e300),
+        Value::F64(-1e-300),
+        Value::F64(f64::INFINITY),
+        Value::F64(f64::NEG_INFINITY),
+        Value::F64(f64::NAN),
+        Value::F64(f64::from_bits(0xfff8_0000_0000_0001)),
+        Value::F32(f32::MAX),
+        Value::F32(f32::NEG_INFINITY),
+        Value::F32(f32::from_bits(0x7fc0_0001)),
+    ];
+    let text = "[1.0, 1e300, -1e-300, Infinity, -Infinity, NaN, NaN(0xfff8000000000001), \
+        f32(3.4028235e38), f32(-Infinity), f32(NaN(0x7fc00001))]";
+
+    assert_eq!(text, diag::to_string(&Value::Array(vals.clone())));
+    assert_eq!(Value::Array(vals), round_trip(&diag::from_str(text).unwrap()));
+}
+
+#[test]
+fn pass_invalid_utf8_string() {
+    let val = read_value(&mut &[0xa2, 0xc3, 0x28][..]).unwrap();
+
+    assert_eq!("str(h'c328')", diag::to_string(&val));
+    assert_eq!(val, round_trip(&val));
+    assert_eq!(Value::from("a"), diag::from_str("str(h'61')").unwrap());
+}
+
+#[test]
+fn pass_integer_bounds() {
+    let val = msgpack!([u64::MAX, i64::MIN, 0]);
+    assert_eq!("[18446744073709551615, -9223372036854775808, 0]", diag::to_string(&val));
+    assert_eq!(val, round_trip(&val));
+}
+
+#[test]
+fn pass_print_truncated() {
+    let val = msgpack!(["añbc", "ab", bin(vec![1, 2, 3]), ext(1, vec![1, 2, 3])]);
+    let printer = Printer::new().with_truncation(2);
+
+    let text = printer.print(&val);
+    assert_eq!(r#"["añ"..., "ab", h'0102'..., ext(1, h'0102'...)]"#, text);
+
+    let err = diag::from_str(&text).unwrap_err();
+    assert_eq!("truncated value at line 1 column 6", err.to_string());
+    let err = diag::from_str("ext(1, h'0102'...)").unwrap_err();
+    assert_eq!("truncated value at line 1 column 15", err.to_string());
+}
+
+#[test]
+fn pass_parse_whitespace_and_trailing_commas() {
+    let text = " { \"a\" : [ 1 , 2 , ] ,\n\t1:h' 01 02 ' , } ";
+    assert_eq!(msgpack!({"a": [1, 2], 1: bin(vec![1, 2])}), diag::from_str(text).unwrap());
+    assert_eq!(msgpack!("\u{1f600}\t"), diag::from_str(r#""\u{1f600}\t""#).unwrap());
+}
+
+#[test]
+fn fail_parse() {
+    let cases = [
+        ("", "unexpected end of input at line 1 column 1"),
+        ("[1 2]", "expected `,` or `]` at line 1 column 4"),
+        ("{1 2}", "expected `:` at line 1 column 4"),
+        ("{\n  1: 2\n  3: 4}", "expected `,` or `}` at line 3 column 3"),
+        ("\"abc", "unterminated string at line 1 column 5"),
+        ("\"\\q\"", "invalid escape at line 1 column 3"),
+        ("\"\\u{d800}\"", "invalid unicode escape at line 1 column 9"),
+        ("18446744073709551616", "integer out of range at line 1 column 1"),
+        ("-9223372036854775809", "integer out of range at line 1 column 1"),
+        ("1.2.3", "invalid number at line 1 column 1"),
+        ("1e400", "float out of range at line 1 column 1"),
+        ("[-1e400]", "float out of range at line 1 column 2"),
+        ("h'0'", "expected a pair of hex digits at line 1 column 3"),
+        ("ext(128, h'')", "invalid ext type at line 1 column 5"),
+        ("f32(x)", "expected a float at line 1 column 5"),
+        ("f32(1e400)", "float out of range at line 1 column 5"),
+        ("f32(-3.5e38)", "float out of range at line 1 column 5"),
+        ("NaN(0x0)", "NaN bits don't encode a NaN at line 1 column 1"),
+        ("f32(NaN(0x7ff8000000000000))", "NaN bits don't encode a NaN at line 1 column 5"),
+        ("null", "expected a value at line 1 column 1"),
+        ("nil nil", "trailing characters at line 1 column 5"),
+    ];
+
+    for (text, msg) in cases {
+        assert_eq!(msg, diag::from_str(text).unwrap_err().to_string(), "{}", text);
+    }
+}
+
+#[test]
+fn fail_parse_depth_limit() {
+    let text = "[".repeat(2000) + &"]".repeat(2000);
+    let err = diag::from_str(&text).unwrap_err();
+
+    assert_eq!(1, err.line());
+    assert_eq!(1025, err.column());
+}
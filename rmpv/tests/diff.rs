@@ -0,0 +1,215 @@
+use rmpv::{apply_patch, diff, msgpack, Operation, PatchError, PathSegment, Value};
+
+fn assert_round_trip(a: &Value, b: &Value) -> Vec<Operation> {
+    let patch = diff(a, b);
+    let mut val = a.clone();
+    apply_patch(&mut val, &patch).unwrap();
+    assert_eq!(*b, val);
+    patch
+}
+
+#[test]
+fn pass_diff_equal() {
+    let val = msgpack!({"a": [1, 2, {"b": nil}], 1: bin(vec![0])});
+    assert_eq!(Vec::<Operation>::new(), diff(&val, &val));
+}
+
+#[test]
+fn pass_diff_non_string_keys() {
+    let a = msgpack!({1: {[1, 2]: "x"}, nil: true});
+    let b = msgpack!({1: {[1, 2]: "y"}, nil: true});
+
+    let patch = assert_round_trip(&a, &b);
+    assert_eq!(vec![Operation::Replace {
+        path: vec![PathSegment::Key(msgpack!(1)), PathSegment::Key(msgpack!([1, 2]))],
+        value: msgpack!("y"),
+    }], patch);
+}
+
+#[test]
+fn pass_diff_arrays() {
+    let a = msgpack!([1, 2, 3, 4]);
+    let b = msgpack!([1, 5]);
+
+    let patch = assert_round_trip(&a, &b);
+    assert_eq!(vec![
+        Operation::Replace { path: vec![PathSegment::Index(1)], value: msgpack!(5) },
+        Operation::Remove { path: vec![PathSegment::Index(3)] },
+        Operation::Remove { path: vec![PathSegment::Index(2)] },
+    ], patch);
+
+    assert_round_trip(&b, &a);
+    assert_round_trip(&msgpack!([]), &a);
+}
+
+#[test]
+fn pass_diff_type_change() {
+    let a = msgpack!({"a": [1]});
+    let b = msgpack!({"a": {"0": 1}});
+
+    let patch = assert_round_trip(&a, &b);
+    assert_eq!(vec![Operation::Replace {
+        path: vec![PathSegment::from("a")],
+        value: msgpack!({"0": 1}),
+    }], patch);
+
+    // Integers and floats are different types, even if numerically equal.
+    assert_eq!(1, assert_round_trip(&msgpack!(1), &Value::F64(1.0)).len());
+}
+
+#[test]
+fn pass_diff_map_add_and_remove() {
+    let a = msgpack!({"a": 1, "b": 2, "c": 3});
+    let b = msgpack!({"a": 1, "c": 4, "d": 5});
+
+    let patch = assert_round_trip(&a, &b);
+    assert_eq!(vec![
+        Operation::Remove { path: vec![PathSegment::from("b")] },
+        Operation::Replace { path: vec![PathSegment::from("c")], value: msgpack!(4) },
+        Operation::Add { path: vec![PathSegment::from("d")], value: msgpack!(5) },
+    ], patch);
+}
+
+#[test]
+fn pass_diff_map_reordered_replaces_map() {
+    let a = msgpack!({"a": 1, "b": 2});
+    let b = msgpack!({"b": 2, "a": 1});
+
+    let patch = assert_round_trip(&a, &b);
+    assert_eq!(vec![Operation::Replace { path: vec![], value: b.clone() }], patch);
+
+    // A new key that isn't last can't be appended either.
+    assert_round_trip(&a, &msgpack!({"a": 1, "c": 3, "b": 2}));
+}
+
+#[test]
+fn pass_diff_map_duplicate_keys_replaces_map() {
+    let a = msgpack!({"a": 1, "a": 2});
+    let b = msgpack!({"a": 1, "a": 3});
+
+    let patch = assert_round_trip(&a, &b);
+    assert_eq!(vec![Operation::Replace { path: vec![], value: b.clone() }], patch);
+}
+
+#[test]
+fn pass_diff_large_map() {
+    let a = Value::Map((0..20_000).map(|i| (Value::from(i), Value::from(i))).collect());
+    let mut b = a.clone();
+    b.as_map_mut().unwrap()[12_345].1 = msgpack!("x");
+    b.as_map_mut().unwrap().push((msgpack!(-1), Value::Nil));
+
+    let patch = assert_round_trip(&a, &b);
+    assert_eq!(vec![
+        Operation::Replace { path: vec![PathSegment::Key(msgpack!(12_345))], value: msgpack!("x") },
+        Operation::Add { path: vec![PathSegment::Key(msgpack!(-1))], value: Value::Nil },
+    ], patch);
+
+    // A duplicate key far apart from its twin is still found.
+    let mut c = a.clone();
+    c.as_map_mut().unwrap()[19_999].0 = msgpack!(0);
+    let patch = assert_round_trip(&c, &a);
+    assert_eq!(vec![Operation::Replace { path: vec![], value: a.clone() }], patch);
+}
+
+#[test]
+fn pass_apply_patch_add() {
+    let mut val = msgpack!({"a": [1, 3], "b": 0});
+
+    apply_patch(&mut val, &[
+        Operation::Add { path: vec!["a".into(), 1.into()], value: msgpack!(2) },
+        Operation::Add { path: vec!["a".into(), 3.into()], value: msgpack!(4) },
+        Operation::Add { path: vec!["b".into()], value: msgpack!(1) },
+        Operation::Add { path: vec![PathSegment::Key(msgpack!(-1))], value: msgpack!(nil) },
+    ]).unwrap();
+
+    assert_eq!(msgpack!({"a": [1, 2, 3, 4], "b": 1, -1: nil}), val);
+}
+
+#[test]
+fn pass_apply_patch_root() {
+    let mut val = msgpack!([1]);
+
+    apply_patch(&mut val, &[Operation::Replace { path: vec![], value: msgpack!("x") }]).unwrap();
+    assert_eq!(msgpack!("x"), val);
+
+    apply_patch(&mut val, &[Operation::Remove { path: vec![] }]).unwrap();
+    assert_eq!(Value::Nil, val);
+}
+
+#[test]
+fn fail_apply_patch_not_found() {
+    let mut val = msgpack!({"a": [1, 2]});
+
+    let path = vec![PathSegment::from("a"), PathSegment::Index(2)];
+    let err = apply_patch(&mut val, &[Operation::Replace { path: path.clone(), value: msgpack!(3) }]);
+    assert_eq!(Err(PatchError::NotFound(path)), err);
+
+    let path = vec![PathSegment::from("a"), PathSegment::Index(3)];
+    let err = apply_patch(&mut val, &[Operation::Add { path: path.clone(), value: msgpack!(3) }]);
+    assert_eq!(Err(PatchError::NotFound(path)), err);
+
+    let path = vec![PathSegment::from("b"), PathSegment::Index(0)];
+    let err = apply_patch(&mut val, &[Operation::Remove { path }]);
+    assert_eq!(Err(PatchError::NotFound(vec![PathSegment::from("b")])), err);
+    assert_eq!("no value at path [\"b\"]", err.unwrap_err().to_string());
+}
+
+#[test]
+fn fail_apply_patch_type_mismatch() {
+    let mut val = msgpack!({"a": [1, 2], "b": 0});
+
+    let path = vec![PathSegment::from("a"), PathSegment::from("x")];
+    let err = apply_patch(&mut val, &[Operation::Remove { path: path.clone() }]);
+    assert_eq!(Err(PatchError::TypeMismatch(path)), err);
+
+    let path = vec![PathSegment::Index(0)];
+    let err = apply_patch(&mut val, &[Operation::Add { path: path.clone(), value: msgpack!(1) }]);
+    assert_eq!(Err(PatchError::TypeMismatch(path)), err);
+
+    let path = vec![PathSegment::from("b"), PathSegment::Index(0)];
+    let err = apply_patch(&mut val, &[Operation::Replace { path: path.clone(), value: msgpack!(1) }]);
+    assert_eq!(Err(PatchError::TypeMismatch(path)), err);
+    assert_eq!("type mismatch at path [\"b\"][0]", err.unwrap_err().to_string());
+}
+
+#[test]
+fn fail_apply_patch_is_atomic() {
+    let mut val = msgpack!({"a": 1});
+
+    let err = apply_patch(&mut val, &[
+        Operation::Replace { path: vec!["a".into()], value: msgpack!(2) },
+        Operation::Remove { path: vec!["b".into()] },
+    ]);
+
+    assert!(err.is_err());
+    assert_eq!(msgpack!({"a": 1}), val);
+}
+
+#[test]
+fn pass_merge() {
+    let mut val = msgpack!({"a": "b", "c": {"d": "e", "f": "g"}, 1: [1, 2]});
+    val.merge(msgpack!({"a": "z", "c": {"f": nil}, 1: [3]}));
+
+    assert_eq!(msgpack!({"a": "z", "c": {"d": "e"}, 1: [3]}), val);
+}
+
+#[test]
+fn pass_merge_nil_removes_duplicate_keys() {
+    let mut val = msgpack!({"a": 1, "b": 2, "a": 3});
+    val.merge(msgpack!({"a": nil, "c": nil}));
+
+    assert_eq!(msgpack!({"b": 2}), val);
+}
+
+#[test]
+fn pass_merge_non_map() {
+    let mut val = msgpack!([1, 2]);
+    val.merge(msgpack!({"a": {"b": nil, "c": 1}}));
+    assert_eq!(msgpack!({"a": {"c": 1}}), val);
+
+    val.merge(msgpack!("text"));
+    assert_eq!(msgpack!("text"), val);
+
+    val.merge(Value::Nil);
+    assert_eq!(Value::Nil, val);
+}
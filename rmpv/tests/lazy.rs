@@ -0,0 +1,190 @@
+use std::io::ErrorKind;
+
+use rmpv::decode::{read_value_ref, Error};
+use rmpv::encode::write_value;
+use rmpv::{msgpack, LazyValue, Value};
+
+fn encode(val: &Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_value(&mut buf, val).unwrap();
+    buf
+}
+
+#[test]
+fn pass_lazy_get_and_index() {
+    let buf = encode(&msgpack!({
+        "name": "doc",
+        "items": [{"id": 1}, {"id": 2, "tags": ["x"]}],
+        -1: 1.5,
+        [1, 2]: true,
+    }));
+    let val = LazyValue::new(&buf).unwrap();
+
+    assert!(val.is_map());
+    assert_eq!(Some(4), val.len());
+    assert_eq!(Some(false), val.is_empty());
+    assert_eq!(Some("doc"), val.get("name").unwrap().as_str());
+    assert_eq!(Some(1.5), val.get(-1).unwrap().as_f64());
+    assert_eq!(Some(true), val.get(&msgpack!([1, 2])).unwrap().as_bool());
+    assert!(val.get("missing").is_none());
+    assert!(val.index(0).is_none());
+
+    let items = val.get("items").unwrap();
+    assert!(items.is_array());
+    assert_eq!(Some(2), items.index(1).unwrap().get("id").unwrap().as_u64());
+    assert_eq!(Some(2), items.get(1).unwrap().get("id").unwrap().as_i64());
+    let tags = items.index(1).unwrap().get("tags").unwrap();
+    assert_eq!(Some("x"), tags.index(0).unwrap().as_str());
+    assert!(items.index(2).is_none());
+    assert!(items.get("id").is_none());
+}
+
+#[test]
+fn pass_lazy_iteration() {
+    let buf = encode(&msgpack!({"a": [1, 2, 3], "b": {}}));
+    let val = LazyValue::new(&buf).unwrap();
+
+    let keys: Vec<_> = val.as_map().unwrap().iter().map(|(k, _)| k.as_str().unwrap()).collect();
+    assert_eq!(vec!["a", "b"], keys);
+
+    let elems = val.get("a").unwrap().as_array().unwrap();
+    let sum: u64 = elems.iter().map(|v| v.as_u64().unwrap()).sum();
+    assert_eq!(6, sum);
+
+    assert_eq!(Some(true), val.get("b").unwrap().is_empty());
+    assert!(val.as_array().is_none());
+    assert!(val.get("a").unwrap().as_map().is_none());
+}
+
+#[test]
+fn pass_lazy_memoizes_containers() {
+    let buf = encode(&msgpack!({"a": {"b": [1, 2]}}));
+    let val = LazyValue::new(&buf).unwrap();
+
+    let first = val.get("a").unwrap().get("b").unwrap();
+    let second = val.get("a").unwrap().get("b").unwrap();
+    assert!(std::ptr::eq(first, second));
+    assert!(std::ptr::eq(first.index(1).unwrap(), second.index(1).unwrap()));
+}
+
+#[test]
+fn pass_lazy_nested_containers() {
+    /// Checks that every part of the value has the bytes it would be encoded to on its own.
+    fn check(val: &LazyValue<'_>) -> usize {
+        let mut count = 1;
+        assert_eq!(encode(&val.to_value_ref().to_owned()), val.as_bytes());
+        if let Some(elems) = val.as_array() {
+            count += elems.iter().map(check).sum::<usize>();
+        } else if let Some(entries) = val.as_map() {
+            count += entries.iter().map(|(k, v)| check(k) + check(v)).sum::<usize>();
+        }
+        count
+    }
+
+    let buf = encode(&msgpack!([
+        [[], [[1]], {}],
+        {[1, [2]]: {"a": [3, {}]}, {"k": []}: [[]], "s": "t"},
+        [[[[[["deep"]]]]], 4],
+        5,
+    ]));
+    let val = LazyValue::new(&buf).unwrap();
+    assert_eq!(33, check(&val));
+
+    let deep = val.index(2).unwrap();
+    let mut inner = deep.index(0).unwrap();
+    while let Some(next) = inner.index(0) {
+        inner = next;
+    }
+    assert_eq!(Some("deep"), inner.as_str());
+    assert_eq!(Some(4), deep.index(1).unwrap().as_u64());
+    assert_eq!(Some(5), val.index(3).unwrap().as_u64());
+
+    let key = &val.index(1).unwrap().as_map().unwrap()[1].0;
+    assert_eq!(Some(0), key.get("k").unwrap().len());
+}
+
+#[test]
+fn pass_lazy_scalars() {
+    let invalid_str = [0xa2, 0xc3, 0x28];
+    let val = LazyValue::new(&invalid_str).unwrap();
+    assert_eq!(None, val.as_str());
+    assert_eq!(Some(&[0xc3, 0x28][..]), val.as_slice());
+
+    let buf = encode(&msgpack!([nil, ext(-5, vec![1, 2, 3]), bin(vec![0; 300]), "x"]));
+    let val = LazyValue::new(&buf).unwrap();
+
+    assert!(val.index(0).unwrap().is_nil());
+    assert!(!val.index(3).unwrap().is_nil());
+    assert_eq!(Some((-5, &[1, 2, 3][..])), val.index(1).unwrap().as_ext());
+    assert_eq!(Some(&[0; 300][..]), val.index(2).unwrap().as_slice());
+    assert_eq!(None, val.index(2).unwrap().len());
+    assert_eq!(None, val.index(3).unwrap().as_u64());
+    assert_eq!(None, val.as_bool());
+    assert!(!val.is_nil());
+}
+
+#[test]
+fn pass_lazy_large_encodings() {
+    // Covers the 16 and 32-bit length headers and all Ext widths.
+    let doc = msgpack!({
+        "str16": "a".repeat(300),
+        "str32": "b".repeat(70000),
+        "bin32": bin(vec![1; 70000]),
+        "array16": Value::Array(vec![Value::from(1); 20]),
+        "array32": Value::Array(vec![Value::Nil; 70000]),
+        "map16": Value::Map((0..20).map(|i| (Value::from(i), Value::from(-i))).collect()),
+        "ext": [
+            ext(1, vec![0; 1]), ext(2, vec![0; 2]), ext(3, vec![0; 4]), ext(4, vec![0; 8]),
+            ext(5, vec![0; 16]), ext(6, vec![0; 3]), ext(7, vec![0; 300]), ext(8, vec![0; 70000]),
+        ],
+        "numbers": [
+            u8::MAX, u16::MAX, u32::MAX, u64::MAX, i8::MIN, i16::MIN, i32::MIN, i64::MIN,
+            Value::F32(1.5), 2.5,
+        ],
+    });
+    let buf = encode(&doc);
+    let val = LazyValue::new(&buf).unwrap();
+
+    assert_eq!(read_value_ref(&mut &buf[..]).unwrap(), val.to_value_ref());
+    assert_eq!(Some(70000), val.get("array32").unwrap().len());
+    assert_eq!(Some(-19), val.get("map16").unwrap().get(19).unwrap().as_i64());
+    assert_eq!(Some(70000), val.get("str32").unwrap().as_str().map(str::len));
+    assert_eq!(Some(8), val.get("ext").unwrap().index(7).unwrap().as_ext().map(|(ty, _)| ty));
+    assert_eq!(Some(i64::MIN), val.get("numbers").unwrap().index(7).unwrap().as_i64());
+}
+
+#[test]
+fn pass_lazy_ignores_trailing_bytes() {
+    let mut buf = encode(&msgpack!([1, "a"]));
+    let len = buf.len();
+    buf.extend_from_slice(&[0xc0, 0xc3]);
+
+    let val = LazyValue::new(&buf).unwrap();
+    assert_eq!(&buf[..len], val.as_bytes());
+    assert_eq!(&buf[1..2], val.index(0).unwrap().as_bytes());
+}
+
+#[test]
+fn fail_lazy_truncated() {
+    let buf = encode(&msgpack!({"a": [1, "text"]}));
+
+    for len in 0..buf.len() {
+        match LazyValue::new(&buf[..len]) {
+            Err(Error::InvalidMarkerRead(err)) | Err(Error::InvalidDataRead(err)) => {
+                assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+            }
+            other => panic!("unexpected result for {} bytes: {:?}", len, other),
+        }
+    }
+}
+
+#[test]
+fn fail_lazy_depth_limit() {
+    let mut buf = vec![0x91; 2000];
+    buf.push(0xc0);
+
+    match LazyValue::new(&buf) {
+        Err(Error::DepthLimitExceeded) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,87 @@
+use rmpv::{msgpack, msgpack_ref, Value, ValueRef};
+
+#[test]
+fn msgpack_scalars() {
+    assert_eq!(Value::Nil, msgpack!(nil));
+    assert_eq!(Value::Boolean(true), msgpack!(true));
+    assert_eq!(Value::from(-5), msgpack!(-5));
+    assert_eq!(Value::F64(1.5), msgpack!(1.5));
+    assert_eq!(Value::from("text"), msgpack!("text"));
+    assert_eq!(Value::Binary(vec![1, 2]), msgpack!(bin(vec![1, 2])));
+    assert_eq!(Value::Binary(vec![1, 2]), msgpack!(bin(&[1u8, 2][..])));
+    assert_eq!(Value::Ext(3, vec![4]), msgpack!(ext(3, vec![4])));
+}
+
+#[test]
+fn msgpack_arrays() {
+    assert_eq!(Value::Array(vec![]), msgpack!([]));
+    assert_eq!(Value::Array(vec![Value::Nil]), msgpack!([nil]));
+    assert_eq!(Value::Array(vec![Value::Nil]), msgpack!([nil,]));
+    assert_eq!(
+        Value::Array(vec![
+            Value::from(1),
+            Value::Array(vec![Value::from(2), Value::Array(vec![])]),
+            Value::Map(vec![]),
+            Value::Binary(vec![0]),
+            Value::Ext(1, vec![]),
+            Value::from("x"),
+        ]),
+        msgpack!([1, [2, []], {}, bin(vec![0]), ext(1, vec![]), "x",])
+    );
+}
+
+#[test]
+fn msgpack_maps() {
+    assert_eq!(Value::Map(vec![]), msgpack!({}));
+    assert_eq!(
+        Value::Map(vec![
+            (Value::from("a"), Value::Nil),
+            (Value::Nil, Value::from(1)),
+            (Value::from(-1), Value::Array(vec![Value::from(true)])),
+            (Value::Array(vec![Value::from(1)]), Value::Map(vec![(Value::from("b"), Value::from(2))])),
+            (Value::Binary(vec![7]), Value::Binary(vec![8])),
+            (Value::Ext(1, vec![]), Value::Ext(2, vec![])),
+            (Value::from("a"), Value::from(2)),
+        ]),
+        msgpack!({
+            "a": nil,
+            nil: 1,
+            -1: [true],
+            [1]: {"b": 2},
+            bin(vec![7]): bin(vec![8]),
+            ext(1, vec![]): ext(2, vec![]),
+            "a": 1 + 1,
+        })
+    );
+}
+
+#[test]
+fn msgpack_interpolation() {
+    let key = String::from("key");
+    let inner = msgpack!([1, 2]);
+    let n: u64 = 7;
+
+    assert_eq!(
+        Value::Map(vec![
+            (Value::from("key"), Value::Array(vec![Value::from(1), Value::from(2)])),
+            (Value::from(7), Value::from(14)),
+        ]),
+        msgpack!({ key.as_str(): inner, n: n * 2 })
+    );
+}
+
+#[test]
+fn msgpack_ref_literals() {
+    let data = [1u8, 2, 3];
+    let text = String::from("text");
+
+    assert_eq!(ValueRef::Nil, msgpack_ref!(nil));
+    assert_eq!(
+        ValueRef::Map(vec![
+            (ValueRef::from("a"), ValueRef::Array(vec![ValueRef::from(1), ValueRef::Binary(&data)])),
+            (ValueRef::from(&text[..]), ValueRef::Ext(5, &data[1..])),
+        ]),
+        msgpack_ref!({ "a": [1, bin(&data)], &text[..]: ext(5, &data[1..]) })
+    );
+    assert_eq!(msgpack!([nil, {"a": bin(vec![1])}]).as_ref(), msgpack_ref!([nil, {"a": bin(&[1])}]));
+}
@@ -191,8 +191,367 @@ fn try_from_val() {
   use rmpv::Utf8String;
   use std::convert::TryInto;
 
-  assert_eq!(false, Value::Boolean(false).try_into().unwrap());
+  assert_eq!(false, TryInto::<bool>::try_into(Value::Boolean(false)).unwrap());
   assert_eq!(Utf8String::from("spook"), Value::from("spook").try_into().unwrap());
   assert_eq!(String::from("spook"), TryInto::<String>::try_into(Value::from("spook")).unwrap());
   assert_eq!(vec![0], TryInto::<Vec<u8>>::try_into(Value::Binary(vec![0u8])).unwrap());
 }
+
+#[test]
+fn get_by_key() {
+    let val = Value::Map(vec![
+        ( Value::from("a"), Value::Nil ),
+        ( Value::from(-1), Value::from("minus one") ),
+        ( Value::Array(vec![Value::from(1)]), Value::from(true) ),
+        ( Value::from("a"), Value::from("shadowed") ),
+    ]);
+
+    assert_eq!(Some(&Value::Nil), val.get("a"));
+    assert_eq!(None, val.get("b"));
+    assert_eq!(Some("minus one"), val.get_str(-1));
+    assert_eq!(Some(true), val.get_bool(&Value::Array(vec![Value::from(1)])));
+    assert_eq!(None, val.get_u64("a"));
+    assert_eq!(None, Value::from(1).get(0));
+
+    let arr = Value::Array(vec![Value::from(10), Value::from(20)]);
+    assert_eq!(Some(20), arr.get_u64(1));
+    assert_eq!(None, arr.get(2));
+    assert_eq!(None, arr.get(-1));
+    assert_eq!(None, arr.get("0"));
+}
+
+#[test]
+fn get_mut_by_key() {
+    let mut val = Value::Map(vec![( Value::from("a"), Value::Array(vec![Value::Nil]) )]);
+    *val.get_mut("a").unwrap().get_mut(0).unwrap() = Value::from(5);
+    assert_eq!(Some(5), val["a"][0].as_u64());
+    assert!(val.get_mut("b").is_none());
+}
+
+#[test]
+fn pointer() {
+    let val = Value::Map(vec![
+        ( Value::from("a"), Value::Array(vec![
+            Value::Map(vec![( Value::from("b"), Value::from(42) )]),
+        ])),
+        ( Value::from("c/d"), Value::from(1) ),
+        ( Value::from("e~f"), Value::from(2) ),
+        ( Value::from(""), Value::from(3) ),
+        ( Value::from(-7), Value::from(4) ),
+        ( Value::from("8"), Value::from(5) ),
+        ( Value::from(8), Value::from(6) ),
+    ]);
+
+    assert_eq!(Some(&val), val.pointer(""));
+    assert_eq!(Some(42), val.pointer("/a/0/b").and_then(Value::as_u64));
+    assert_eq!(Some(1), val.pointer("/c~1d").and_then(Value::as_u64));
+    assert_eq!(Some(2), val.pointer("/e~0f").and_then(Value::as_u64));
+    assert_eq!(Some(3), val.pointer("/").and_then(Value::as_u64));
+    assert_eq!(Some(4), val.pointer("/-7").and_then(Value::as_u64));
+    // String keys take precedence over integer keys.
+    assert_eq!(Some(5), val.pointer("/8").and_then(Value::as_u64));
+
+    assert_eq!(None, val.pointer("a"));
+    assert_eq!(None, val.pointer("/a/1"));
+    assert_eq!(None, val.pointer("/a/00"));
+    assert_eq!(None, val.pointer("/a/+0"));
+    assert_eq!(None, val.pointer("/a/0/b/c"));
+}
+
+#[test]
+fn pointer_mut() {
+    let mut val = Value::Array(vec![Value::Map(vec![( Value::from(1), Value::Nil )])]);
+    *val.pointer_mut("/0/1").unwrap() = Value::from("one");
+    assert_eq!(Some("one"), val.pointer("/0/1").and_then(Value::as_str));
+    assert!(val.pointer_mut("/1").is_none());
+}
+
+#[test]
+fn insert_keeps_order_and_replaces_duplicates() {
+    let mut val = Value::Map(vec![( Value::from("a"), Value::from(1) )]);
+    assert_eq!(None, val.insert(2, "two"));
+    assert_eq!(None, val.insert("c", Value::Nil));
+    assert_eq!(Some(Value::from(1)), val.insert("a", true));
+
+    assert_eq!(Value::Map(vec![
+        ( Value::from("a"), Value::from(true) ),
+        ( Value::from(2), Value::from("two") ),
+        ( Value::from("c"), Value::Nil ),
+    ]), val);
+}
+
+#[test]
+#[should_panic]
+fn insert_into_non_map() {
+    Value::from(1).insert("a", 1);
+}
+
+#[test]
+fn remove_from_containers() {
+    let mut val = Value::Map(vec![
+        ( Value::from("a"), Value::from(1) ),
+        ( Value::from("b"), Value::Nil ),
+        ( Value::from("c"), Value::from(3) ),
+    ]);
+    assert_eq!(Some(Value::Nil), val.remove("b"));
+    assert_eq!(None, val.remove("b"));
+    assert_eq!(Value::Map(vec![
+        ( Value::from("a"), Value::from(1) ),
+        ( Value::from("c"), Value::from(3) ),
+    ]), val);
+
+    let mut arr = Value::Array(vec![Value::from(1), Value::from(2), Value::from(3)]);
+    assert_eq!(Some(Value::from(1)), arr.remove(0));
+    assert_eq!(None, arr.remove(2));
+    assert_eq!(Value::Array(vec![Value::from(2), Value::from(3)]), arr);
+}
+
+#[test]
+fn entry_api() {
+    use rmpv::Entry;
+
+    let mut val = Value::Nil;
+    val.entry("list").or_insert_with(|| Value::Array(vec![])).as_array_mut().unwrap().push(Value::from(1));
+    val.entry("list").or_insert(Value::Nil).as_array_mut().unwrap().push(Value::from(2));
+    val.entry("n").and_modify(|_| unreachable!()).or_insert(0);
+    val.entry("n").and_modify(|n| *n = Value::from(5));
+
+    match val.entry("n") {
+        Entry::Occupied(entry) => {
+            assert_eq!(&Value::from("n"), entry.key());
+            assert_eq!(Value::from(5), entry.remove());
+        }
+        Entry::Vacant(..) => panic!("unexpected vacant entry"),
+    }
+    match val.entry(1) {
+        Entry::Vacant(entry) => assert_eq!(Value::from(1), entry.into_key()),
+        Entry::Occupied(..) => panic!("unexpected occupied entry"),
+    }
+
+    assert_eq!(Value::Map(vec![
+        ( Value::from("list"), Value::Array(vec![Value::from(1), Value::from(2)]) ),
+    ]), val);
+}
+
+#[test]
+fn index_mut_and_take() {
+    let mut val = Value::Nil;
+    val["a"] = Value::Array(vec![Value::Nil, Value::Nil]);
+    val["a"][1] = Value::from("x");
+    val["b"]["c"] = Value::from(true);
+
+    assert_eq!(Some("x"), val.pointer("/a/1").and_then(Value::as_str));
+    assert_eq!(Some(true), val.pointer("/b/c").and_then(Value::as_bool));
+
+    let taken = val["a"].take();
+    assert_eq!(Value::Array(vec![Value::Nil, Value::from("x")]), taken);
+    assert_eq!(Some(&Value::Nil), val.get("a"));
+}
+
+#[test]
+#[should_panic]
+fn index_mut_out_of_bounds() {
+    let mut val = Value::Array(vec![]);
+    val[0] = Value::Nil;
+}
+
+#[test]
+fn total_order() {
+    let sorted = vec![
+        Value::Nil,
+        Value::from(false),
+        Value::from(true),
+        Value::from(i64::MIN),
+        Value::from(-1),
+        Value::from(0),
+        Value::from(u64::MAX),
+        Value::F32(f32::NEG_INFINITY),
+        Value::F32(1.0),
+        Value::F32(f32::NAN),
+        Value::F64(-0.0),
+        Value::F64(0.0),
+        Value::from(""),
+        Value::from("a"),
+        Value::from("b"),
+        Value::Binary(vec![]),
+        Value::Binary(vec![0]),
+        Value::Array(vec![]),
+        Value::Array(vec![Value::Nil]),
+        Value::Array(vec![Value::Nil, Value::Nil]),
+        Value::Array(vec![Value::from(1)]),
+        Value::Map(vec![]),
+        Value::Map(vec![(Value::from(1), Value::Nil)]),
+        Value::Map(vec![(Value::from(1), Value::from(1))]),
+        Value::Ext(-1, vec![9]),
+        Value::Ext(1, vec![]),
+        Value::Ext(1, vec![0]),
+    ];
+
+    let mut shuffled = sorted.clone();
+    shuffled.reverse();
+    shuffled.swap(3, 17);
+    shuffled.sort();
+    assert_eq!(sorted, shuffled);
+
+    for (i, a) in sorted.iter().enumerate() {
+        for (j, b) in sorted.iter().enumerate() {
+            assert_eq!(i.cmp(&j), a.cmp(b), "{} vs {}", a, b);
+            assert_eq!(i.cmp(&j), a.as_ref().cmp(&b.as_ref()), "{} vs {}", a, b);
+        }
+    }
+}
+
+#[test]
+fn eq_is_structural() {
+    assert_eq!(Value::F64(f64::NAN), Value::F64(f64::NAN));
+    assert_ne!(Value::F64(0.0), Value::F64(-0.0));
+    assert_ne!(Value::from(1), Value::F64(1.0));
+    assert_ne!(Value::F32(1.0), Value::F64(1.0));
+    assert_ne!(
+        Value::Map(vec![(Value::from(1), Value::Nil), (Value::from(2), Value::Nil)]),
+        Value::Map(vec![(Value::from(2), Value::Nil), (Value::from(1), Value::Nil)])
+    );
+}
+
+#[test]
+fn hash_consistent_with_eq() {
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    let keys = [
+        Value::Nil,
+        Value::from(1),
+        Value::F64(1.0),
+        Value::F64(f64::NAN),
+        Value::from("1"),
+        Value::Binary(b"1".to_vec()),
+        Value::Array(vec![Value::from(1)]),
+        Value::Ext(1, vec![1]),
+    ];
+
+    let set: HashSet<_> = keys.iter().cloned().chain(keys.iter().cloned()).collect();
+    assert_eq!(keys.len(), set.len());
+    let set: HashSet<_> = keys.iter().map(Value::as_ref).collect();
+    assert_eq!(keys.len(), set.len());
+
+    let mut map = HashMap::new();
+    map.insert(Value::F64(f64::NAN), "nan");
+    assert_eq!(Some(&"nan"), map.get(&Value::F64(f64::NAN)));
+
+    let map: BTreeMap<_, _> = keys.iter().cloned().zip(0..).collect();
+    assert_eq!(Some(&3), map.get(&Value::F64(f64::NAN)));
+}
+
+#[test]
+fn numeric_eq() {
+    assert!(Value::from(1).numeric_eq(&Value::F32(1.0)));
+    assert!(Value::F64(-1.0).numeric_eq(&Value::from(-1)));
+    assert!(Value::from(i64::MIN).numeric_eq(&Value::F64(-9223372036854775808.0)));
+    assert!(Value::F64(0.0).numeric_eq(&Value::F64(-0.0)));
+    assert!(Value::Array(vec![Value::from(2)]).numeric_eq(&Value::Array(vec![Value::F64(2.0)])));
+    assert!(Value::Map(vec![(Value::F32(1.0), Value::from("a"))])
+        .numeric_eq(&Value::Map(vec![(Value::from(1), Value::from("a"))])));
+
+    // 2^64 is just out of range.
+    assert!(!Value::from(u64::MAX).numeric_eq(&Value::F64(18446744073709551616.0)));
+    assert!(!Value::from(u64::MAX - 1).numeric_eq(&Value::F64(u64::MAX as f64)));
+    assert!(!Value::from(1).numeric_eq(&Value::F64(1.5)));
+    assert!(!Value::from(1).numeric_eq(&Value::F64(f64::INFINITY)));
+    assert!(!Value::F64(f64::NAN).numeric_eq(&Value::F64(f64::NAN)));
+    assert!(!Value::from(1).numeric_eq(&Value::from("1")));
+    assert!(!Value::Array(vec![Value::from(1)]).numeric_eq(&Value::Array(vec![])));
+    assert!(Value::from("1").numeric_eq(&Value::from("1")));
+}
+
+#[test]
+fn value_ref_accessors() {
+    use rmpv::ValueRef;
+
+    let val = Value::Map(vec![
+        ( Value::from("s"), Value::from("text") ),
+        ( Value::from("b"), Value::Binary(vec![1, 2]) ),
+        ( Value::from("n"), Value::from(-3) ),
+        ( Value::from("f"), Value::F32(0.5) ),
+        ( Value::from("a"), Value::Array(vec![Value::Boolean(true), Value::Nil]) ),
+        ( Value::from(7), Value::Ext(4, vec![5]) ),
+    ]);
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+
+    // Everything borrowed from the buffer outlives the decoded value.
+    let (text, bin, ext) = {
+        let val = rmpv::decode::read_value_ref(&mut &buf[..]).unwrap();
+        assert!(val.is_map());
+        assert_eq!(6, val.as_map().unwrap().len());
+        assert_eq!(Some(-3), val.get("n").and_then(ValueRef::as_i64));
+        assert_eq!(None, val.get("n").and_then(ValueRef::as_u64));
+        assert_eq!(Some(0.5), val.get("f").and_then(ValueRef::as_f64));
+        assert!(val.get("f").unwrap().is_f32());
+        assert!(val.pointer("/a/0").unwrap().is_bool());
+        assert!(val.pointer("/a/1").unwrap().is_nil());
+        assert_eq!(None, val.pointer("/a/2"));
+        (
+            val.get("s").unwrap().as_str().unwrap(),
+            val.get("b").unwrap().as_slice().unwrap(),
+            val.pointer("/7").unwrap().as_ext().unwrap(),
+        )
+    };
+    assert_eq!("text", text);
+    assert_eq!(&[1, 2], bin);
+    assert_eq!((4, &[5][..]), ext);
+
+    assert!(ValueRef::from(1).is_number() && ValueRef::from(1).is_i64() && ValueRef::from(1).is_u64());
+    assert!(!ValueRef::Nil.is_str() && !ValueRef::Nil.is_bin() && !ValueRef::Nil.is_ext());
+    assert!(!ValueRef::F64(1.0).is_f32() && ValueRef::F64(1.0).is_f64());
+    assert!(ValueRef::Array(vec![]).is_array());
+    assert_eq!(Some(vec![]), ValueRef::Map(vec![]).into_map());
+}
+
+#[test]
+fn value_ref_get_by_key() {
+    use rmpv::ValueRef;
+
+    let val = Value::Map(vec![
+        ( Value::from("s"), Value::from("text") ),
+        ( Value::from("b"), Value::Binary(vec![1, 2]) ),
+        ( Value::from("t"), Value::Boolean(true) ),
+        ( Value::from(-1), Value::from(-3) ),
+        ( Value::from("f"), Value::F32(0.5) ),
+        ( Value::from("a"), Value::Array(vec![Value::from(10), Value::from(20)]) ),
+        ( Value::from("m"), Value::Map(vec![]) ),
+    ]);
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+
+    // Strings and slices are borrowed from the buffer, so they outlive the decoded value.
+    let (text, bin) = {
+        let val = rmpv::decode::read_value_ref(&mut &buf[..]).unwrap();
+        assert_eq!(Some(true), val.get_bool("t"));
+        assert_eq!(Some(-3), val.get_i64(-1));
+        assert_eq!(None, val.get_u64(-1));
+        assert_eq!(Some(0.5), val.get_f64("f"));
+        assert_eq!(Some(2), val.get_array("a").map(Vec::len));
+        assert_eq!(Some(20), val.get("a").and_then(|a| a.get_u64(1)));
+        assert_eq!(Some(&vec![]), val.get_map("m"));
+        assert_eq!(None, val.get_map("a"));
+        assert_eq!(None, val.get_str("missing"));
+        (val.get_str("s").unwrap(), val.get_slice("b").unwrap())
+    };
+    assert_eq!("text", text);
+    assert_eq!(&[1, 2], bin);
+    assert_eq!(None, ValueRef::from(1).get_bool(0));
+}
+
+#[test]
+fn try_from_val_ref() {
+    use rmpv::ValueRef;
+    use std::convert::TryFrom;
+
+    assert_eq!(Ok(-1), i64::try_from(ValueRef::from(-1)));
+    assert_eq!(Ok(42), u64::try_from(ValueRef::from(42)));
+    assert_eq!(Ok(0.5), f64::try_from(ValueRef::F32(0.5)));
+    assert_eq!(Ok(2.0), f64::try_from(ValueRef::from(2)));
+    assert_eq!(Ok("spook"), <&str>::try_from(ValueRef::from("spook")));
+    assert_eq!(Ok(String::from("spook")), String::try_from(ValueRef::from("spook")));
+    assert_eq!(Err(ValueRef::Nil), <&str>::try_from(ValueRef::Nil));
+    assert_eq!(Err(ValueRef::from(u64::MAX)), i64::try_from(ValueRef::from(u64::MAX)));
+}